@@ -2,14 +2,23 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    CosmosMsg, BankMsg, WasmMsg, from_binary
+    CosmosMsg, BankMsg, WasmMsg, from_binary, Storage, Addr, Event
 };
 use cw2::set_contract_version;
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use escrow_common::history::{query_history, record_transition};
+use escrow_common::validation::{
+    validate_amounts, validate_identifier, validate_minimum_fill, validate_secret_hash, validate_timelock,
+};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, EscrowResponse};
-use crate::state::{EscrowInfo, EscrowStatus, ESCROW_INFO};
+use crate::msg::{
+    CounterpartyParamsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, EscrowResponse, HistoryResponse,
+    PreviewCancelResponse, RefundAsset,
+};
+use crate::state::{
+    EscrowInfo, EscrowStatus, ESCROW_INFO, FACTORY, RESOLVER,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:destination_escrow";
@@ -19,15 +28,28 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let taker = deps.api.addr_validate(&msg.taker)?;
     let maker = deps.api.addr_validate(&msg.maker)?;
+    let claim_addr = msg.claim_addr.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let resolver = msg.resolver.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let allowed_cw20 = msg
+        .allowed_cw20
+        .map(|list| list.iter().map(|a| deps.api.addr_validate(a)).collect::<StdResult<Vec<_>>>())
+        .transpose()?;
+
+    validate_identifier(&msg.src_chain_id)?;
+    validate_timelock(msg.timelock, env.block.time.seconds())?;
+    validate_amounts(msg.expected_amount, None)?;
+    validate_secret_hash(&msg.secret_hash)?;
+    validate_minimum_fill(msg.allow_partial_fill, msg.minimum_fill_amount, msg.expected_amount)?;
 
     let escrow_info = EscrowInfo {
         taker: taker.clone(),
         maker: maker.clone(),
+        claim_addr,
         secret_hash: msg.secret_hash,
         timelock: msg.timelock,
         src_chain_id: msg.src_chain_id,
@@ -35,16 +57,27 @@ pub fn instantiate(
         expected_amount: msg.expected_amount,
         deposited_amount: Uint128::zero(),
         deposited_denom: None,
+        allow_partial_fill: msg.allow_partial_fill,
+        minimum_fill_amount: msg.minimum_fill_amount,
+        filled_amount: Uint128::zero(),
+        remaining_amount: Uint128::zero(), // Will be set when deposit is made
         cw20_contract: None,
+        allowed_cw20,
         status: EscrowStatus::Active,
         created_at: env.block.time.seconds(),
         src_confirmed: false,
         src_tx_hash: None,
         src_block_height: None,
+        hash_algorithm: msg.hash_algorithm.unwrap_or_default(),
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    FACTORY.save(deps.storage, &info.sender)?;
+    if let Some(resolver) = &resolver {
+        RESOLVER.save(deps.storage, resolver)?;
+    }
+    record_transition(deps.storage, &env, None, EscrowStatus::Active, &info.sender)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -64,10 +97,17 @@ pub fn execute(
         ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::Withdraw { secret } => execute_withdraw(deps, env, info, secret),
+        ExecuteMsg::PartialWithdraw { secret, amount } => {
+            execute_partial_withdraw(deps, env, info, secret, amount)
+        }
         ExecuteMsg::Cancel {} => execute_cancel(deps, env, info),
         ExecuteMsg::ConfirmSourceEscrow { src_tx_hash, block_height } => {
             execute_confirm_source_escrow(deps, env, info, src_tx_hash, block_height)
         }
+        ExecuteMsg::UpdateSecretHash { new_secret_hash } => {
+            execute_update_secret_hash(deps, info, new_secret_hash)
+        }
+        ExecuteMsg::Rescue { denom, cw20_contract } => execute_rescue(deps, env, info, denom, cw20_contract),
     }
 }
 
@@ -79,7 +119,9 @@ pub fn execute_deposit(
     let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
     if escrow_info.status != EscrowStatus::Active {
-        return Err(ContractError::AlreadyWithdrawn {});
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
     }
 
     if info.sender != escrow_info.taker {
@@ -97,6 +139,7 @@ pub fn execute_deposit(
 
     escrow_info.deposited_amount = coin.amount;
     escrow_info.deposited_denom = Some(coin.denom.clone());
+    escrow_info.remaining_amount = coin.amount;
 
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
 
@@ -121,19 +164,29 @@ pub fn execute_receive(
             let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
             if escrow_info.status != EscrowStatus::Active {
-                return Err(ContractError::AlreadyWithdrawn {});
+                return Err(ContractError::InvalidState {
+                    current: format!("{:?}", escrow_info.status),
+                });
             }
 
             if sender != escrow_info.taker {
                 return Err(ContractError::Unauthorized {});
             }
 
+            // Only whitelisted CW20 contracts may fund the escrow, when a whitelist is set
+            if let Some(allowed_cw20) = &escrow_info.allowed_cw20 {
+                if !allowed_cw20.contains(&info.sender) {
+                    return Err(ContractError::Cw20NotAllowed {});
+                }
+            }
+
             if amount != escrow_info.expected_amount {
                 return Err(ContractError::InvalidAmount {});
             }
 
             escrow_info.deposited_amount = amount;
             escrow_info.cw20_contract = Some(info.sender);
+            escrow_info.remaining_amount = amount;
 
             ESCROW_INFO.save(deps.storage, &escrow_info)?;
 
@@ -147,7 +200,7 @@ pub fn execute_receive(
 
 pub fn execute_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     secret: String,
 ) -> Result<Response, ContractError> {
@@ -172,50 +225,66 @@ pub fn execute_withdraw(
     }
 
     // Verify secret hash
-    let secret_hash = format!("{:x}", sha2::Sha256::digest(secret.as_bytes()));
-    if secret_hash != escrow_info.secret_hash {
+    if hash_secret(&escrow_info, &secret) != escrow_info.secret_hash {
         return Err(ContractError::InvalidSecret {});
     }
 
+    let withdraw_amount = escrow_info.remaining_amount;
     let mut messages = vec![];
 
-    // Transfer tokens to maker
+    // Transfer tokens to the claim address (or maker)
+    let recipient = escrow_info.claim_addr.as_ref().unwrap_or(&escrow_info.maker);
     if let Some(cw20_contract) = &escrow_info.cw20_contract {
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: cw20_contract.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: escrow_info.maker.to_string(),
-                amount: escrow_info.deposited_amount,
+                recipient: recipient.to_string(),
+                amount: withdraw_amount,
             })?,
             funds: vec![],
         }));
     } else if let Some(denom) = &escrow_info.deposited_denom {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: escrow_info.maker.to_string(),
+            to_address: recipient.to_string(),
             amount: vec![cosmwasm_std::Coin {
                 denom: denom.clone(),
-                amount: escrow_info.deposited_amount,
+                amount: withdraw_amount,
             }],
         }));
     }
 
+    escrow_info.filled_amount += withdraw_amount;
+    escrow_info.remaining_amount = Uint128::zero();
+
+    let from_status = escrow_info.status.clone();
     escrow_info.status = EscrowStatus::Withdrawn;
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    record_transition(deps.storage, &env, Some(from_status), EscrowStatus::Withdrawn, &info.sender)?;
 
     Ok(Response::new()
         .add_messages(messages)
+        .add_event(secret_revealed_event(&secret, &escrow_info.secret_hash))
         .add_attribute("method", "withdraw")
-        .add_attribute("maker", escrow_info.maker)
-        .add_attribute("amount", escrow_info.deposited_amount))
+        .add_attribute("maker", escrow_info.maker.clone())
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", withdraw_amount)
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", escrow_info.secret_hash))
 }
 
-pub fn execute_cancel(
+pub fn execute_partial_withdraw(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    secret: String,
+    amount: Uint128,
 ) -> Result<Response, ContractError> {
     let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
+    if !escrow_info.allow_partial_fill {
+        return Err(ContractError::InvalidPartialFillAmount {});
+    }
+
     if escrow_info.status == EscrowStatus::Withdrawn {
         return Err(ContractError::AlreadyWithdrawn {});
     }
@@ -224,44 +293,150 @@ pub fn execute_cancel(
         return Err(ContractError::AlreadyCancelled {});
     }
 
-    if info.sender != escrow_info.taker {
-        return Err(ContractError::Unauthorized {});
+    // Source escrow must be confirmed
+    if !escrow_info.src_confirmed {
+        return Err(ContractError::SourceEscrowNotConfirmed {});
     }
 
-    if env.block.time.seconds() < escrow_info.timelock {
-        return Err(ContractError::TimelockNotExpired {});
+    if amount.is_zero() || amount > escrow_info.remaining_amount {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    // Releases mirror source fills at the settlement price, so the last one may fall below the minimum
+    if let Some(min_fill) = escrow_info.minimum_fill_amount {
+        if amount < min_fill && amount != escrow_info.remaining_amount {
+            return Err(ContractError::InvalidPartialFillAmount {});
+        }
+    }
+
+    // Verify secret hash
+    if hash_secret(&escrow_info, &secret) != escrow_info.secret_hash {
+        return Err(ContractError::InvalidSecret {});
     }
 
     let mut messages = vec![];
 
-    // Return tokens to taker
+    // Funds always go to the claim address (or maker), so any caller holding the secret may release
+    let recipient = escrow_info.claim_addr.clone().unwrap_or_else(|| escrow_info.maker.clone());
     if let Some(cw20_contract) = &escrow_info.cw20_contract {
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: cw20_contract.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: escrow_info.taker.to_string(),
-                amount: escrow_info.deposited_amount,
+                recipient: recipient.to_string(),
+                amount,
             })?,
             funds: vec![],
         }));
     } else if let Some(denom) = &escrow_info.deposited_denom {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: escrow_info.taker.to_string(),
+            to_address: recipient.to_string(),
             amount: vec![cosmwasm_std::Coin {
                 denom: denom.clone(),
-                amount: escrow_info.deposited_amount,
+                amount,
             }],
         }));
     }
 
+    // Update escrow state
+    escrow_info.filled_amount += amount;
+    escrow_info.remaining_amount -= amount;
+
+    let from_status = escrow_info.status.clone();
+    escrow_info.status = if escrow_info.remaining_amount.is_zero() {
+        EscrowStatus::Withdrawn
+    } else {
+        EscrowStatus::PartiallyFilled
+    };
+
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    if escrow_info.status != from_status {
+        record_transition(deps.storage, &env, Some(from_status), escrow_info.status.clone(), &info.sender)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_event(secret_revealed_event(&secret, &escrow_info.secret_hash))
+        .add_attribute("method", "partial_withdraw")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("remaining", escrow_info.remaining_amount)
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", escrow_info.secret_hash))
+}
+
+pub fn execute_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if escrow_info.status == EscrowStatus::Withdrawn {
+        return Err(ContractError::AlreadyWithdrawn {});
+    }
+
+    if escrow_info.status == EscrowStatus::Cancelled {
+        return Err(ContractError::AlreadyCancelled {});
+    }
+
+    // The factory can unwind an escrow early during an emergency
+    let emergency = FACTORY.may_load(deps.storage)?.map_or(false, |factory| info.sender == factory);
+    let resolver = is_resolver(deps.storage, &info.sender)?;
+
+    if info.sender != escrow_info.taker && !resolver && !emergency {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // An unfunded escrow holds nothing to refund, so its resolver may retire it before the timelock
+    let retiring = resolver && escrow_info.deposited_amount.is_zero();
+    if env.block.time.seconds() < escrow_info.timelock && !emergency && !retiring {
+        return Err(ContractError::TimelockNotExpired {});
+    }
+
+    let return_amount = escrow_info.remaining_amount;
+    let messages = cancel_messages(&escrow_info)?;
+
+    let from_status = escrow_info.status.clone();
     escrow_info.status = EscrowStatus::Cancelled;
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    record_transition(deps.storage, &env, Some(from_status), EscrowStatus::Cancelled, &info.sender)?;
 
     Ok(Response::new()
         .add_messages(messages)
         .add_attribute("method", "cancel")
         .add_attribute("taker", escrow_info.taker)
-        .add_attribute("returned_amount", escrow_info.deposited_amount))
+        .add_attribute("returned_amount", return_amount))
+}
+
+fn is_resolver(storage: &dyn Storage, sender: &Addr) -> StdResult<bool> {
+    Ok(RESOLVER.may_load(storage)?.map_or(false, |resolver| *sender == resolver))
+}
+
+/// Messages a cancel emits, returning the unreleased funds to the taker.
+fn cancel_messages(escrow_info: &EscrowInfo) -> StdResult<Vec<CosmosMsg>> {
+    let return_amount = escrow_info.remaining_amount;
+    let mut messages = vec![];
+
+    // Return remaining tokens to taker
+    if let Some(cw20_contract) = &escrow_info.cw20_contract {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: escrow_info.taker.to_string(),
+                amount: return_amount,
+            })?,
+            funds: vec![],
+        }));
+    } else if let Some(denom) = &escrow_info.deposited_denom {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: escrow_info.taker.to_string(),
+            amount: vec![cosmwasm_std::Coin {
+                denom: denom.clone(),
+                amount: return_amount,
+            }],
+        }));
+    }
+    Ok(messages)
 }
 
 pub fn execute_confirm_source_escrow(
@@ -290,11 +465,138 @@ pub fn execute_confirm_source_escrow(
         .add_attribute("block_height", block_height.to_string()))
 }
 
+pub fn execute_update_secret_hash(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_secret_hash: String,
+) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    // The resolver forwards rotations the maker requests through it
+    if info.sender != escrow_info.maker && !is_resolver(deps.storage, &info.sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // The hashlock can only be rotated before any funds are locked against it
+    if escrow_info.status != EscrowStatus::Active || !escrow_info.deposited_amount.is_zero() {
+        return Err(ContractError::AlreadyFunded {});
+    }
+
+    validate_secret_hash(&new_secret_hash)?;
+    escrow_info.secret_hash = new_secret_hash.clone();
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_secret_hash")
+        .add_attribute("secret_hash", new_secret_hash))
+}
+
+pub fn execute_rescue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+    cw20_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if info.sender != escrow_info.taker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !matches!(escrow_info.status, EscrowStatus::Withdrawn | EscrowStatus::Cancelled) {
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
+    }
+
+    let (msg, asset, amount) = match (denom, cw20_contract) {
+        (Some(denom), None) => {
+            if escrow_info.deposited_denom.as_deref() == Some(denom.as_str()) {
+                return Err(ContractError::TrackedAssetNotRescuable {});
+            }
+            let balance = deps.querier.query_balance(&env.contract.address, &denom)?;
+            let amount = balance.amount;
+            let msg = CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![balance],
+            });
+            (msg, denom, amount)
+        }
+        (None, Some(cw20_contract)) => {
+            let cw20_addr = deps.api.addr_validate(&cw20_contract)?;
+            if escrow_info.cw20_contract.as_ref() == Some(&cw20_addr) {
+                return Err(ContractError::TrackedAssetNotRescuable {});
+            }
+            let balance: BalanceResponse = deps.querier.query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+            )?;
+            let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: cw20_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: balance.balance,
+                })?,
+                funds: vec![],
+            });
+            (msg, cw20_addr.to_string(), balance.balance)
+        }
+        _ => return Err(ContractError::InvalidRescueTarget {}),
+    };
+
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "rescue")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("asset", asset)
+        .add_attribute("amount", amount))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Escrow {} => to_binary(&query_escrow(deps)?),
+        QueryMsg::History { start_after, limit } => {
+            let history: HistoryResponse = query_history(deps.storage, start_after, limit)?;
+            to_binary(&history)
+        }
+        QueryMsg::PreviewCancel {} => to_binary(&query_preview_cancel(deps, env)?),
+        QueryMsg::CounterpartyParams {} => to_binary(&query_counterparty_params(deps)?),
+    }
+}
+
+fn query_preview_cancel(deps: Deps, env: Env) -> StdResult<PreviewCancelResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let settled = matches!(escrow_info.status, EscrowStatus::Withdrawn | EscrowStatus::Cancelled);
+    if settled || env.block.time.seconds() < escrow_info.timelock {
+        return Ok(PreviewCancelResponse {
+            cancellable: false,
+            recipient: escrow_info.taker,
+            amount: Uint128::zero(),
+            asset: None,
+            messages: vec![],
+        });
     }
+
+    let asset = if escrow_info.deposited_amount.is_zero() {
+        None
+    } else if let Some(contract) = &escrow_info.cw20_contract {
+        Some(RefundAsset::Cw20 { contract: contract.clone() })
+    } else {
+        escrow_info.deposited_denom.clone().map(|denom| RefundAsset::Native { denom })
+    };
+    Ok(PreviewCancelResponse {
+        cancellable: true,
+        messages: cancel_messages(&escrow_info)?,
+        recipient: escrow_info.taker,
+        amount: escrow_info.remaining_amount,
+        asset,
+    })
 }
 
 fn query_escrow(deps: Deps) -> StdResult<EscrowResponse> {
@@ -302,6 +604,7 @@ fn query_escrow(deps: Deps) -> StdResult<EscrowResponse> {
     Ok(EscrowResponse {
         taker: escrow_info.taker,
         maker: escrow_info.maker,
+        claim_addr: escrow_info.claim_addr,
         secret_hash: escrow_info.secret_hash,
         timelock: escrow_info.timelock,
         src_chain_id: escrow_info.src_chain_id,
@@ -309,12 +612,380 @@ fn query_escrow(deps: Deps) -> StdResult<EscrowResponse> {
         expected_amount: escrow_info.expected_amount,
         deposited_amount: escrow_info.deposited_amount,
         deposited_denom: escrow_info.deposited_denom,
+        allow_partial_fill: escrow_info.allow_partial_fill,
+        minimum_fill_amount: escrow_info.minimum_fill_amount,
+        filled_amount: escrow_info.filled_amount,
+        remaining_amount: escrow_info.remaining_amount,
         cw20_contract: escrow_info.cw20_contract,
+        allowed_cw20: escrow_info.allowed_cw20,
         status: escrow_info.status,
         created_at: escrow_info.created_at,
         src_confirmed: escrow_info.src_confirmed,
         src_tx_hash: escrow_info.src_tx_hash,
         src_block_height: escrow_info.src_block_height,
+        hash_algorithm: escrow_info.hash_algorithm,
     })
 }
 
+fn query_counterparty_params(deps: Deps) -> StdResult<CounterpartyParamsResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let recipient = deps.api.addr_canonicalize(escrow_info.taker.as_str())?;
+    Ok(CounterpartyParamsResponse {
+        chain_id: escrow_info.src_chain_id,
+        escrow: escrow_info.src_escrow_address,
+        secret_hash: format!("0x{}", escrow_info.secret_hash),
+        timelock: format!("0x{:064x}", escrow_info.timelock),
+        amount: format!("0x{:064x}", escrow_info.expected_amount.u128()),
+        recipient: format!(
+            "0x{}",
+            recipient.as_slice().iter().map(|b| format!("{:02x}", b)).collect::<String>()
+        ),
+    })
+}
+
+/// Dedicated event relayers filter on to pick up a secret revealed by a withdrawal.
+fn secret_revealed_event(secret: &str, secret_hash: &str) -> Event {
+    Event::new("secret_revealed")
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", secret_hash)
+}
+
+/// Lowercase hex hash of a secret under the escrow's algorithm, comparable to the stored `secret_hash`
+fn hash_secret(escrow_info: &EscrowInfo, secret: &str) -> String {
+    escrow_info
+        .hash_algorithm
+        .digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coin, coins};
+    use escrow_common::hash::HashAlgo;
+
+    // sha256("secret")
+    const SECRET_HASH: &str = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            taker: "taker".to_string(),
+            maker: "maker".to_string(),
+            secret_hash: SECRET_HASH.to_string(),
+            timelock: 1000,
+            src_chain_id: "osmosis-1".to_string(),
+            src_escrow_address: "src_escrow".to_string(),
+            expected_amount: Uint128::from(100u128),
+            allow_partial_fill: true,
+            minimum_fill_amount: None,
+            claim_addr: None,
+            allowed_cw20: None,
+            hash_algorithm: None,
+            resolver: None,
+        }
+    }
+
+    #[test]
+    fn cancel_after_partial_release_refunds_unfilled_portion() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+            .unwrap();
+
+        let res = execute_partial_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            "secret".to_string(),
+            Uint128::from(60u128),
+        )
+        .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: coins(60, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+
+        // mock_env block time is past the timelock
+        let res = execute_cancel(deps.as_mut(), mock_env(), mock_info("taker", &[])).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: coins(40, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn resolver_retires_unfunded_escrow_before_timelock() {
+        let msg = InstantiateMsg {
+            timelock: mock_env().block.time.seconds() + 1000,
+            resolver: Some("resolver".to_string()),
+            ..instantiate_msg()
+        };
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), msg.clone()).unwrap();
+        let err = execute_cancel(deps.as_mut(), mock_env(), mock_info("stranger", &[])).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute_cancel(deps.as_mut(), mock_env(), mock_info("resolver", &[])).unwrap();
+        assert_eq!(EscrowStatus::Cancelled, query_escrow(deps.as_ref()).unwrap().status);
+
+        // Once funded, the resolver waits for the timelock like the taker
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        let err = execute_cancel(deps.as_mut(), mock_env(), mock_info("resolver", &[])).unwrap_err();
+        assert!(matches!(err, ContractError::TimelockNotExpired {}));
+    }
+
+    #[test]
+    fn counterparty_params_are_evm_encoded() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            timelock: mock_env().block.time.seconds() + 1000,
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CounterpartyParams {}).unwrap();
+        let params: CounterpartyParamsResponse = from_binary(&res).unwrap();
+        assert_eq!(format!("0x{}", SECRET_HASH), params.secret_hash);
+        assert_eq!(format!("0x{:064x}", mock_env().block.time.seconds() + 1000), params.timelock);
+        assert_eq!(
+            "0x0000000000000000000000000000000000000000000000000000000000000064",
+            params.amount
+        );
+        let taker = deps.api.addr_canonicalize("taker").unwrap();
+        assert_eq!(2 + 2 * taker.len(), params.recipient.len());
+        assert_eq!("osmosis-1", params.chain_id);
+        assert_eq!("src_escrow", params.escrow);
+    }
+
+    #[test]
+    fn deposit_into_cancelled_escrow_reports_state() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        execute_cancel(deps.as_mut(), mock_env(), mock_info("taker", &[])).unwrap();
+
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidState { current } if current == "Cancelled"));
+    }
+
+    #[test]
+    fn keccak_hashlock_requires_keccak_mode() {
+        // keccak256("secret")
+        let keccak_hash = "65462b0520ef7d3df61b9992ed3bea0c56ead753be7c8b3614e0ce01e4cac41b";
+        for (hash_algorithm, unlocks) in [(None, false), (Some(HashAlgo::Keccak256), true)] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                secret_hash: keccak_hash.to_string(),
+                hash_algorithm,
+                ..instantiate_msg()
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+            execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+                .unwrap();
+
+            let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("maker", &[]), "secret".to_string());
+            if unlocks {
+                res.unwrap();
+            } else {
+                assert!(matches!(res.unwrap_err(), ContractError::InvalidSecret {}));
+            }
+        }
+    }
+
+    #[test]
+    fn preview_cancel_matches_actual_cancel() {
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        for partial_fill in [None, Some(40u128)] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                timelock: mock_env().block.time.seconds() + 1000,
+                ..instantiate_msg()
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+            if let Some(amount) = partial_fill {
+                execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+                    .unwrap();
+                execute_partial_withdraw(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info("relayer", &[]),
+                    "secret".to_string(),
+                    Uint128::from(amount),
+                )
+                .unwrap();
+            }
+
+            let early: PreviewCancelResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PreviewCancel {}).unwrap()).unwrap();
+            assert!(!early.cancellable);
+
+            let preview: PreviewCancelResponse =
+                from_binary(&query(deps.as_ref(), expired.clone(), QueryMsg::PreviewCancel {}).unwrap()).unwrap();
+            assert!(preview.cancellable);
+            assert_eq!("taker", preview.recipient);
+            assert_eq!(Uint128::from(100 - partial_fill.unwrap_or(0)), preview.amount);
+            assert_eq!(Some(RefundAsset::Native { denom: "uatom".to_string() }), preview.asset);
+
+            let res = execute_cancel(deps.as_mut(), expired.clone(), mock_info("taker", &[])).unwrap();
+            let sent: Vec<CosmosMsg> = res.messages.into_iter().map(|sub_msg| sub_msg.msg).collect();
+            assert_eq!(preview.messages, sent);
+            assert!(res.attributes.contains(&cosmwasm_std::attr("returned_amount", preview.amount)));
+        }
+    }
+
+    #[test]
+    fn withdrawals_emit_secret_revealed_event() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+            .unwrap();
+
+        let revealed = Event::new("secret_revealed")
+            .add_attribute("secret", "secret")
+            .add_attribute("secret_hash", SECRET_HASH);
+        let res = execute_partial_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            "secret".to_string(),
+            Uint128::from(60u128),
+        )
+        .unwrap();
+        assert_eq!(vec![revealed.clone()], res.events);
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("maker", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(vec![revealed], res.events);
+        assert!(res.attributes.contains(&cosmwasm_std::attr("secret", "secret")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("secret_hash", SECRET_HASH)));
+    }
+
+
+    #[test]
+    fn malformed_secret_hash_rejected_at_instantiate() {
+        for secret_hash in [
+            SECRET_HASH.to_uppercase(),
+            format!("0x{}", SECRET_HASH),
+            SECRET_HASH[..63].to_string(),
+        ] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                secret_hash,
+                ..instantiate_msg()
+            };
+            let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidSecretHash {}));
+        }
+    }
+
+    #[test]
+    fn partial_withdrawals_drain_the_escrow() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            minimum_fill_amount: Some(Uint128::from(30u128)),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+            .unwrap();
+        let release = |deps: DepsMut, amount: u128| {
+            execute_partial_withdraw(deps, mock_env(), mock_info("relayer", &[]), "secret".to_string(), amount.into())
+        };
+
+        let err = release(deps.as_mut(), 20).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPartialFillAmount {}));
+
+        release(deps.as_mut(), 80).unwrap();
+        let escrow = query_escrow(deps.as_ref()).unwrap();
+        assert_eq!(EscrowStatus::PartiallyFilled, escrow.status);
+        assert_eq!(Uint128::from(80u128), escrow.filled_amount);
+        assert_eq!(Uint128::from(20u128), escrow.remaining_amount);
+
+        // The final release clears the remainder even below the minimum
+        let res = release(deps.as_mut(), 20).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: coins(20, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+        let escrow = query_escrow(deps.as_ref()).unwrap();
+        assert_eq!(EscrowStatus::Withdrawn, escrow.status);
+        assert!(escrow.remaining_amount.is_zero());
+        let err = release(deps.as_mut(), 1).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyWithdrawn {}));
+    }
+
+    #[test]
+    fn partial_withdraw_requires_opt_in() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            allow_partial_fill: false,
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        execute_confirm_source_escrow(deps.as_mut(), mock_env(), mock_info("relayer", &[]), "tx".to_string(), 1)
+            .unwrap();
+
+        let err = execute_partial_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            "secret".to_string(),
+            Uint128::from(50u128),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPartialFillAmount {}));
+    }
+
+    #[test]
+    fn rescue_sweeps_stray_denom_after_settlement() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("taker", &coins(100, "uatom"))).unwrap();
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![coin(100, "uatom"), coin(5, "ujuno")]);
+
+        let rescue = |denom: &str| ExecuteMsg::Rescue { denom: Some(denom.to_string()), cw20_contract: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), rescue("ujuno")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidState { .. }));
+
+        // mock_env block time is past the timelock
+        execute_cancel(deps.as_mut(), mock_env(), mock_info("taker", &[])).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), rescue("ujuno")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), rescue("uatom")).unwrap_err();
+        assert!(matches!(err, ContractError::TrackedAssetNotRescuable {}));
+        let both = ExecuteMsg::Rescue { denom: Some("ujuno".to_string()), cw20_contract: Some("token".to_string()) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), both).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRescueTarget {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), rescue("ujuno")).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: coins(5, "ujuno"),
+            }),
+            res.messages[0].msg
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use escrow_common::validation::ValidationError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,6 +10,12 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Escrow already funded")]
+    AlreadyFunded {},
+
+    #[error("CW20 contract not allowed")]
+    Cw20NotAllowed {},
+
     #[error("Invalid secret")]
     InvalidSecret {},
 
@@ -29,5 +36,43 @@ pub enum ContractError {
 
     #[error("Source escrow not confirmed")]
     SourceEscrowNotConfirmed {},
+
+    #[error("Escrow is {current} and cannot take this action")]
+    InvalidState { current: String },
+
+    #[error("Invalid chain or asset identifier")]
+    InvalidIdentifier {},
+
+    #[error("Invalid timelock")]
+    InvalidTimelock {},
+
+    #[error("Invalid secret hash")]
+    InvalidSecretHash {},
+
+    #[error("Invalid partial fill amount")]
+    InvalidPartialFillAmount {},
+
+    #[error("Minimum fill amount exceeds the order amount")]
+    MinimumFillTooLarge {},
+
+    #[error("Set exactly one of denom or cw20_contract to rescue")]
+    InvalidRescueTarget {},
+
+    #[error("The escrow's tracked asset cannot be rescued")]
+    TrackedAssetNotRescuable {},
 }
 
+impl From<ValidationError> for ContractError {
+    fn from(err: ValidationError) -> Self {
+        match err {
+            ValidationError::InvalidTimelock {} => ContractError::InvalidTimelock {},
+            ValidationError::InvalidIdentifier {} => ContractError::InvalidIdentifier {},
+            ValidationError::InvalidSecretHash {} => ContractError::InvalidSecretHash {},
+            ValidationError::MinimumFillTooLarge {} => ContractError::MinimumFillTooLarge {},
+            // Destination escrows carry no auction of their own
+            ValidationError::InvalidAuctionParams {} | ValidationError::InvalidAmount {} => {
+                ContractError::InvalidAmount {}
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, CosmosMsg, Uint128};
 use cw20::Cw20ReceiveMsg;
+use escrow_common::hash::HashAlgo;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -11,6 +12,18 @@ pub struct InstantiateMsg {
     pub src_chain_id: String,
     pub src_escrow_address: String,
     pub expected_amount: Uint128,
+    /// Release the deposit over several `PartialWithdraw` calls instead of one `Withdraw`
+    pub allow_partial_fill: bool,
+    /// Smallest partial release accepted; the final release may be smaller
+    pub minimum_fill_amount: Option<Uint128>,
+    /// Address paid on withdraw; defaults to the maker
+    pub claim_addr: Option<String>,
+    /// CW20 contracts accepted for deposits; any when unset
+    pub allowed_cw20: Option<Vec<String>>,
+    /// Hash applied to the secret; sha256 when unset
+    pub hash_algorithm: Option<HashAlgo>,
+    /// Resolver that ordered the escrow; it may cancel the escrow alongside the taker
+    pub resolver: Option<String>,
 }
 
 #[cw_serde]
@@ -21,13 +34,25 @@ pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     /// Withdraw tokens using the secret (for maker)
     Withdraw { secret: String },
-    /// Cancel the escrow after timelock expires (for taker)
+    /// Release part of the deposit to the maker using the secret
+    PartialWithdraw {
+        secret: String,
+        amount: Uint128,
+    },
+    /// Cancel the escrow after timelock expires (for taker); the factory may cancel at any time, the resolver while unfunded
     Cancel {},
     /// Confirm source escrow (called by relayer)
     ConfirmSourceEscrow { 
         src_tx_hash: String,
         block_height: u64,
     },
+    /// Rotate the hashlock before the escrow is funded (maker, or the resolver on the maker's behalf)
+    UpdateSecretHash { new_secret_hash: String },
+    /// Sweep a stray native denom or CW20 balance to the taker once the escrow is settled (taker only)
+    Rescue {
+        denom: Option<String>,
+        cw20_contract: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -42,12 +67,25 @@ pub enum QueryMsg {
     /// Get escrow details
     #[returns(EscrowResponse)]
     Escrow {},
+    /// Get the status transition history of this escrow
+    #[returns(HistoryResponse)]
+    History {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The messages a cancel would emit at the current block, without cancelling
+    #[returns(PreviewCancelResponse)]
+    PreviewCancel {},
+    /// Parameters for checking the source HTLC on an EVM chain against this escrow
+    #[returns(CounterpartyParamsResponse)]
+    CounterpartyParams {},
 }
 
 #[cw_serde]
 pub struct EscrowResponse {
     pub taker: Addr,
     pub maker: Addr,
+    pub claim_addr: Option<Addr>,
     pub secret_hash: String,
     pub timelock: u64,
     pub src_chain_id: String,
@@ -55,18 +93,61 @@ pub struct EscrowResponse {
     pub expected_amount: Uint128,
     pub deposited_amount: Uint128,
     pub deposited_denom: Option<String>,
+    pub allow_partial_fill: bool,
+    pub minimum_fill_amount: Option<Uint128>,
+    pub filled_amount: Uint128,
+    pub remaining_amount: Uint128,
     pub cw20_contract: Option<Addr>,
+    pub allowed_cw20: Option<Vec<Addr>>,
     pub status: EscrowStatus,
     pub created_at: u64,
     pub src_confirmed: bool,
     pub src_tx_hash: Option<String>,
     pub src_block_height: Option<u64>,
+    pub hash_algorithm: HashAlgo,
+}
+
+#[cw_serde]
+pub struct PreviewCancelResponse {
+    /// False while the timelock is running or once the escrow is settled
+    pub cancellable: bool,
+    pub recipient: Addr,
+    /// Unreleased funds returned to `recipient`; zero when not cancellable
+    pub amount: Uint128,
+    /// Asset the funds are refunded in; `None` when nothing would be refunded
+    pub asset: Option<RefundAsset>,
+    /// Messages the cancel would emit, in order
+    pub messages: Vec<CosmosMsg>,
+}
+
+/// Counterparty HTLC parameters as 0x-prefixed hex, with numbers as 32-byte uint256 words.
+#[cw_serde]
+pub struct CounterpartyParamsResponse {
+    pub chain_id: String,
+    /// Source escrow on the counterparty chain, as recorded at instantiation
+    pub escrow: String,
+    pub secret_hash: String,
+    /// This escrow's timelock; the counterparty HTLC must stay locked past it
+    pub timelock: String,
+    pub amount: String,
+    /// Taker's canonical address bytes; matches the EVM address on Ethermint chains
+    pub recipient: String,
 }
 
+#[cw_serde]
+pub enum RefundAsset {
+    Native { denom: String },
+    Cw20 { contract: Addr },
+}
+
+/// Status history of the escrow, shared with the other escrow contracts
+pub type HistoryResponse = escrow_common::history::HistoryResponse<crate::state::EscrowStatus>;
+
 #[cw_serde]
 pub enum EscrowStatus {
     Active,
     Withdrawn,
     Cancelled,
+    PartiallyFilled,
 }
 
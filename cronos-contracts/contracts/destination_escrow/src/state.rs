@@ -1,5 +1,6 @@
 use cosmwasm_std::{Addr, Uint128};
 use cw_storage_plus::Item;
+use escrow_common::hash::HashAlgo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct EscrowInfo {
     pub taker: Addr,
     pub maker: Addr,
+    pub claim_addr: Option<Addr>,
     pub secret_hash: String,
     pub timelock: u64,
     pub src_chain_id: String,
@@ -14,12 +16,18 @@ pub struct EscrowInfo {
     pub expected_amount: Uint128,
     pub deposited_amount: Uint128,
     pub deposited_denom: Option<String>,
+    pub allow_partial_fill: bool,
+    pub minimum_fill_amount: Option<Uint128>,
+    pub filled_amount: Uint128,
+    pub remaining_amount: Uint128,
     pub cw20_contract: Option<Addr>,
+    pub allowed_cw20: Option<Vec<Addr>>,
     pub status: EscrowStatus,
     pub created_at: u64,
     pub src_confirmed: bool,
     pub src_tx_hash: Option<String>,
     pub src_block_height: Option<u64>,
+    pub hash_algorithm: HashAlgo,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -27,7 +35,12 @@ pub enum EscrowStatus {
     Active,
     Withdrawn,
     Cancelled,
+    PartiallyFilled,
 }
 
 pub const ESCROW_INFO: Item<EscrowInfo> = Item::new("escrow_info");
+/// Contract that instantiated the escrow; it may cancel before the timelock in an emergency
+pub const FACTORY: Item<Addr> = Item::new("factory");
+/// Resolver that ordered the escrow through the factory, when it was given one
+pub const RESOLVER: Item<Addr> = Item::new("resolver");
 
@@ -1,43 +1,42 @@
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128, BankMsg, CosmosMsg, coin
+    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128, BankMsg, CosmosMsg, coin, Order
 };
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Auction, AUCTION};
+use crate::msg::{
+    AuctionHistoryResponse, AuctionListResponse, AuctionResponse, AuctionStatus, BidInfo, ExecuteMsg,
+    InstantiateMsg, PriceResponse, QueryMsg,
+};
+use crate::state::{Auction, Config, AUCTIONS, AUCTION_BIDS, AUCTION_BID_COUNT, CONFIG};
 
-const CONTRACT_NAME: &str = "dutch-auction-simple";
+const CONTRACT_NAME: &str = "crates.io:dutch_auction";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Denom bids are paid in
+const BID_DENOM: &str = "uatom";
+
+// Page size bounds for auction and bid listings
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
+    _env: Env,
+    _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let auction = Auction {
-        seller: info.sender.clone(),
-        start_price: msg.start_price,
-        end_price: msg.end_price,
-        start_time: env.block.time.seconds(),
-        end_time: env.block.time.seconds() + msg.duration,
-        current_bidder: None,
-        current_bid: Uint128::zero(),
-        is_active: true,
-    };
-
-    AUCTION.save(deps.storage, &auction)?;
+    let owner = deps.api.addr_validate(&msg.owner)?;
+    CONFIG.save(deps.storage, &Config { owner: owner.clone() })?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("seller", info.sender)
-        .add_attribute("start_price", msg.start_price)
-        .add_attribute("end_price", msg.end_price))
+        .add_attribute("owner", owner))
 }
 
 #[entry_point]
@@ -48,102 +47,694 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Bid {} => execute_bid(deps, env, info),
-        ExecuteMsg::EndAuction {} => execute_end_auction(deps, env, info),
+        ExecuteMsg::CreateAuction {
+            auction_id,
+            seller,
+            asset,
+            amount,
+            initial_price,
+            minimum_price,
+            price_decay_rate,
+            duration,
+            bid_cutoff,
+            escrow_address,
+        } => execute_create_auction(
+            deps,
+            env,
+            info,
+            auction_id,
+            seller,
+            asset,
+            amount,
+            initial_price,
+            minimum_price,
+            price_decay_rate,
+            duration,
+            bid_cutoff,
+            escrow_address,
+        ),
+        ExecuteMsg::PlaceBid { auction_id, bidder, bid_amount } => {
+            execute_place_bid(deps, env, info, auction_id, bidder, bid_amount)
+        }
+        ExecuteMsg::UpdatePrice { auction_id } => execute_update_price(deps, env, auction_id),
+        ExecuteMsg::EndAuction { auction_id } => execute_end_auction(deps, env, info, auction_id),
+        ExecuteMsg::Settle { auction_id } => execute_settle(deps, env, info, auction_id),
+        ExecuteMsg::CancelAuction { auction_id } => execute_cancel_auction(deps, info, auction_id),
+        ExecuteMsg::UpdateOwner { new_owner } => execute_update_owner(deps, info, new_owner),
     }
 }
 
-pub fn execute_bid(
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_auction(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    auction_id: String,
+    seller: String,
+    asset: String,
+    amount: Uint128,
+    initial_price: Uint128,
+    minimum_price: Uint128,
+    price_decay_rate: Uint128,
+    duration: u64,
+    bid_cutoff: Option<u64>,
+    escrow_address: Option<String>,
 ) -> Result<Response, ContractError> {
-    let mut auction = AUCTION.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let seller = deps.api.addr_validate(&seller)?;
 
-    if !auction.is_active {
-        return Err(ContractError::AuctionNotActive {});
+    // Sellers list their own auctions; the owner may list on a seller's behalf
+    if info.sender != seller && info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    if env.block.time.seconds() > auction.end_time {
-        return Err(ContractError::AuctionEnded {});
+    let bid_cutoff = bid_cutoff.unwrap_or(0);
+    if auction_id.is_empty()
+        || amount.is_zero()
+        || duration == 0
+        || bid_cutoff >= duration
+        || initial_price < minimum_price
+    {
+        return Err(ContractError::InvalidAuctionParameters {});
+    }
+    if AUCTIONS.has(deps.storage, auction_id.clone()) {
+        return Err(ContractError::InvalidAuctionParameters {});
     }
 
-    let current_price = auction.get_current_price(env.block.time.seconds());
-    let bid_amount = info.funds.iter().find(|c| c.denom == "uatom")
+    let escrow_address = escrow_address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let start_time = env.block.time.seconds();
+    let auction = Auction {
+        auction_id: auction_id.clone(),
+        seller: seller.clone(),
+        asset,
+        amount,
+        initial_price,
+        minimum_price,
+        current_price: initial_price,
+        price_decay_rate,
+        start_time,
+        end_time: start_time + duration,
+        duration,
+        bid_cutoff,
+        status: AuctionStatus::Active,
+        winner: None,
+        winning_bid: None,
+        escrow_address,
+    };
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_auction")
+        .add_attribute("auction_id", auction_id)
+        .add_attribute("seller", seller)
+        .add_attribute("initial_price", initial_price)
+        .add_attribute("minimum_price", minimum_price))
+}
+
+pub fn execute_place_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: String,
+    bidder: String,
+    bid_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.as_ref(), &auction_id)?;
+    let now = env.block.time.seconds();
+    ensure_bidding_open(&auction, now)?;
+
+    let bidder = deps.api.addr_validate(&bidder)?;
+    let paid = info
+        .funds
+        .iter()
+        .find(|c| c.denom == BID_DENOM)
         .map(|c| c.amount)
         .unwrap_or_else(Uint128::zero);
+    if bid_amount.is_zero() || paid != bid_amount {
+        return Err(ContractError::InvalidBidAmount {});
+    }
 
+    let current_price = live_price(&auction, now);
     if bid_amount < current_price {
+        return Err(ContractError::InvalidBidAmount {});
+    }
+
+    // First accepted bid wins ties; a later bid only displaces it by strictly outbidding
+    if auction.winning_bid.map_or(false, |winning| bid_amount <= winning) {
         return Err(ContractError::BidTooLow {});
     }
 
     let mut response = Response::new();
 
-    // Refund previous bidder
-    if let Some(prev_bidder) = &auction.current_bidder {
-        let refund_msg = BankMsg::Send {
+    // Refund the outbid bidder
+    if let (Some(prev_bidder), Some(prev_bid)) = (&auction.winner, auction.winning_bid) {
+        response = response.add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: prev_bidder.to_string(),
-            amount: vec![coin(auction.current_bid.u128(), "uatom")],
-        };
-        response = response.add_message(CosmosMsg::Bank(refund_msg));
+            amount: vec![coin(prev_bid.u128(), BID_DENOM)],
+        }));
     }
 
-    auction.current_bidder = Some(info.sender.clone());
-    auction.current_bid = bid_amount;
-    AUCTION.save(deps.storage, &auction)?;
+    auction.current_price = current_price;
+    auction.winner = Some(bidder.clone());
+    auction.winning_bid = Some(bid_amount);
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
+
+    let bid_index = AUCTION_BID_COUNT.may_load(deps.storage, auction_id.clone())?.unwrap_or(0);
+    AUCTION_BIDS.save(
+        deps.storage,
+        (auction_id.clone(), bid_index),
+        &BidInfo {
+            bidder: bidder.clone(),
+            amount: bid_amount,
+            timestamp: now,
+            price_at_bid: current_price,
+        },
+    )?;
+    AUCTION_BID_COUNT.save(deps.storage, auction_id.clone(), &(bid_index + 1))?;
 
     Ok(response
-        .add_attribute("method", "bid")
-        .add_attribute("bidder", info.sender)
-        .add_attribute("amount", bid_amount))
+        .add_attribute("method", "place_bid")
+        .add_attribute("auction_id", auction_id)
+        .add_attribute("bidder", bidder)
+        .add_attribute("amount", bid_amount)
+        .add_attribute("price", current_price))
+}
+
+/// Stores the decayed price so listings read without recomputing (permissionless)
+pub fn execute_update_price(
+    deps: DepsMut,
+    env: Env,
+    auction_id: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.as_ref(), &auction_id)?;
+
+    if auction.status != AuctionStatus::Active {
+        return Err(ContractError::AuctionEnded {});
+    }
+
+    let current_price = live_price(&auction, env.block.time.seconds());
+    auction.current_price = current_price;
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_price")
+        .add_attribute("auction_id", auction_id)
+        .add_attribute("current_price", current_price))
 }
 
 pub fn execute_end_auction(
     deps: DepsMut,
     env: Env,
     _info: MessageInfo,
+    auction_id: String,
 ) -> Result<Response, ContractError> {
-    let mut auction = AUCTION.load(deps.storage)?;
+    let mut auction = load_auction(deps.as_ref(), &auction_id)?;
 
-    if !auction.is_active {
-        return Err(ContractError::AuctionNotActive {});
+    if auction.status != AuctionStatus::Active {
+        return Err(ContractError::AuctionEnded {});
     }
 
     if env.block.time.seconds() < auction.end_time {
+        return Err(ContractError::AuctionStillActive {});
+    }
+
+    auction.status = AuctionStatus::Ended;
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
+
+    Ok(close_auction(&auction)
+        .add_attribute("method", "end_auction")
+        .add_attribute("auction_id", auction_id)
+        .add_attribute("winner", auction.winner.map(|w| w.to_string()).unwrap_or_default())
+        .add_attribute("winning_bid", auction.winning_bid.unwrap_or_default()))
+}
+
+/// Permissionless close once the price has decayed to the floor, without waiting for `end_time`
+pub fn execute_settle(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    auction_id: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.as_ref(), &auction_id)?;
+
+    if auction.status != AuctionStatus::Active {
         return Err(ContractError::AuctionEnded {});
     }
 
-    auction.is_active = false;
-    AUCTION.save(deps.storage, &auction)?;
+    let current_price = live_price(&auction, env.block.time.seconds());
+    if current_price > auction.minimum_price {
+        return Err(ContractError::PriceAboveFloor {});
+    }
+
+    auction.current_price = current_price;
+    auction.status = AuctionStatus::Ended;
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
+
+    // A resting bid is accepted at the floor; otherwise the auction simply closes
+    Ok(close_auction(&auction)
+        .add_attribute("method", "settle")
+        .add_attribute("auction_id", auction_id)
+        .add_attribute("winner", auction.winner.map(|w| w.to_string()).unwrap_or_default())
+        .add_attribute("winning_bid", auction.winning_bid.unwrap_or_default()))
+}
+
+pub fn execute_cancel_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: String,
+) -> Result<Response, ContractError> {
+    let mut auction = load_auction(deps.as_ref(), &auction_id)?;
+
+    if info.sender != auction.seller {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if auction.status != AuctionStatus::Active {
+        return Err(ContractError::AuctionEnded {});
+    }
+
+    auction.status = AuctionStatus::Cancelled;
+    AUCTIONS.save(deps.storage, auction_id.clone(), &auction)?;
 
     let mut response = Response::new();
 
-    if let Some(winner) = &auction.current_bidder {
-        // Send funds to seller
-        let payment_msg = BankMsg::Send {
-            to_address: auction.seller.to_string(),
-            amount: vec![coin(auction.current_bid.u128(), "uatom")],
-        };
-        response = response.add_message(CosmosMsg::Bank(payment_msg));
+    // Refund the resting bid
+    if let (Some(bidder), Some(bid)) = (&auction.winner, auction.winning_bid) {
+        response = response.add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: bidder.to_string(),
+            amount: vec![coin(bid.u128(), BID_DENOM)],
+        }));
     }
 
     Ok(response
-        .add_attribute("method", "end_auction")
-        .add_attribute("winner", auction.current_bidder.unwrap_or_default())
-        .add_attribute("winning_bid", auction.current_bid))
+        .add_attribute("method", "cancel_auction")
+        .add_attribute("auction_id", auction_id))
+}
+
+pub fn execute_update_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    config.owner = new_owner.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_owner")
+        .add_attribute("new_owner", new_owner))
+}
+
+/// Pays the winning bid, if any, to the seller
+fn close_auction(auction: &Auction) -> Response {
+    let mut response = Response::new();
+    if let (Some(_), Some(bid)) = (&auction.winner, auction.winning_bid) {
+        response = response.add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: auction.seller.to_string(),
+            amount: vec![coin(bid.u128(), BID_DENOM)],
+        }));
+    }
+    response
+}
+
+fn load_auction(deps: Deps, auction_id: &str) -> Result<Auction, ContractError> {
+    AUCTIONS
+        .may_load(deps.storage, auction_id.to_string())?
+        .ok_or(ContractError::AuctionNotFound {})
 }
 
 #[entry_point]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::GetAuction {} => to_binary(&AUCTION.load(deps.storage)?),
-        QueryMsg::GetCurrentPrice {} => {
-            let auction = AUCTION.load(deps.storage)?;
-            let current_price = auction.get_current_price(env.block.time.seconds());
-            to_binary(&current_price)
+        QueryMsg::Auction { auction_id } => to_binary(&query_auction(deps, env, auction_id)?),
+        QueryMsg::ActiveAuctions { start_after, limit } => {
+            to_binary(&query_active_auctions(deps, env, start_after, limit)?)
+        }
+        QueryMsg::CurrentPrice { auction_id } => to_binary(&query_current_price(deps, env, auction_id)?),
+        QueryMsg::AuctionHistory { auction_id, start_after, limit } => {
+            to_binary(&query_auction_history(deps, auction_id, start_after, limit)?)
         }
     }
 }
 
+fn query_auction(deps: Deps, env: Env, auction_id: String) -> StdResult<AuctionResponse> {
+    let auction = AUCTIONS.load(deps.storage, auction_id)?;
+    Ok(to_auction_response(auction, env.block.time.seconds()))
+}
+
+fn query_current_price(deps: Deps, env: Env, auction_id: String) -> StdResult<PriceResponse> {
+    let auction = AUCTIONS.load(deps.storage, auction_id)?;
+    let now = env.block.time.seconds();
+
+    Ok(PriceResponse {
+        current_price: live_price(&auction, now),
+        time_remaining: auction.end_time.saturating_sub(now),
+        price_at_end: live_price(&auction, auction.end_time),
+    })
+}
+
+/// Pages through auctions by ID, skipping any that are no longer active
+fn query_active_auctions(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let now = env.block.time.seconds();
+
+    let auctions = AUCTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, auction)) if auction.status != AuctionStatus::Active))
+        .take(limit)
+        .map(|item| item.map(|(_, auction)| to_auction_response(auction, now)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AuctionListResponse { auctions })
+}
+
+/// Bids in placement order; `start_after` is the index of the last bid already seen
+fn query_auction_history(
+    deps: Deps,
+    auction_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|index| {
+            index
+                .parse::<u64>()
+                .map_err(|_| StdError::generic_err("start_after must be a bid index"))
+        })
+        .transpose()?
+        .map(Bound::exclusive);
+
+    let bids = AUCTION_BIDS
+        .prefix(auction_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AuctionHistoryResponse { bids })
+}
+
+/// Bids close `bid_cutoff` seconds ahead of the hard `end_time`
+fn ensure_bidding_open(auction: &Auction, now: u64) -> Result<(), ContractError> {
+    if auction.status != AuctionStatus::Active {
+        return Err(ContractError::AuctionEnded {});
+    }
+    if now < auction.start_time {
+        return Err(ContractError::AuctionNotStarted {});
+    }
+    if now >= auction.end_time.saturating_sub(auction.bid_cutoff) {
+        return Err(ContractError::AuctionEnded {});
+    }
+    Ok(())
+}
+
+/// Linear decay from the initial price, floored at the minimum price
+fn live_price(auction: &Auction, now: u64) -> Uint128 {
+    let elapsed = now.saturating_sub(auction.start_time);
+    let decay = auction.price_decay_rate.saturating_mul(Uint128::from(elapsed));
+    auction.initial_price.saturating_sub(decay).max(auction.minimum_price)
+}
+
+fn to_auction_response(auction: Auction, now: u64) -> AuctionResponse {
+    AuctionResponse {
+        current_price: live_price(&auction, now),
+        auction_id: auction.auction_id,
+        seller: auction.seller,
+        asset: auction.asset,
+        amount: auction.amount,
+        initial_price: auction.initial_price,
+        minimum_price: auction.minimum_price,
+        price_decay_rate: auction.price_decay_rate,
+        start_time: auction.start_time,
+        end_time: auction.end_time,
+        duration: auction.duration,
+        bid_cutoff: auction.bid_cutoff,
+        status: auction.status,
+        winner: auction.winner,
+        winning_bid: auction.winning_bid,
+        escrow_address: auction.escrow_address,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::coins;
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            owner: "owner".to_string(),
+        }
+    }
+
+    fn create_auction(deps: DepsMut, auction_id: &str) {
+        execute_create_auction(
+            deps,
+            mock_env(),
+            mock_info("seller", &[]),
+            auction_id.to_string(),
+            "seller".to_string(),
+            "uusdc".to_string(),
+            Uint128::from(10u128),
+            Uint128::from(200u128),
+            Uint128::from(100u128),
+            Uint128::from(1u128),
+            100,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn create_and_bid_records_history() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        create_auction(deps.as_mut(), "a");
+
+        let err = execute_create_auction(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mallory", &[]),
+            "b".to_string(),
+            "seller".to_string(),
+            "uusdc".to_string(),
+            Uint128::from(10u128),
+            Uint128::from(200u128),
+            Uint128::from(100u128),
+            Uint128::from(1u128),
+            100,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(20);
+        let err = execute_place_bid(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("bidder", &coins(170, "uatom")),
+            "a".to_string(),
+            "bidder".to_string(),
+            Uint128::from(170u128),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBidAmount {}));
+
+        execute_place_bid(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("bidder", &coins(180, "uatom")),
+            "a".to_string(),
+            "bidder".to_string(),
+            Uint128::from(180u128),
+        )
+        .unwrap();
 
+        let auction = query_auction(deps.as_ref(), later, "a".to_string()).unwrap();
+        assert_eq!(Some(Uint128::from(180u128)), auction.winning_bid);
+        assert_eq!(Uint128::from(180u128), auction.current_price);
 
+        let history = query_auction_history(deps.as_ref(), "a".to_string(), None, None).unwrap();
+        assert_eq!(1, history.bids.len());
+        assert_eq!(Uint128::from(180u128), history.bids[0].price_at_bid);
+        let history = query_auction_history(deps.as_ref(), "a".to_string(), Some("0".to_string()), None).unwrap();
+        assert!(history.bids.is_empty());
+    }
+
+    #[test]
+    fn settle_closes_at_price_floor() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        create_auction(deps.as_mut(), "a");
+
+        let err = execute_settle(deps.as_mut(), mock_env(), mock_info("anyone", &[]), "a".to_string()).unwrap_err();
+        assert!(matches!(err, ContractError::PriceAboveFloor {}));
+
+        let mut at_floor = mock_env();
+        at_floor.block.time = at_floor.block.time.plus_seconds(100);
+        let res = execute_settle(deps.as_mut(), at_floor.clone(), mock_info("anyone", &[]), "a".to_string()).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(AuctionStatus::Ended, AUCTIONS.load(&deps.storage, "a".to_string()).unwrap().status);
+
+        let err = execute_settle(deps.as_mut(), at_floor, mock_info("anyone", &[]), "a".to_string()).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionEnded {}));
+    }
+
+    #[test]
+    fn settle_accepts_resting_bid_at_floor() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        create_auction(deps.as_mut(), "a");
+        execute_place_bid(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bidder", &coins(200, "uatom")),
+            "a".to_string(),
+            "bidder".to_string(),
+            Uint128::from(200u128),
+        )
+        .unwrap();
+
+        let mut at_floor = mock_env();
+        at_floor.block.time = at_floor.block.time.plus_seconds(100);
+        let res = execute_settle(deps.as_mut(), at_floor, mock_info("anyone", &[]), "a".to_string()).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "seller".to_string(),
+                amount: coins(200, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn active_auctions_pages_over_active_only() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let start_time = env.block.time.seconds();
+        for (id, status) in [
+            ("a", AuctionStatus::Active),
+            ("b", AuctionStatus::Ended),
+            ("c", AuctionStatus::Active),
+            ("d", AuctionStatus::Cancelled),
+            ("e", AuctionStatus::Active),
+        ] {
+            let auction = Auction {
+                auction_id: id.to_string(),
+                seller: cosmwasm_std::Addr::unchecked("seller"),
+                asset: "uatom".to_string(),
+                amount: Uint128::from(10u128),
+                initial_price: Uint128::from(200u128),
+                minimum_price: Uint128::from(100u128),
+                current_price: Uint128::from(200u128),
+                price_decay_rate: Uint128::from(1u128),
+                start_time,
+                end_time: start_time + 100,
+                duration: 100,
+                bid_cutoff: 0,
+                status,
+                winner: None,
+                winning_bid: None,
+                escrow_address: None,
+            };
+            AUCTIONS.save(deps.as_mut().storage, id.to_string(), &auction).unwrap();
+        }
+
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(30);
+        let page = query_active_auctions(deps.as_ref(), later.clone(), None, Some(2)).unwrap();
+        let ids: Vec<_> = page.auctions.iter().map(|a| a.auction_id.as_str()).collect();
+        assert_eq!(vec!["a", "c"], ids);
+        assert_eq!(Uint128::from(170u128), page.auctions[0].current_price);
+
+        let page = query_active_auctions(deps.as_ref(), later, Some("c".to_string()), Some(2)).unwrap();
+        let ids: Vec<_> = page.auctions.iter().map(|a| a.auction_id.as_str()).collect();
+        assert_eq!(vec!["e"], ids);
+    }
+
+    #[test]
+    fn bids_close_at_cutoff_before_end() {
+        let env = mock_env();
+        let start_time = env.block.time.seconds();
+        let auction = Auction {
+            auction_id: "a".to_string(),
+            seller: cosmwasm_std::Addr::unchecked("seller"),
+            asset: "uatom".to_string(),
+            amount: Uint128::from(10u128),
+            initial_price: Uint128::from(200u128),
+            minimum_price: Uint128::from(100u128),
+            current_price: Uint128::from(200u128),
+            price_decay_rate: Uint128::from(1u128),
+            start_time,
+            end_time: start_time + 100,
+            duration: 100,
+            bid_cutoff: 10,
+            status: AuctionStatus::Active,
+            winner: None,
+            winning_bid: None,
+            escrow_address: None,
+        };
+
+        ensure_bidding_open(&auction, start_time + 89).unwrap();
+        let err = ensure_bidding_open(&auction, start_time + 90).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionEnded {}));
+    }
+
+    #[test]
+    fn equal_bid_loses_to_first_and_outbid_is_refunded() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        create_auction(deps.as_mut(), "a");
+
+        let bid = |deps: DepsMut, bidder: &str, amount: u128| {
+            execute_place_bid(
+                deps,
+                mock_env(),
+                mock_info(bidder, &coins(amount, "uatom")),
+                "a".to_string(),
+                bidder.to_string(),
+                Uint128::from(amount),
+            )
+        };
+
+        let res = bid(deps.as_mut(), "first", 200).unwrap();
+        assert!(res.messages.is_empty());
+
+        let err = bid(deps.as_mut(), "second", 200).unwrap_err();
+        assert!(matches!(err, ContractError::BidTooLow {}));
+        let auction = AUCTIONS.load(&deps.storage, "a".to_string()).unwrap();
+        assert_eq!(Some(cosmwasm_std::Addr::unchecked("first")), auction.winner);
+
+        let res = bid(deps.as_mut(), "second", 201).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "first".to_string(),
+                amount: coins(200, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+        let history = query_auction_history(deps.as_ref(), "a".to_string(), None, None).unwrap();
+        assert_eq!(2, history.bids.len());
+    }
+
+}
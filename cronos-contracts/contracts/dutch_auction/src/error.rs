@@ -21,6 +21,9 @@ pub enum ContractError {
     #[error("Invalid bid amount")]
     InvalidBidAmount {},
 
+    #[error("Bid must exceed the current winning bid")]
+    BidTooLow {},
+
     #[error("Auction still active")]
     AuctionStillActive {},
 
@@ -29,5 +32,8 @@ pub enum ContractError {
 
     #[error("Minimum price reached")]
     MinimumPriceReached {},
+
+    #[error("Auction price has not reached the floor")]
+    PriceAboveFloor {},
 }
 
@@ -18,6 +18,8 @@ pub enum ExecuteMsg {
         minimum_price: Uint128,
         price_decay_rate: Uint128,
         duration: u64,
+        /// Seconds before `end_time` after which no new bids are accepted; bids run to the end when unset
+        bid_cutoff: Option<u64>,
         escrow_address: Option<String>,
     },
     /// Place a bid on an auction
@@ -34,6 +36,10 @@ pub enum ExecuteMsg {
     EndAuction {
         auction_id: String,
     },
+    /// Close an auction whose price has reached the floor, accepting any resting bid (permissionless)
+    Settle {
+        auction_id: String,
+    },
     /// Cancel an auction (only by seller)
     CancelAuction {
         auction_id: String,
@@ -81,6 +87,7 @@ pub struct AuctionResponse {
     pub start_time: u64,
     pub end_time: u64,
     pub duration: u64,
+    pub bid_cutoff: u64,
     pub status: AuctionStatus,
     pub winner: Option<Addr>,
     pub winning_bid: Option<Uint128>,
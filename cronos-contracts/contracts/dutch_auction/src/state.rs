@@ -23,6 +23,8 @@ pub struct Auction {
     pub start_time: u64,
     pub end_time: u64,
     pub duration: u64,
+    /// Seconds before `end_time` during which bidding is closed, against last-second sniping
+    pub bid_cutoff: u64,
     pub status: AuctionStatus,
     pub winner: Option<Addr>,
     pub winning_bid: Option<Uint128>,
@@ -1,26 +1,41 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg, ReplyOn, Reply, Uint128
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, SubMsg,
+    WasmMsg, Reply, Uint128, Coin, Addr
 };
+use cw_storage_plus::Bound;
 use cw2::set_contract_version;
 use cw_utils::parse_reply_instantiate_data;
+use escrow_common::hash::HashAlgo;
 
 use crate::error::ContractError;
 use crate::msg::{
     ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse, EscrowAddressResponse,
-    EscrowListResponse, EscrowInfo, EscrowType
+    EscrowListResponse, EscrowInfo, EscrowType, TotalValueLockedResponse
+};
+use crate::state::{
+    Config, CONFIG, ESCROWS, ESCROWS_BY_ADDRESS, ESCROWS_BY_CREATOR, INSTANTIATE_COUNT, PENDING_INSTANTIATES,
 };
-use crate::state::{Config, CONFIG, ESCROWS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:escrow_factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // Reply IDs
-const INSTANTIATE_SOURCE_ESCROW_REPLY_ID: u64 = 1;
-const INSTANTIATE_DESTINATION_ESCROW_REPLY_ID: u64 = 2;
+const EMERGENCY_CANCEL_REPLY_ID: u64 = 3;
+// Escrow instantiations reply with this base plus a per-instantiation counter
+const INSTANTIATE_REPLY_ID_BASE: u64 = 1_000;
+
+// Longest contract label accepted by the chain
+const MAX_LABEL_LEN: usize = 128;
+
+// Escrows smart-queried per TotalValueLocked page
+const DEFAULT_TVL_LIMIT: u32 = 10;
+const MAX_TVL_LIMIT: u32 = 30;
+
+// Escrows cancelled per EmergencyCancel, bounded for gas
+const MAX_EMERGENCY_CANCELS: usize = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -35,6 +50,7 @@ pub fn instantiate(
         owner: owner.clone(),
         source_escrow_code_id: msg.source_escrow_code_id,
         destination_escrow_code_id: msg.destination_escrow_code_id,
+        frozen: false,
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -63,11 +79,28 @@ pub fn execute(
             dst_chain_id,
             dst_asset,
             dst_amount,
+            src_amount,
             initial_price,
             price_decay_rate,
             minimum_price,
             allow_partial_fill,
             minimum_fill_amount,
+            dust_threshold,
+            refund_addr,
+            safety_deposit,
+            safety_deposit_swap,
+            ibc_forward,
+            allowed_cw20,
+            basket,
+            withdraw_whitelist,
+            on_complete_msg,
+            max_total_extension,
+            hash_algorithm,
+            timelocks,
+            case_insensitive_denoms,
+            merkle_root,
+            tranche_count,
+            price_decimals,
             label,
         } => execute_create_source_escrow(
             deps,
@@ -80,11 +113,28 @@ pub fn execute(
             dst_chain_id,
             dst_asset,
             dst_amount,
+            src_amount,
             initial_price,
             price_decay_rate,
             minimum_price,
             allow_partial_fill,
             minimum_fill_amount,
+            dust_threshold,
+            refund_addr,
+            safety_deposit,
+            safety_deposit_swap,
+            ibc_forward,
+            allowed_cw20,
+            basket,
+            withdraw_whitelist,
+            on_complete_msg,
+            max_total_extension,
+            hash_algorithm,
+            timelocks,
+            case_insensitive_denoms,
+            merkle_root,
+            tranche_count,
+            price_decimals,
             label,
         ),
         ExecuteMsg::CreateDestinationEscrow {
@@ -95,6 +145,11 @@ pub fn execute(
             src_chain_id,
             src_escrow_address,
             expected_amount,
+            allow_partial_fill,
+            minimum_fill_amount,
+            claim_addr,
+            allowed_cw20,
+            hash_algorithm,
             label,
         } => execute_create_destination_escrow(
             deps,
@@ -107,6 +162,11 @@ pub fn execute(
             src_chain_id,
             src_escrow_address,
             expected_amount,
+            allow_partial_fill,
+            minimum_fill_amount,
+            claim_addr,
+            allowed_cw20,
+            hash_algorithm,
             label,
         ),
         ExecuteMsg::UpdateCodeIds {
@@ -114,6 +174,8 @@ pub fn execute(
             destination_escrow_code_id,
         } => execute_update_code_ids(deps, info, source_escrow_code_id, destination_escrow_code_id),
         ExecuteMsg::UpdateOwner { new_owner } => execute_update_owner(deps, info, new_owner),
+        ExecuteMsg::SetFrozen { frozen } => execute_set_frozen(deps, info, frozen),
+        ExecuteMsg::EmergencyCancel { salts } => execute_emergency_cancel(deps, info, salts),
     }
 }
 
@@ -128,23 +190,51 @@ pub fn execute_create_source_escrow(
     dst_chain_id: String,
     dst_asset: String,
     dst_amount: Uint128,
+    src_amount: Option<Uint128>,
     initial_price: Option<Uint128>,
     price_decay_rate: Option<Uint128>,
     minimum_price: Option<Uint128>,
     allow_partial_fill: bool,
     minimum_fill_amount: Option<Uint128>,
+    dust_threshold: Option<Uint128>,
+    refund_addr: Option<String>,
+    safety_deposit: Option<Coin>,
+    safety_deposit_swap: Option<source_escrow::msg::SafetyDepositSwap>,
+    ibc_forward: Option<source_escrow::msg::IbcForwardParams>,
+    allowed_cw20: Option<Vec<String>>,
+    basket: Option<Vec<source_escrow::msg::AssetInfo>>,
+    withdraw_whitelist: Option<Vec<String>>,
+    on_complete_msg: Option<(String, Binary)>,
+    max_total_extension: Option<u64>,
+    hash_algorithm: Option<HashAlgo>,
+    timelocks: Option<source_escrow::msg::Timelocks>,
+    case_insensitive_denoms: bool,
+    merkle_root: Option<String>,
+    tranche_count: Option<u64>,
+    price_decimals: Option<u8>,
     label: String,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
+    if config.frozen {
+        return Err(ContractError::FactoryFrozen {});
+    }
+
     // Generate salt for deterministic address
-    let salt = format!("{}:{}:{}", info.sender, env.block.time.nanos(), label);
+    let salt = escrow_salt(&info.sender, &env, &label);
 
     // Check if escrow already exists
     if ESCROWS.has(deps.storage, salt.clone()) {
         return Err(ContractError::EscrowAlreadyExists {});
     }
 
+    let escrow_label = escrow_label(
+        "src_escrow",
+        &format!("{}->{}", env.block.chain_id, dst_chain_id),
+        &format!("{}{}", dst_amount, dst_asset),
+        &salt,
+    );
+
     let instantiate_msg = source_escrow::msg::InstantiateMsg {
         maker,
         taker,
@@ -153,11 +243,29 @@ pub fn execute_create_source_escrow(
         dst_chain_id,
         dst_asset,
         dst_amount,
+        src_amount,
         initial_price,
         price_decay_rate,
         minimum_price,
         allow_partial_fill,
         minimum_fill_amount,
+        dust_threshold,
+        refund_addr,
+        safety_deposit,
+        safety_deposit_swap,
+        ibc_forward,
+        allowed_cw20,
+        basket,
+        withdraw_whitelist,
+        on_complete_msg,
+        max_total_extension,
+        hash_algorithm,
+        timelocks,
+        case_insensitive_denoms,
+        merkle_root,
+        tranche_count,
+        price_decimals,
+        resolver: Some(info.sender.to_string()),
     };
 
     let wasm_msg = WasmMsg::Instantiate {
@@ -165,15 +273,10 @@ pub fn execute_create_source_escrow(
         code_id: config.source_escrow_code_id,
         msg: to_binary(&instantiate_msg)?,
         funds: vec![],
-        label: format!("source_escrow_{}", salt),
+        label: escrow_label,
     };
 
-    let sub_msg = SubMsg {
-        id: INSTANTIATE_SOURCE_ESCROW_REPLY_ID,
-        msg: wasm_msg.into(),
-        gas_limit: None,
-        reply_on: ReplyOn::Success,
-    };
+    let sub_msg = instantiate_sub_msg(deps.storage, &salt, wasm_msg)?;
 
     // Store pending escrow info
     let escrow_info = EscrowInfo {
@@ -184,6 +287,7 @@ pub fn execute_create_source_escrow(
         salt: salt.clone(),
     };
     ESCROWS.save(deps.storage, salt.clone(), &escrow_info)?;
+    ESCROWS_BY_CREATOR.save(deps.storage, (&escrow_info.creator, &salt), &())?;
 
     Ok(Response::new()
         .add_submessage(sub_msg)
@@ -202,18 +306,34 @@ pub fn execute_create_destination_escrow(
     src_chain_id: String,
     src_escrow_address: String,
     expected_amount: Uint128,
+    allow_partial_fill: bool,
+    minimum_fill_amount: Option<Uint128>,
+    claim_addr: Option<String>,
+    allowed_cw20: Option<Vec<String>>,
+    hash_algorithm: Option<HashAlgo>,
     label: String,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
+    if config.frozen {
+        return Err(ContractError::FactoryFrozen {});
+    }
+
     // Generate salt for deterministic address
-    let salt = format!("{}:{}:{}", info.sender, env.block.time.nanos(), label);
+    let salt = escrow_salt(&info.sender, &env, &label);
 
     // Check if escrow already exists
     if ESCROWS.has(deps.storage, salt.clone()) {
         return Err(ContractError::EscrowAlreadyExists {});
     }
 
+    let escrow_label = escrow_label(
+        "dst_escrow",
+        &format!("{}->{}", src_chain_id, env.block.chain_id),
+        &expected_amount.to_string(),
+        &salt,
+    );
+
     let instantiate_msg = destination_escrow::msg::InstantiateMsg {
         taker,
         maker,
@@ -222,6 +342,12 @@ pub fn execute_create_destination_escrow(
         src_chain_id,
         src_escrow_address,
         expected_amount,
+        allow_partial_fill,
+        minimum_fill_amount,
+        claim_addr,
+        allowed_cw20,
+        hash_algorithm,
+        resolver: Some(info.sender.to_string()),
     };
 
     let wasm_msg = WasmMsg::Instantiate {
@@ -229,15 +355,10 @@ pub fn execute_create_destination_escrow(
         code_id: config.destination_escrow_code_id,
         msg: to_binary(&instantiate_msg)?,
         funds: vec![],
-        label: format!("destination_escrow_{}", salt),
+        label: escrow_label,
     };
 
-    let sub_msg = SubMsg {
-        id: INSTANTIATE_DESTINATION_ESCROW_REPLY_ID,
-        msg: wasm_msg.into(),
-        gas_limit: None,
-        reply_on: ReplyOn::Success,
-    };
+    let sub_msg = instantiate_sub_msg(deps.storage, &salt, wasm_msg)?;
 
     // Store pending escrow info
     let escrow_info = EscrowInfo {
@@ -248,6 +369,7 @@ pub fn execute_create_destination_escrow(
         salt: salt.clone(),
     };
     ESCROWS.save(deps.storage, salt.clone(), &escrow_info)?;
+    ESCROWS_BY_CREATOR.save(deps.storage, (&escrow_info.creator, &salt), &())?;
 
     Ok(Response::new()
         .add_submessage(sub_msg)
@@ -304,38 +426,105 @@ pub fn execute_update_owner(
         .add_attribute("new_owner", new_owner))
 }
 
+pub fn execute_set_frozen(
+    deps: DepsMut,
+    info: MessageInfo,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.frozen = frozen;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_frozen")
+        .add_attribute("frozen", frozen.to_string()))
+}
+
+pub fn execute_emergency_cancel(
+    deps: DepsMut,
+    info: MessageInfo,
+    salts: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if salts.len() > MAX_EMERGENCY_CANCELS {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    let mut sub_msgs = vec![];
+    for salt in &salts {
+        let escrow_info = ESCROWS.load(deps.storage, salt.clone())?;
+        let msg = match escrow_info.escrow_type {
+            EscrowType::Source => to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?,
+            EscrowType::Destination => to_binary(&destination_escrow::msg::ExecuteMsg::Cancel {})?,
+        };
+        // An escrow that already settled must not block the rest of the batch
+        sub_msgs.push(SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: escrow_info.address.to_string(),
+                msg,
+                funds: vec![],
+            },
+            EMERGENCY_CANCEL_REPLY_ID,
+        ));
+    }
+
+    Ok(Response::new()
+        .add_submessages(sub_msgs)
+        .add_attribute("method", "emergency_cancel")
+        .add_attribute("escrows", salts.len().to_string()))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
-        INSTANTIATE_SOURCE_ESCROW_REPLY_ID | INSTANTIATE_DESTINATION_ESCROW_REPLY_ID => {
-            handle_instantiate_reply(deps, msg)
-        }
+        EMERGENCY_CANCEL_REPLY_ID => Ok(Response::new()
+            .add_attribute("method", "emergency_cancel_failed")
+            .add_attribute("error", msg.result.into_result().err().unwrap_or_default())),
+        id if id >= INSTANTIATE_REPLY_ID_BASE => handle_instantiate_reply(deps, msg),
         id => Err(ContractError::Std(cosmwasm_std::StdError::generic_err(
             format!("Unknown reply id: {}", id),
         ))),
     }
 }
 
+/// Wraps an escrow instantiation in a reply-on-success submessage whose id maps back to `salt`
+fn instantiate_sub_msg(storage: &mut dyn Storage, salt: &str, wasm_msg: WasmMsg) -> StdResult<SubMsg> {
+    let count = INSTANTIATE_COUNT.may_load(storage)?.unwrap_or(0);
+    INSTANTIATE_COUNT.save(storage, &(count + 1))?;
+
+    let reply_id = INSTANTIATE_REPLY_ID_BASE + count;
+    PENDING_INSTANTIATES.save(storage, reply_id, &salt.to_string())?;
+    Ok(SubMsg::reply_on_success(wasm_msg, reply_id))
+}
+
 fn handle_instantiate_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let reply_id = msg.id;
+    let salt = PENDING_INSTANTIATES.load(deps.storage, reply_id)?;
+    PENDING_INSTANTIATES.remove(deps.storage, reply_id);
+
     let reply = parse_reply_instantiate_data(msg)?;
     let contract_address = deps.api.addr_validate(&reply.contract_address)?;
 
-    // Find the pending escrow and update its address
-    // This is a simplified approach - in production, you might want to store the salt in the reply data
-    let escrows: Vec<_> = ESCROWS
-        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .collect::<StdResult<Vec<_>>>()?;
-
-    for (salt, mut escrow_info) in escrows {
-        if escrow_info.address == deps.api.addr_validate("pending")? {
-            escrow_info.address = contract_address.clone();
-            ESCROWS.save(deps.storage, salt, &escrow_info)?;
-            break;
-        }
-    }
+    ESCROWS.update(deps.storage, salt.clone(), |escrow_info| -> StdResult<_> {
+        let mut escrow_info = escrow_info.ok_or_else(|| cosmwasm_std::StdError::not_found("EscrowInfo"))?;
+        escrow_info.address = contract_address.clone();
+        Ok(escrow_info)
+    })?;
+    ESCROWS_BY_ADDRESS.save(deps.storage, &contract_address, &salt)?;
 
     Ok(Response::new()
         .add_attribute("method", "handle_instantiate_reply")
+        .add_attribute("salt", salt)
         .add_attribute("contract_address", contract_address))
 }
 
@@ -344,9 +533,16 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::EscrowAddress { salt } => to_binary(&query_escrow_address(deps, salt)?),
+        QueryMsg::EscrowByAddress { address } => to_binary(&query_escrow_by_address(deps, address)?),
         QueryMsg::EscrowList { start_after, limit } => {
             to_binary(&query_escrow_list(deps, start_after, limit)?)
         }
+        QueryMsg::EscrowsByCreator { creator, start_after, limit } => {
+            to_binary(&query_escrows_by_creator(deps, creator, start_after, limit)?)
+        }
+        QueryMsg::TotalValueLocked { denom, continue_from, limit } => {
+            to_binary(&query_total_value_locked(deps, denom, continue_from, limit)?)
+        }
     }
 }
 
@@ -356,6 +552,7 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: config.owner,
         source_escrow_code_id: config.source_escrow_code_id,
         destination_escrow_code_id: config.destination_escrow_code_id,
+        frozen: config.frozen,
     })
 }
 
@@ -366,6 +563,12 @@ fn query_escrow_address(deps: Deps, salt: String) -> StdResult<EscrowAddressResp
     })
 }
 
+fn query_escrow_by_address(deps: Deps, address: String) -> StdResult<EscrowInfo> {
+    let address = deps.api.addr_validate(&address)?;
+    let salt = ESCROWS_BY_ADDRESS.load(deps.storage, &address)?;
+    ESCROWS.load(deps.storage, salt)
+}
+
 fn query_escrow_list(
     deps: Deps,
     start_after: Option<String>,
@@ -385,3 +588,546 @@ fn query_escrow_list(
     })
 }
 
+fn query_escrows_by_creator(
+    deps: Deps,
+    creator: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<EscrowListResponse> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let escrows = ESCROWS_BY_CREATOR
+        .prefix(&creator)
+        .keys(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|salt| ESCROWS.load(deps.storage, salt?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowListResponse { escrows })
+}
+
+fn query_total_value_locked(
+    deps: Deps,
+    denom: String,
+    continue_from: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TotalValueLockedResponse> {
+    let limit = limit.unwrap_or(DEFAULT_TVL_LIMIT).min(MAX_TVL_LIMIT) as usize;
+    let start = continue_from.map(Bound::exclusive);
+
+    let escrows = ESCROWS
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let pending = deps.api.addr_validate("pending")?;
+    let mut total = Uint128::zero();
+    for (_, escrow_info) in &escrows {
+        if escrow_info.address != pending {
+            total += locked_amount(deps, escrow_info, &denom)?;
+        }
+    }
+
+    // A full page may have more escrows behind it
+    let continue_from = if escrows.len() == limit {
+        escrows.last().map(|(salt, _)| salt.clone())
+    } else {
+        None
+    };
+
+    Ok(TotalValueLockedResponse {
+        denom,
+        total,
+        continue_from,
+    })
+}
+
+/// Funds still locked in a live escrow, if it holds `denom` (native denom or cw20 address).
+fn locked_amount(deps: Deps, escrow_info: &EscrowInfo, denom: &str) -> StdResult<Uint128> {
+    let (live, deposited_denom, cw20_contract, remaining_amount) = match escrow_info.escrow_type {
+        EscrowType::Source => {
+            let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                escrow_info.address.to_string(),
+                &source_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            let live = matches!(
+                escrow.status,
+                source_escrow::msg::EscrowStatus::Active
+                    | source_escrow::msg::EscrowStatus::PartiallyFilled
+            );
+            (live, escrow.deposited_denom, escrow.cw20_contract, escrow.remaining_amount)
+        }
+        EscrowType::Destination => {
+            let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                escrow_info.address.to_string(),
+                &destination_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            let live = matches!(
+                escrow.status,
+                destination_escrow::msg::EscrowStatus::Active
+                    | destination_escrow::msg::EscrowStatus::PartiallyFilled
+            );
+            (live, escrow.deposited_denom, escrow.cw20_contract, escrow.remaining_amount)
+        }
+    };
+
+    let holds_denom = deposited_denom.as_deref() == Some(denom)
+        || cw20_contract.as_ref().map(Addr::as_str) == Some(denom);
+    if live && holds_denom {
+        Ok(remaining_amount)
+    } else {
+        Ok(Uint128::zero())
+    }
+}
+
+/// Salt keying an escrow created by `creator` in the current block.
+pub fn escrow_salt(creator: &Addr, env: &Env, label: &str) -> String {
+    format!("{}:{}:{}", creator, env.block.time.nanos(), label)
+}
+
+/// Builds an explorer-friendly label (`kind:route:amount:salt`), truncating the salt to fit.
+fn escrow_label(kind: &str, route: &str, amount: &str, salt: &str) -> String {
+    format!("{}:{}:{}:{}", kind, route, amount, salt)
+        .chars()
+        .take(MAX_LABEL_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, ContractResult, CosmosMsg, SystemResult, WasmQuery};
+
+    #[test]
+    fn source_escrow_label_includes_route_and_asset() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: "owner".to_string(),
+            source_escrow_code_id: 1,
+            destination_escrow_code_id: 2,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateSourceEscrow {
+            maker: "maker".to_string(),
+            taker: None,
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            initial_price: None,
+            price_decay_rate: None,
+            minimum_price: None,
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            dust_threshold: None,
+            refund_addr: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            ibc_forward: None,
+            allowed_cw20: None,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: None,
+            hash_algorithm: None,
+            timelocks: None,
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            tranche_count: None,
+            price_decimals: None,
+            label: "x".repeat(200),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("resolver", &[]), msg).unwrap();
+
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { label, .. }) => {
+                assert!(label.starts_with("src_escrow:cosmos-testnet-14002->ethereum-1:100ETH:"));
+                assert!(label.len() <= MAX_LABEL_LEN);
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    fn source_escrow_response(remaining: u128, denom: &str) -> source_escrow::msg::EscrowResponse {
+        source_escrow::msg::EscrowResponse {
+            maker: Addr::unchecked("maker"),
+            taker: None,
+            refund_addr: None,
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            deposited_amount: Uint128::from(remaining),
+            deposited_denom: Some(denom.to_string()),
+            cw20_contract: None,
+            allowed_cw20: None,
+            status: source_escrow::msg::EscrowStatus::Active,
+            created_at: 0,
+            allow_partial_fill: false,
+            filled_amount: Uint128::zero(),
+            remaining_amount: Uint128::from(remaining),
+            dust_threshold: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            safety_deposit_funded: false,
+            ibc_forward: None,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: 0,
+            total_extension: 0,
+            hash_algorithm: HashAlgo::Sha256,
+            timelocks: source_escrow::msg::Timelocks::from_timelock(0, 1000),
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            last_used_index: None,
+            tranche_count: None,
+            cancelled_tranches: vec![],
+        }
+    }
+
+    #[test]
+    fn total_value_locked_sums_funded_escrows_across_pages() {
+        let mut deps = mock_dependencies();
+        for (salt, address) in [("a", "escrow_a"), ("b", "escrow_b"), ("c", "escrow_c"), ("d", "escrow_d")] {
+            let escrow_info = EscrowInfo {
+                address: Addr::unchecked(address),
+                escrow_type: EscrowType::Source,
+                creator: Addr::unchecked("resolver"),
+                created_at: 0,
+                salt: salt.to_string(),
+            };
+            ESCROWS.save(deps.as_mut().storage, salt.to_string(), &escrow_info).unwrap();
+        }
+        deps.querier.update_wasm(|query| {
+            let response = match query {
+                WasmQuery::Smart { contract_addr, .. } => match contract_addr.as_str() {
+                    "escrow_a" => source_escrow_response(100, "uatom"),
+                    "escrow_b" => source_escrow_response(200, "uatom"),
+                    "escrow_c" => source_escrow_response(300, "uatom"),
+                    _ => source_escrow_response(400, "uosmo"),
+                },
+                _ => panic!("unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(to_binary(&response).unwrap()))
+        });
+
+        let tvl = |deps: Deps, continue_from: Option<String>| -> TotalValueLockedResponse {
+            let msg = QueryMsg::TotalValueLocked {
+                denom: "uatom".to_string(),
+                continue_from,
+                limit: Some(2),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let first = tvl(deps.as_ref(), None);
+        assert_eq!(Uint128::from(300u128), first.total);
+        assert_eq!(Some("b".to_string()), first.continue_from);
+
+        let second = tvl(deps.as_ref(), first.continue_from);
+        assert_eq!(Uint128::from(300u128), second.total);
+        assert_eq!(Some("d".to_string()), second.continue_from);
+
+        let last = tvl(deps.as_ref(), second.continue_from);
+        assert_eq!(Uint128::zero(), last.total);
+        assert_eq!(None, last.continue_from);
+    }
+
+    #[test]
+    fn emergency_cancel_refunds_frozen_factory_escrows() {
+        use cosmwasm_std::coins;
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::new(|router, _, storage| {
+            router.bank.init_balance(storage, &Addr::unchecked("maker"), coins(300, "uatom")).unwrap();
+            router.bank.init_balance(storage, &Addr::unchecked("taker"), coins(100, "uosmo")).unwrap();
+        });
+        let source_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                source_escrow::contract::execute,
+                source_escrow::contract::instantiate,
+                source_escrow::contract::query,
+            )
+            .with_reply(source_escrow::contract::reply),
+        ));
+        let destination_code = app.store_code(Box::new(ContractWrapper::new(
+            destination_escrow::contract::execute,
+            destination_escrow::contract::instantiate,
+            destination_escrow::contract::query,
+        )));
+        let factory_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply)));
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    owner: "owner".to_string(),
+                    source_escrow_code_id: source_code,
+                    destination_escrow_code_id: destination_code,
+                },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+
+        // sha256("secret")
+        let secret_hash = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+        let timelock = app.block_info().time.seconds() + 1000;
+        let create_source = |label: &str| ExecuteMsg::CreateSourceEscrow {
+            maker: "maker".to_string(),
+            taker: None,
+            secret_hash: secret_hash.to_string(),
+            timelock,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            initial_price: None,
+            price_decay_rate: None,
+            minimum_price: None,
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            dust_threshold: None,
+            refund_addr: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            ibc_forward: None,
+            allowed_cw20: None,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: None,
+            hash_algorithm: None,
+            timelocks: None,
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            tranche_count: None,
+            price_decimals: None,
+            label: label.to_string(),
+        };
+        for label in ["a", "b"] {
+            app.execute_contract(Addr::unchecked("resolver"), factory.clone(), &create_source(label), &[])
+                .unwrap();
+        }
+        let create_destination = ExecuteMsg::CreateDestinationEscrow {
+            taker: "taker".to_string(),
+            maker: "maker".to_string(),
+            secret_hash: secret_hash.to_string(),
+            timelock,
+            src_chain_id: "osmosis-1".to_string(),
+            src_escrow_address: "src_escrow".to_string(),
+            expected_amount: Uint128::from(100u128),
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            claim_addr: None,
+            allowed_cw20: None,
+            hash_algorithm: None,
+            label: "c".to_string(),
+        };
+        app.execute_contract(Addr::unchecked("resolver"), factory.clone(), &create_destination, &[])
+            .unwrap();
+
+        let list: EscrowListResponse = app
+            .wrap()
+            .query_wasm_smart(&factory, &QueryMsg::EscrowList { start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(3, list.escrows.len());
+        for escrow in &list.escrows {
+            let res = match escrow.escrow_type {
+                EscrowType::Source => app.execute_contract(
+                    Addr::unchecked("maker"),
+                    escrow.address.clone(),
+                    &source_escrow::msg::ExecuteMsg::Deposit {},
+                    &coins(100, "uatom"),
+                ),
+                EscrowType::Destination => app.execute_contract(
+                    Addr::unchecked("taker"),
+                    escrow.address.clone(),
+                    &destination_escrow::msg::ExecuteMsg::Deposit {},
+                    &coins(100, "uosmo"),
+                ),
+            };
+            res.unwrap();
+        }
+
+        let freeze = ExecuteMsg::SetFrozen { frozen: true };
+        app.execute_contract(Addr::unchecked("owner"), factory.clone(), &freeze, &[]).unwrap();
+        let err = app
+            .execute_contract(Addr::unchecked("resolver"), factory.clone(), &create_source("d"), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast().unwrap(), ContractError::FactoryFrozen {}));
+
+        let cancel = ExecuteMsg::EmergencyCancel {
+            salts: list.escrows.iter().map(|escrow| escrow.salt.clone()).collect(),
+        };
+        let err = app
+            .execute_contract(Addr::unchecked("resolver"), factory.clone(), &cancel, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(Addr::unchecked("owner"), factory.clone(), &cancel, &[]).unwrap();
+
+        // Refunds land well before any timelock expires
+        assert_eq!(Uint128::from(300u128), app.wrap().query_balance("maker", "uatom").unwrap().amount);
+        assert_eq!(Uint128::from(100u128), app.wrap().query_balance("taker", "uosmo").unwrap().amount);
+
+        // Escrows that are already cancelled are reported without failing the batch
+        let res = app.execute_contract(Addr::unchecked("owner"), factory, &cancel, &[]).unwrap();
+        assert!(res.has_event(
+            &cosmwasm_std::Event::new("wasm").add_attribute("method", "emergency_cancel_failed")
+        ));
+    }
+
+    #[test]
+    fn same_block_escrows_resolve_to_their_own_addresses() {
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let source_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                source_escrow::contract::execute,
+                source_escrow::contract::instantiate,
+                source_escrow::contract::query,
+            )
+            .with_reply(source_escrow::contract::reply),
+        ));
+        let destination_code = app.store_code(Box::new(ContractWrapper::new(
+            destination_escrow::contract::execute,
+            destination_escrow::contract::instantiate,
+            destination_escrow::contract::query,
+        )));
+        let factory_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply)));
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    owner: "owner".to_string(),
+                    source_escrow_code_id: source_code,
+                    destination_escrow_code_id: destination_code,
+                },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+
+        // sha256("secret")
+        let secret_hash = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+        let timelock = app.block_info().time.seconds() + 1000;
+        for label in ["first", "second"] {
+            let msg = ExecuteMsg::CreateSourceEscrow {
+                maker: "maker".to_string(),
+                taker: None,
+                secret_hash: secret_hash.to_string(),
+                timelock,
+                dst_chain_id: "ethereum-1".to_string(),
+                dst_asset: "ETH".to_string(),
+                dst_amount: Uint128::from(100u128),
+                src_amount: None,
+                initial_price: None,
+                price_decay_rate: None,
+                minimum_price: None,
+                allow_partial_fill: false,
+                minimum_fill_amount: None,
+                dust_threshold: None,
+                refund_addr: None,
+                safety_deposit: None,
+                safety_deposit_swap: None,
+                ibc_forward: None,
+                allowed_cw20: None,
+                basket: None,
+                withdraw_whitelist: None,
+                on_complete_msg: None,
+                max_total_extension: None,
+                hash_algorithm: None,
+                timelocks: None,
+                case_insensitive_denoms: false,
+                merkle_root: None,
+                tranche_count: None,
+                price_decimals: None,
+                label: label.to_string(),
+            };
+            app.execute_contract(Addr::unchecked("resolver"), factory.clone(), &msg, &[]).unwrap();
+        }
+
+        let list: EscrowListResponse = app
+            .wrap()
+            .query_wasm_smart(&factory, &QueryMsg::EscrowList { start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(2, list.escrows.len());
+        assert_ne!(list.escrows[0].address, list.escrows[1].address);
+        for escrow in &list.escrows {
+            // Each escrow's instantiate label ends with the salt it was created under
+            let data = app.contract_data(&escrow.address).unwrap();
+            assert!(data.label.ends_with(&escrow.salt));
+
+            let by_address: EscrowInfo = app
+                .wrap()
+                .query_wasm_smart(&factory, &QueryMsg::EscrowByAddress { address: escrow.address.to_string() })
+                .unwrap();
+            assert_eq!(escrow, &by_address);
+        }
+        let unknown = QueryMsg::EscrowByAddress { address: "maker".to_string() };
+        assert!(app.wrap().query_wasm_smart::<EscrowInfo>(&factory, &unknown).is_err());
+    }
+
+
+    #[test]
+    fn escrows_by_creator_lists_only_that_creator() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: "owner".to_string(),
+            source_escrow_code_id: 1,
+            destination_escrow_code_id: 2,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        for (creator, label) in [("resolver_a", "1"), ("resolver_b", "2"), ("resolver_a", "3")] {
+            let msg = ExecuteMsg::CreateDestinationEscrow {
+                taker: "taker".to_string(),
+                maker: "maker".to_string(),
+                secret_hash: "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b".to_string(),
+                timelock: 1000,
+                src_chain_id: "osmosis-1".to_string(),
+                src_escrow_address: "src_escrow".to_string(),
+                expected_amount: Uint128::from(100u128),
+                allow_partial_fill: false,
+                minimum_fill_amount: None,
+                claim_addr: None,
+                allowed_cw20: None,
+                hash_algorithm: None,
+                label: label.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(creator, &[]), msg).unwrap();
+        }
+
+        let by_creator = |start_after: Option<String>, limit: Option<u32>| -> EscrowListResponse {
+            let msg = QueryMsg::EscrowsByCreator {
+                creator: "resolver_a".to_string(),
+                start_after,
+                limit,
+            };
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let all = by_creator(None, None);
+        assert_eq!(2, all.escrows.len());
+        assert!(all.escrows.iter().all(|escrow| escrow.creator == Addr::unchecked("resolver_a")));
+
+        let first = by_creator(None, Some(1));
+        let rest = by_creator(Some(first.escrows[0].salt.clone()), None);
+        assert_eq!(1, rest.escrows.len());
+        assert_eq!(all.escrows[1].salt, rest.escrows[0].salt);
+    }
+
+}
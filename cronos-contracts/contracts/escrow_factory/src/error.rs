@@ -14,5 +14,11 @@ pub enum ContractError {
 
     #[error("Escrow already exists")]
     EscrowAlreadyExists {},
+
+    #[error("Factory is frozen")]
+    FactoryFrozen {},
+
+    #[error("Batch too large")]
+    BatchTooLarge {},
 }
 
@@ -1,5 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use escrow_common::hash::HashAlgo;
+use source_escrow::msg::{AssetInfo, IbcForwardParams, SafetyDepositSwap, Timelocks};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -19,6 +21,7 @@ pub enum ExecuteMsg {
         dst_chain_id: String,
         dst_asset: String,
         dst_amount: Uint128,
+        src_amount: Option<Uint128>,
         // Dutch auction parameters
         initial_price: Option<Uint128>,
         price_decay_rate: Option<Uint128>,
@@ -26,6 +29,27 @@ pub enum ExecuteMsg {
         // Partial fill parameters
         allow_partial_fill: bool,
         minimum_fill_amount: Option<Uint128>,
+        dust_threshold: Option<Uint128>,
+        refund_addr: Option<String>,
+        safety_deposit: Option<Coin>,
+        safety_deposit_swap: Option<SafetyDepositSwap>,
+        ibc_forward: Option<IbcForwardParams>,
+        allowed_cw20: Option<Vec<String>>,
+        basket: Option<Vec<AssetInfo>>,
+        withdraw_whitelist: Option<Vec<String>>,
+        on_complete_msg: Option<(String, Binary)>,
+        max_total_extension: Option<u64>,
+        hash_algorithm: Option<HashAlgo>,
+        /// Staged withdrawal and cancellation windows; derived from `timelock` when unset
+        timelocks: Option<Timelocks>,
+        /// Match basket and safety deposit denoms ignoring case; IBC denoms always match exactly
+        case_insensitive_denoms: bool,
+        /// Merkle root of per-fill secret hashes for multi-resolver partial fills
+        merkle_root: Option<String>,
+        /// Equal tranches the Merkle secrets split the deposit into
+        tranche_count: Option<u64>,
+        /// Decimals of the escrow's quoted prices; 6 when unset
+        price_decimals: Option<u8>,
         label: String,
     },
     /// Create a new destination escrow
@@ -37,6 +61,11 @@ pub enum ExecuteMsg {
         src_chain_id: String,
         src_escrow_address: String,
         expected_amount: Uint128,
+        allow_partial_fill: bool,
+        minimum_fill_amount: Option<Uint128>,
+        claim_addr: Option<String>,
+        allowed_cw20: Option<Vec<String>>,
+        hash_algorithm: Option<HashAlgo>,
         label: String,
     },
     /// Update code IDs (owner only)
@@ -46,6 +75,10 @@ pub enum ExecuteMsg {
     },
     /// Update owner
     UpdateOwner { new_owner: String },
+    /// Stop or resume escrow creation (owner only)
+    SetFrozen { frozen: bool },
+    /// Cancel the listed escrows and refund their depositors, ignoring timelocks (owner only)
+    EmergencyCancel { salts: Vec<String> },
 }
 
 #[cw_serde]
@@ -57,12 +90,29 @@ pub enum QueryMsg {
     /// Get escrow address by salt
     #[returns(EscrowAddressResponse)]
     EscrowAddress { salt: String },
+    /// Get an instantiated escrow by its contract address
+    #[returns(EscrowInfo)]
+    EscrowByAddress { address: String },
     /// List all created escrows
     #[returns(EscrowListResponse)]
     EscrowList {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// List escrows created by one address, ordered by salt
+    #[returns(EscrowListResponse)]
+    EscrowsByCreator {
+        creator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Sum of funds still locked in live escrows for a native denom or cw20 address, one page at a time
+    #[returns(TotalValueLockedResponse)]
+    TotalValueLocked {
+        denom: String,
+        continue_from: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -70,6 +120,7 @@ pub struct ConfigResponse {
     pub owner: Addr,
     pub source_escrow_code_id: u64,
     pub destination_escrow_code_id: u64,
+    pub frozen: bool,
 }
 
 #[cw_serde]
@@ -82,6 +133,14 @@ pub struct EscrowListResponse {
     pub escrows: Vec<EscrowInfo>,
 }
 
+#[cw_serde]
+pub struct TotalValueLockedResponse {
+    pub denom: String,
+    pub total: Uint128,
+    /// Salt to pass as `continue_from` for the next page; `None` once all escrows are counted
+    pub continue_from: Option<String>,
+}
+
 #[cw_serde]
 pub struct EscrowInfo {
     pub address: Addr,
@@ -10,8 +10,17 @@ pub struct Config {
     pub owner: Addr,
     pub source_escrow_code_id: u64,
     pub destination_escrow_code_id: u64,
+    /// Blocks new escrows while set; existing escrows are unaffected
+    pub frozen: bool,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const ESCROWS: Map<String, EscrowInfo> = Map::new("escrows");
+/// Salts of escrows by creator, for per-creator listings
+pub const ESCROWS_BY_CREATOR: Map<(&Addr, &str), ()> = Map::new("escrows_by_creator");
+/// Salt of each instantiated escrow, by its contract address
+pub const ESCROWS_BY_ADDRESS: Map<&Addr, String> = Map::new("escrows_by_address");
+/// Salt of each escrow awaiting its instantiate reply, by reply id
+pub const PENDING_INSTANTIATES: Map<u64, String> = Map::new("pending_instantiates");
+pub const INSTANTIATE_COUNT: Item<u64> = Item::new("instantiate_count");
 
@@ -1,23 +1,53 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    WasmMsg, CosmosMsg
+    to_binary, Addr, Binary, BlockInfo, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    WasmMsg, CosmosMsg, BankMsg, Coin, Reply, Storage, SubMsg, to_vec
 };
+use sha2::{Digest, Sha256};
 use cw2::set_contract_version;
+use escrow_common::hash::HashAlgo;
+use escrow_common::validation::{
+    validate_amounts, validate_auction_params, validate_identifier, validate_minimum_fill, validate_timelock,
+};
+use escrow_factory::msg::EscrowType;
 
 use crate::error::ContractError;
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, QueryMsg, OrderAction, ConfigResponse, OrderResponse,
-    OrderListResponse, PriceResponse, RelayerResponse, OrderStatus, DutchAuctionInfo,
-    PartialFillInfo
+    AccruedFeesResponse, ExecuteMsg, FeaturesResponse, HealthResponse, InstantiateMsg, QueryMsg, OrderAction, ConfigResponse, OrderResponse,
+    OrderListResponse, OrderSummary, ExportOrdersResponse, OrderEvent, OrderEventRef, EventsSinceResponse, OrdersByPriceResponse, PricedOrder, PriceResponse, SignedSrcOrder, SignedWithdrawal, ImportedOrder, PreviewDeployResponse, TimelockScheduleResponse, EvaluateFillResponse, RelayerResponse, RelayerScopeResponse, TakerResponse, SourceHeadResponse, PerformanceStatsResponse, OrderStatus,
+    DecayBasis, DutchAuctionInfo, DisputeInfo, DisputeOutcome, PartialFillInfo, ExpiryAction, RelistParams
 };
-use crate::state::{Config, Order, CONFIG, ORDERS, ORDER_COUNT};
+use source_escrow::msg::{IbcForwardParams, SafetyDepositSwap, Timelocks};
+use crate::state::{Config, Order, orders, ORDER_EVENTS, EVENT_COUNT, ARCHIVED_ORDERS, CONFIG, ORDER_COUNT, REGISTERED_TAKERS, SOURCE_HEADS,
+    PERFORMANCE, COMPLETION_BUCKETS, MAKER_PUBKEYS, SIGNED_ORDERS, RELAYER_EXPIRIES, CHAIN_RELAYERS, ACCRUED_FEES, ACCRUED_CW20_FEES, ACCRUED_SURPLUS, FEE_EXEMPT,
+    ESCROW_REPLY_COUNT, PENDING_ESCROWS, PENDING_FUNDING, FUNDING_CHECK};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:escrow_resolver";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Page size bounds for order exports
+const DEFAULT_EXPORT_PAGE_SIZE: u32 = 100;
+const MAX_EXPORT_PAGE_SIZE: u32 = 250;
+
+// Basis points denominator for fee math
+const BPS_DENOMINATOR: u128 = 10_000;
+// Upper bound on orders moved by a single ArchiveOrders call
+const MAX_ARCHIVE_LIMIT: u32 = 100;
+// Upper bound on orders written by a single ImportOrders call
+const MAX_IMPORT_ORDERS: usize = 100;
+// Upper bound on orders confirmed in a single BatchConfirmSource
+const MAX_BATCH_CONFIRMATIONS: usize = 50;
+// Upper bound on auctions refreshed by a single UpdatePricesForChain
+const MAX_PRICE_UPDATES: u32 = 100;
+// Price precision assumed when a deploy does not give one, matching micro-denominated assets
+const DEFAULT_PRICE_DECIMALS: u8 = 6;
+// Escrow creations reply with this base plus a per-creation counter
+const ESCROW_REPLY_ID_BASE: u64 = 1_000;
+// Reply ID for the deposit that funds a freshly created source escrow
+const FUNDING_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -33,10 +63,33 @@ pub fn instantiate(
         authorized_relayers.push(deps.api.addr_validate(&relayer)?);
     }
 
+    if u128::from(msg.fee_bps) > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidFeeConfig {});
+    }
+    let fee_collector = match msg.fee_collector {
+        Some(collector) => deps.api.addr_validate(&collector)?,
+        None => owner.clone(),
+    };
+
     let config = Config {
         owner: owner.clone(),
         escrow_factory,
         authorized_relayers,
+        require_registered_taker: msg.require_registered_taker,
+        min_confirmations: msg.min_confirmations,
+        fast_fill_window: 0,
+        fast_fill_rebate_bps: 0,
+        cancel_grace_period: 0,
+        price_improvement_maker_bps: 0,
+        price_improvement_collector: None,
+        relayer_exclusive_window: 0,
+        proof_required: false,
+        refund_overpayment: false,
+        require_funded_destination: false,
+        default_hash_algo: HashAlgo::default(),
+        fee_bps: msg.fee_bps,
+        fee_collector,
+        paused: false,
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -65,32 +118,68 @@ pub fn execute(
             dst_chain_id,
             dst_asset,
             dst_amount,
+            src_amount,
             initial_price,
             price_decay_rate,
             minimum_price,
+            decay_basis,
+            min_settle_price,
+            price_decimals,
             allow_partial_fill,
             minimum_fill_amount,
+            dust_threshold,
             lop_order_data,
+            on_expiry,
+            src_refund_addr,
+            safety_deposit,
+            safety_deposit_swap,
+            ibc_forward,
+            allowed_cw20,
+            relayer_reward,
+            principal,
+            relist,
+            hash_algo,
             label,
         } => execute_deploy_src(
             deps,
             env,
             info,
-            maker,
-            taker,
-            secret_hash,
-            timelock,
-            dst_chain_id,
-            dst_asset,
-            dst_amount,
-            initial_price,
-            price_decay_rate,
-            minimum_price,
-            allow_partial_fill,
-            minimum_fill_amount,
-            lop_order_data,
-            label,
+            DeploySrcParams {
+                maker,
+                taker,
+                secret_hash,
+                timelock,
+                dst_chain_id,
+                dst_asset,
+                dst_amount,
+                src_amount,
+                initial_price,
+                price_decay_rate,
+                minimum_price,
+                decay_basis,
+                min_settle_price,
+                price_decimals,
+                allow_partial_fill,
+                minimum_fill_amount,
+                dust_threshold,
+                lop_order_data,
+                on_expiry,
+                src_refund_addr,
+                safety_deposit,
+                safety_deposit_swap,
+                ibc_forward,
+                allowed_cw20,
+                relayer_reward,
+                principal,
+                relist,
+                hash_algo,
+                label,
+            },
         ),
+        ExecuteMsg::DeploySrcSigned { order, maker_signature } => {
+            execute_deploy_src_signed(deps, env, info, order, maker_signature)
+        }
+        ExecuteMsg::RegisterMakerPubkey { pubkey } => execute_register_maker_pubkey(deps, info, pubkey),
         ExecuteMsg::DeployDst {
             taker,
             maker,
@@ -99,6 +188,10 @@ pub fn execute(
             src_chain_id,
             src_escrow_address,
             expected_amount,
+            dst_claim_addr,
+            allowed_cw20,
+            src_order_id,
+            hash_algo,
             label,
         } => execute_deploy_dst(
             deps,
@@ -111,11 +204,18 @@ pub fn execute(
             src_chain_id,
             src_escrow_address,
             expected_amount,
+            dst_claim_addr,
+            allowed_cw20,
+            src_order_id,
+            hash_algo,
             label,
         ),
         ExecuteMsg::Withdraw { escrow_address, secret } => {
             execute_withdraw(deps, env, info, escrow_address, secret)
         }
+        ExecuteMsg::WithdrawSigned { order_id, signature, secret } => {
+            execute_withdraw_signed(deps, env, info, order_id, signature, secret)
+        }
         ExecuteMsg::PartialWithdraw { escrow_address, secret, amount } => {
             execute_partial_withdraw(deps, env, info, escrow_address, secret, amount)
         }
@@ -125,11 +225,31 @@ pub fn execute(
         ExecuteMsg::UpdatePrice { escrow_address } => {
             execute_update_price(deps, env, info, escrow_address)
         }
+        ExecuteMsg::UpdatePricesForChain { chain_id, limit } => {
+            execute_update_prices_for_chain(deps, env, chain_id, limit)
+        }
+        ExecuteMsg::ExpireOrder { order_id } => {
+            execute_expire_order(deps, env, info, order_id)
+        }
+        ExecuteMsg::MigrateOrderEscrow { order_id } => {
+            execute_migrate_order_escrow(deps, env, info, order_id)
+        }
+        ExecuteMsg::UpdateSecretHash { order_id, new_secret_hash } => {
+            execute_update_secret_hash(deps, env, info, order_id, new_secret_hash)
+        }
+        ExecuteMsg::CancelBoth { order_id } => execute_cancel_both(deps, env, info, order_id),
+        ExecuteMsg::SyncBothLegs { order_id } => execute_sync_both_legs(deps, env, order_id),
         ExecuteMsg::ProcessOrder { order_id, action, proof } => {
             execute_process_order(deps, env, info, order_id, action, proof)
         }
-        ExecuteMsg::AddRelayer { relayer } => {
-            execute_add_relayer(deps, info, relayer)
+        ExecuteMsg::BatchConfirmSource { confirmations, block_height, proof } => {
+            execute_batch_confirm_source(deps, env, info, confirmations, block_height, proof)
+        }
+        ExecuteMsg::AddRelayer { relayer, ttl } => {
+            execute_add_relayer(deps, env, info, relayer, ttl)
+        }
+        ExecuteMsg::SetChainRelayers { chain_id, relayers } => {
+            execute_set_chain_relayers(deps, info, chain_id, relayers)
         }
         ExecuteMsg::RemoveRelayer { relayer } => {
             execute_remove_relayer(deps, info, relayer)
@@ -137,35 +257,186 @@ pub fn execute(
         ExecuteMsg::UpdateOwner { new_owner } => {
             execute_update_owner(deps, info, new_owner)
         }
+        ExecuteMsg::RegisterTaker { taker } => execute_register_taker(deps, info, taker),
+        ExecuteMsg::SetFeeExempt { maker, exempt } => execute_set_fee_exempt(deps, info, maker, exempt),
+        ExecuteMsg::DeregisterTaker { taker } => execute_deregister_taker(deps, info, taker),
+        ExecuteMsg::SetRequireRegisteredTaker { required } => {
+            execute_set_require_registered_taker(deps, info, required)
+        }
+        ExecuteMsg::UpdateSourceHead { chain_id, height } => {
+            execute_update_source_head(deps, env, info, chain_id, height)
+        }
+        ExecuteMsg::SetMinConfirmations { min_confirmations } => {
+            execute_set_min_confirmations(deps, info, min_confirmations)
+        }
+        ExecuteMsg::ArchiveOrders { before_ts, limit } => {
+            execute_archive_orders(deps, env, info, before_ts, limit)
+        }
+        ExecuteMsg::ImportOrders { orders } => execute_import_orders(deps, info, orders),
+        ExecuteMsg::SetFastFillRebate { window, rebate_bps } => {
+            execute_set_fast_fill_rebate(deps, info, window, rebate_bps)
+        }
+        ExecuteMsg::SetOrderFrozen { order_id, frozen } => {
+            execute_set_order_frozen(deps, env, info, order_id, frozen)
+        }
+        ExecuteMsg::SetOrderAcceptingFills { order_id, accepting } => {
+            execute_set_order_accepting_fills(deps, env, info, order_id, accepting)
+        }
+        ExecuteMsg::SetCancelGracePeriod { seconds } => {
+            execute_set_cancel_grace_period(deps, info, seconds)
+        }
+        ExecuteMsg::AssignRelayer { order_id, relayer } => {
+            execute_assign_relayer(deps, env, info, order_id, relayer)
+        }
+        ExecuteMsg::SetRelayerExclusiveWindow { seconds } => {
+            execute_set_relayer_exclusive_window(deps, info, seconds)
+        }
+        ExecuteMsg::SetProofRequired { required } => execute_set_proof_required(deps, info, required),
+        ExecuteMsg::SetRefundOverpayment { enabled } => execute_set_refund_overpayment(deps, info, enabled),
+        ExecuteMsg::SetRequireFundedDestination { required } => {
+            execute_set_require_funded_destination(deps, info, required)
+        }
+        ExecuteMsg::SetDefaultHashAlgo { hash_algo } => execute_set_default_hash_algo(deps, info, hash_algo),
+        ExecuteMsg::RaiseDispute { order_id, evidence_hash } => {
+            execute_raise_dispute(deps, env, info, order_id, evidence_hash)
+        }
+        ExecuteMsg::ResolveDispute { order_id, outcome } => {
+            execute_resolve_dispute(deps, env, info, order_id, outcome)
+        }
+        ExecuteMsg::CancelWithSecret { order_id, secret } => {
+            execute_cancel_with_secret(deps, env, info, order_id, secret)
+        }
+        ExecuteMsg::VetoCancel { order_id } => execute_veto_cancel(deps, env, info, order_id),
+        ExecuteMsg::SetPriceImprovementSplit { maker_bps, collector } => {
+            execute_set_price_improvement_split(deps, info, maker_bps, collector)
+        }
+        ExecuteMsg::WithdrawFees { to } => execute_withdraw_fees(deps, info, to),
+        ExecuteMsg::UpdateFee { fee_bps, fee_collector } => execute_update_fee(deps, info, fee_bps, fee_collector),
+        ExecuteMsg::SetPaused { paused } => execute_set_paused(deps, info, paused),
     }
 }
 
+/// Terms of a source order deploy, shared by direct, signed and re-listed deploys.
+#[derive(Default)]
+pub struct DeploySrcParams {
+    pub maker: String,
+    pub taker: Option<String>,
+    pub secret_hash: String,
+    pub timelock: u64,
+    pub dst_chain_id: String,
+    pub dst_asset: String,
+    pub dst_amount: Uint128,
+    pub src_amount: Option<Uint128>,
+    pub initial_price: Option<Uint128>,
+    pub price_decay_rate: Option<Uint128>,
+    pub minimum_price: Option<Uint128>,
+    pub decay_basis: Option<DecayBasis>,
+    pub min_settle_price: Option<Uint128>,
+    pub price_decimals: Option<u8>,
+    pub allow_partial_fill: bool,
+    pub minimum_fill_amount: Option<Uint128>,
+    pub dust_threshold: Option<Uint128>,
+    pub lop_order_data: Option<String>,
+    pub on_expiry: Option<ExpiryAction>,
+    pub src_refund_addr: Option<String>,
+    pub safety_deposit: Option<Coin>,
+    pub safety_deposit_swap: Option<SafetyDepositSwap>,
+    pub ibc_forward: Option<IbcForwardParams>,
+    pub allowed_cw20: Option<Vec<String>>,
+    pub relayer_reward: Option<Coin>,
+    pub principal: Option<Coin>,
+    pub relist: Option<RelistParams>,
+    pub hash_algo: Option<HashAlgo>,
+    pub label: String,
+}
+
 pub fn execute_deploy_src(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    maker: String,
-    taker: Option<String>,
-    secret_hash: String,
-    timelock: u64,
-    dst_chain_id: String,
-    dst_asset: String,
-    dst_amount: Uint128,
-    initial_price: Option<Uint128>,
-    price_decay_rate: Option<Uint128>,
-    minimum_price: Option<Uint128>,
-    allow_partial_fill: bool,
-    minimum_fill_amount: Option<Uint128>,
-    lop_order_data: Option<String>,
-    label: String,
+    params: DeploySrcParams,
 ) -> Result<Response, ContractError> {
+    let DeploySrcParams {
+        maker,
+        taker,
+        secret_hash,
+        timelock,
+        dst_chain_id,
+        dst_asset,
+        dst_amount,
+        src_amount,
+        initial_price,
+        price_decay_rate,
+        minimum_price,
+        decay_basis,
+        min_settle_price,
+        price_decimals,
+        allow_partial_fill,
+        minimum_fill_amount,
+        dust_threshold,
+        lop_order_data,
+        on_expiry,
+        src_refund_addr,
+        safety_deposit,
+        safety_deposit_swap,
+        ibc_forward,
+        allowed_cw20,
+        relayer_reward,
+        principal,
+        relist,
+        hash_algo,
+        label,
+    } = params;
+
     let config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config)?;
     
     // Only owner or authorized relayers can deploy escrows
-    if info.sender != config.owner && !config.authorized_relayers.contains(&info.sender) {
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
         return Err(ContractError::Unauthorized {});
     }
 
+    let taker_addr = taker.as_ref().map(|t| deps.api.addr_validate(t)).transpose()?;
+    ensure_taker_registered(deps.as_ref(), &config, taker_addr.as_ref())?;
+
+    let src_refund_addr = src_refund_addr.map(|a| deps.api.addr_validate(&a)).transpose()?;
+
+    validate_minimum_fill(allow_partial_fill, minimum_fill_amount, src_amount.unwrap_or(dst_amount))?;
+    validate_identifier(&dst_chain_id)?;
+    validate_identifier(&dst_asset)?;
+    validate_timelock(timelock, env.block.time.seconds())?;
+    validate_amounts(dst_amount, src_amount)?;
+    validate_auction_params(initial_price, price_decay_rate, minimum_price)?;
+
+    // The principal is deposited as it is, so it cannot cover a safety deposit as well
+    if let Some(principal) = &principal {
+        if principal.amount.is_zero() || safety_deposit.is_some() {
+            return Err(ContractError::InvalidOrderParameters {});
+        }
+    }
+    // The reward and principal are all a deploy carries, so anything else attached would be stranded
+    let mut expected_funds: Vec<Coin> = vec![];
+    for coin in relayer_reward.iter().chain(principal.iter()) {
+        match expected_funds.iter_mut().find(|expected| expected.denom == coin.denom) {
+            Some(expected) => expected.amount += coin.amount,
+            None => expected_funds.push(coin.clone()),
+        }
+    }
+    if !expected_funds.is_empty() {
+        let mut attached = info.funds.clone();
+        attached.sort_by(|a, b| a.denom.cmp(&b.denom));
+        expected_funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+        if attached != expected_funds {
+            return Err(match principal {
+                Some(_) => ContractError::DeployFundsMismatch {},
+                None => ContractError::MissingRelayerReward {},
+            });
+        }
+    }
+    let reward_payer = relayer_reward.as_ref().map(|_| info.sender.clone());
+
+    let hash_algo = hash_algo.unwrap_or_else(|| config.default_hash_algo.clone());
+
     // Generate order ID
     let mut order_count = ORDER_COUNT.load(deps.storage)?;
     order_count += 1;
@@ -183,11 +454,28 @@ pub fn execute_deploy_src(
             dst_chain_id: dst_chain_id.clone(),
             dst_asset,
             dst_amount,
+            src_amount,
             initial_price,
             price_decay_rate,
             minimum_price,
             allow_partial_fill,
             minimum_fill_amount,
+            dust_threshold,
+            refund_addr: src_refund_addr.as_ref().map(|a| a.to_string()),
+            safety_deposit,
+            safety_deposit_swap,
+            ibc_forward,
+            allowed_cw20,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: None,
+            hash_algorithm: Some(hash_algo.clone()),
+            timelocks: None,
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            tranche_count: None,
+            price_decimals,
             label: label.clone(),
         })?,
         funds: vec![],
@@ -201,19 +489,36 @@ pub fn execute_deploy_src(
             minimum_price: min_price,
             price_decay_rate: decay_rate,
             start_time: env.block.time.seconds(),
+            start_height: env.block.height,
+            decay_basis: decay_basis.unwrap_or(DecayBasis::Time),
             current_price: init_price,
+            min_settle_price,
+            price_decimals: price_decimals.unwrap_or(DEFAULT_PRICE_DECIMALS),
         })
     } else {
         None
     };
+    // A settle floor only applies to an auction, and one above the start price could never fill
+    if let Some(min_settle_price) = min_settle_price {
+        match &dutch_auction {
+            Some(auction) if min_settle_price <= auction.initial_price => {}
+            _ => return Err(ContractError::InvalidOrderParameters {}),
+        }
+    }
 
     // Create partial fill info if enabled
     let partial_fill = if allow_partial_fill {
         Some(PartialFillInfo {
             allow_partial_fill: true,
             minimum_fill_amount,
+            dust_threshold,
             filled_amount: Uint128::zero(),
-            remaining_amount: dst_amount,
+            // The source leg is filled in its own asset
+            remaining_amount: src_amount.unwrap_or(dst_amount),
+            src_filled: Uint128::zero(),
+            src_remaining: src_amount.unwrap_or(dst_amount),
+            dst_filled: Uint128::zero(),
+            dst_remaining: dst_amount,
         })
     } else {
         None
@@ -222,25 +527,54 @@ pub fn execute_deploy_src(
     // Store order information
     let order = Order {
         order_id: order_id.clone(),
-        escrow_address: deps.api.addr_validate("pending")?, // Will be updated when escrow is created
+        escrow_address: deps.api.addr_validate("pending")?, // Set by the factory reply
+        escrow_type: EscrowType::Source,
         maker: deps.api.addr_validate(&maker)?,
-        taker: taker.as_ref().map(|t| deps.api.addr_validate(t)).transpose()?,
+        taker: taker_addr,
+        secret_hash,
+        src_amount,
+        dst_amount,
         status: OrderStatus::Active,
         created_at: env.block.time.seconds(),
         updated_at: env.block.time.seconds(),
         dutch_auction,
         partial_fill,
         lop_order_data,
+        on_expiry: on_expiry.unwrap_or(ExpiryAction::CancelRefund),
+        src_refund_addr,
+        dst_claim_addr: None,
+        counterparty_order_id: None,
+        src_chain_id: None,
+        dst_chain_id: Some(dst_chain_id.clone()),
+        filled_at: None,
+        frozen: false,
+        accepting_fills: true,
+        cancel_effective_at: None,
+        assigned_relayer: None,
+        exclusive_until: None,
+        relayer_reward,
+        reward_payer,
+        dispute: None,
+        relist,
+        relisted_as: None,
+        hash_algo: hash_algo.clone(),
+        fee_paid: None,
     };
 
-    ORDERS.save(deps.storage, order_id.clone(), &order)?;
+    orders().save(deps.storage, order_id.clone(), &order)?;
+    record_event(deps.storage, &env.block, &order)?;
+    let create_escrow_msg = create_escrow_sub_msg(deps.storage, &order_id, create_escrow_msg)?;
+    if let Some(principal) = &principal {
+        PENDING_FUNDING.save(deps.storage, &order_id, principal)?;
+    }
 
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(create_escrow_msg))
+        .add_submessage(create_escrow_msg)
         .add_attribute("method", "deploy_src")
         .add_attribute("order_id", order_id)
         .add_attribute("maker", maker)
-        .add_attribute("dst_chain_id", dst_chain_id))
+        .add_attribute("dst_chain_id", dst_chain_id)
+        .add_attribute("hash_algo", hash_algo.as_str()))
 }
 
 pub fn execute_deploy_dst(
@@ -254,21 +588,64 @@ pub fn execute_deploy_dst(
     src_chain_id: String,
     src_escrow_address: String,
     expected_amount: Uint128,
+    dst_claim_addr: Option<String>,
+    allowed_cw20: Option<Vec<String>>,
+    src_order_id: Option<String>,
+    hash_algo: Option<HashAlgo>,
     label: String,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config)?;
     
-    // Only owner or authorized relayers can deploy escrows
-    if info.sender != config.owner && !config.authorized_relayers.contains(&info.sender) {
+    // Only owner or relayers serving the source chain can deploy escrows
+    if info.sender != config.owner
+        && !is_chain_relayer(deps.storage, &config, Some(&src_chain_id), &info.sender, env.block.time.seconds())?
+    {
         return Err(ContractError::Unauthorized {});
     }
 
+    let taker_addr = deps.api.addr_validate(&taker)?;
+    ensure_taker_registered(deps.as_ref(), &config, Some(&taker_addr))?;
+    validate_identifier(&src_chain_id)?;
+    validate_timelock(timelock, env.block.time.seconds())?;
+    validate_amounts(expected_amount, None)?;
+
+    let dst_claim_addr = dst_claim_addr.map(|a| deps.api.addr_validate(&a)).transpose()?;
+
+    let hash_algo = hash_algo.unwrap_or_else(|| config.default_hash_algo.clone());
+
     // Generate order ID
     let mut order_count = ORDER_COUNT.load(deps.storage)?;
     order_count += 1;
     ORDER_COUNT.save(deps.storage, &order_count)?;
     let order_id = format!("order_{}", order_count);
 
+    // Link to the source-leg order so partial fills can be mirrored on this side
+    let mut partial_fill = None;
+    if let Some(src_order_id) = &src_order_id {
+        let mut src_order = orders().load(deps.storage, src_order_id.clone())?;
+        if src_order.escrow_type != EscrowType::Source {
+            return Err(ContractError::InvalidOrderParameters {});
+        }
+        if let Some(src_fill) = src_order.partial_fill.as_mut() {
+            // The destination leg's expected amount fixes the settlement price for the pair
+            src_fill.dst_remaining = expected_amount;
+            partial_fill = Some(PartialFillInfo {
+                allow_partial_fill: true,
+                minimum_fill_amount: None,
+                dust_threshold: None,
+                filled_amount: Uint128::zero(),
+                remaining_amount: expected_amount,
+                src_filled: src_fill.src_filled,
+                src_remaining: src_fill.src_remaining,
+                dst_filled: src_fill.dst_filled,
+                dst_remaining: src_fill.dst_remaining,
+            });
+        }
+        src_order.counterparty_order_id = Some(order_id.clone());
+        orders().save(deps.storage, src_order_id.clone(), &src_order)?;
+    }
+
     // Create escrow through factory
     let create_escrow_msg = WasmMsg::Execute {
         contract_addr: config.escrow_factory.to_string(),
@@ -280,6 +657,11 @@ pub fn execute_deploy_dst(
             src_chain_id: src_chain_id.clone(),
             src_escrow_address: src_escrow_address.clone(),
             expected_amount,
+            allow_partial_fill: partial_fill.is_some(),
+            minimum_fill_amount: None,
+            claim_addr: dst_claim_addr.as_ref().map(|a| a.to_string()),
+            allowed_cw20,
+            hash_algorithm: Some(hash_algo.clone()),
             label: label.clone(),
         })?,
         funds: vec![],
@@ -288,26 +670,125 @@ pub fn execute_deploy_dst(
     // Store order information
     let order = Order {
         order_id: order_id.clone(),
-        escrow_address: deps.api.addr_validate("pending")?, // Will be updated when escrow is created
+        escrow_address: deps.api.addr_validate("pending")?, // Set by the factory reply
+        escrow_type: EscrowType::Destination,
         maker: deps.api.addr_validate(&maker)?,
-        taker: Some(deps.api.addr_validate(&taker)?),
+        taker: Some(taker_addr),
+        secret_hash,
+        src_amount: None,
+        dst_amount: expected_amount,
         status: OrderStatus::Active,
         created_at: env.block.time.seconds(),
         updated_at: env.block.time.seconds(),
         dutch_auction: None,
-        partial_fill: None,
+        partial_fill,
         lop_order_data: None,
+        on_expiry: ExpiryAction::CancelRefund,
+        src_refund_addr: None,
+        dst_claim_addr,
+        counterparty_order_id: src_order_id,
+        src_chain_id: Some(src_chain_id.clone()),
+        dst_chain_id: None,
+        filled_at: None,
+        frozen: false,
+        accepting_fills: true,
+        cancel_effective_at: None,
+        assigned_relayer: None,
+        exclusive_until: None,
+        relayer_reward: None,
+        reward_payer: None,
+        dispute: None,
+        relist: None,
+        relisted_as: None,
+        hash_algo: hash_algo.clone(),
+        fee_paid: None,
     };
 
-    ORDERS.save(deps.storage, order_id.clone(), &order)?;
+    orders().save(deps.storage, order_id.clone(), &order)?;
+    record_event(deps.storage, &env.block, &order)?;
+    let create_escrow_msg = create_escrow_sub_msg(deps.storage, &order_id, create_escrow_msg)?;
 
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(create_escrow_msg))
+        .add_submessage(create_escrow_msg)
         .add_attribute("method", "deploy_dst")
         .add_attribute("order_id", order_id)
         .add_attribute("taker", taker)
         .add_attribute("maker", maker)
-        .add_attribute("src_chain_id", src_chain_id))
+        .add_attribute("src_chain_id", src_chain_id)
+        .add_attribute("hash_algo", hash_algo.as_str()))
+}
+
+pub fn execute_deploy_src_signed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order: SignedSrcOrder,
+    maker_signature: Binary,
+) -> Result<Response, ContractError> {
+    if order.resolver != env.contract.address {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let maker = deps.api.addr_validate(&order.maker)?;
+    let pubkey = MAKER_PUBKEYS
+        .may_load(deps.storage, &maker)?
+        .ok_or(ContractError::MakerPubkeyNotRegistered {})?;
+
+    let order_hash = Sha256::digest(to_vec(&order)?);
+    let verified = deps
+        .api
+        .secp256k1_verify(&order_hash, &maker_signature, &pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    if SIGNED_ORDERS.has(deps.storage, &order_hash) {
+        return Err(ContractError::SignedOrderUsed {});
+    }
+    let order_id = format!("order_{}", ORDER_COUNT.load(deps.storage)? + 1);
+    SIGNED_ORDERS.save(deps.storage, &order_hash, &order_id)?;
+
+    let order_hash_hex: String = order_hash.iter().map(|b| format!("{:02x}", b)).collect();
+    let res = execute_deploy_src(
+        deps,
+        env,
+        info,
+        DeploySrcParams {
+            maker: order.maker,
+            taker: order.taker,
+            secret_hash: order.secret_hash,
+            timelock: order.timelock,
+            dst_chain_id: order.dst_chain_id,
+            dst_asset: order.dst_asset,
+            dst_amount: order.dst_amount,
+            src_amount: order.src_amount,
+            initial_price: order.initial_price,
+            price_decay_rate: order.price_decay_rate,
+            minimum_price: order.minimum_price,
+            allow_partial_fill: order.allow_partial_fill,
+            minimum_fill_amount: order.minimum_fill_amount,
+            label: order.label,
+            ..DeploySrcParams::default()
+        },
+    )?;
+    Ok(res.add_attribute("signed_order_hash", order_hash_hex))
+}
+
+pub fn execute_register_maker_pubkey(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Binary,
+) -> Result<Response, ContractError> {
+    // Compressed or uncompressed secp256k1 keys only
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+        return Err(ContractError::InvalidSignature {});
+    }
+    MAKER_PUBKEYS.save(deps.storage, &info.sender, &pubkey)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "register_maker_pubkey")
+        .add_attribute("maker", info.sender))
 }
 
 pub fn execute_withdraw(
@@ -318,9 +799,10 @@ pub fn execute_withdraw(
     secret: String,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config)?;
     
     // Only owner or authorized relayers can execute withdrawals
-    if info.sender != config.owner && !config.authorized_relayers.contains(&info.sender) {
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -334,27 +816,102 @@ pub fn execute_withdraw(
     };
 
     // Update order status if found
-    let orders: Vec<_> = ORDERS
+    let all_orders: Vec<_> = orders()
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
         .collect::<StdResult<Vec<_>>>()?;
 
-    for (order_id, mut order) in orders {
+    let mut reward_msgs = vec![];
+    for (order_id, mut order) in all_orders {
         if order.escrow_address == escrow_addr {
-            order.status = OrderStatus::Completed;
+            if is_frozen(&order) {
+                return Err(ContractError::OrderFrozen {});
+            }
+            complete_order(deps.storage, &mut order, &env.block)?;
+            reward_msgs = release_relayer_reward(&mut order, &info.sender);
             order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id, &order)?;
+            orders().save(deps.storage, order_id, &order)?;
             break;
         }
     }
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(withdraw_msg))
+        .add_messages(reward_msgs)
         .add_attribute("method", "withdraw")
         .add_attribute("escrow_address", escrow_address))
 }
 
+/// A swap the maker authorized by signature; apart from the signature it is held to the same checks
+/// and fee as `OrderAction::ExecuteSwap`.
+pub fn execute_withdraw_signed(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    signature: Binary,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config)?;
+
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    if !is_chain_relayer(deps.storage, &config, order_chain(&order), &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::InvalidRelayer {});
+    }
+    if !has_relayer_priority(&order, &info.sender, env.block.time.seconds()) {
+        return Err(ContractError::NotAssignedRelayer {});
+    }
+    if order.escrow_type != EscrowType::Source
+        || !matches!(order.status, OrderStatus::Active | OrderStatus::Matched)
+    {
+        return Err(ContractError::InvalidOrderParameters {});
+    }
+    if is_frozen(&order) {
+        return Err(ContractError::OrderFrozen {});
+    }
+    ensure_accepting_fill(&order, None)?;
+    ensure_settle_price(&order, &env.block)?;
+
+    let pubkey = MAKER_PUBKEYS
+        .may_load(deps.storage, &order.maker)?
+        .ok_or(ContractError::MakerPubkeyNotRegistered {})?;
+    let authorization = SignedWithdrawal {
+        resolver: env.contract.address.to_string(),
+        order_id: order_id.clone(),
+        secret: secret.clone(),
+    };
+    let authorization_hash = Sha256::digest(to_vec(&authorization)?);
+    let verified = deps
+        .api
+        .secp256k1_verify(&authorization_hash, &signature, &pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let withdraw_msg = WasmMsg::Execute {
+        contract_addr: order.escrow_address.to_string(),
+        msg: to_binary(&source_escrow::msg::ExecuteMsg::Withdraw { secret })?,
+        funds: vec![],
+    };
+
+    complete_order(deps.storage, &mut order, &env.block)?;
+    let fee_msgs = collect_swap_fee(deps.branch(), &env, &config, &mut order, &info)?;
+    let reward_msgs = release_relayer_reward(&mut order, &info.sender);
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(withdraw_msg))
+        .add_messages(fee_msgs)
+        .add_messages(reward_msgs)
+        .add_attribute("method", "withdraw_signed")
+        .add_attribute("order_id", order_id)
+        .add_attribute("fast_fill", is_fast_fill(&config, &order).to_string()))
+}
+
 pub fn execute_partial_withdraw(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_address: String,
@@ -362,9 +919,10 @@ pub fn execute_partial_withdraw(
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config)?;
     
     // Only owner or authorized relayers can execute withdrawals
-    if info.sender != config.owner && !config.authorized_relayers.contains(&info.sender) {
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -378,28 +936,38 @@ pub fn execute_partial_withdraw(
     };
 
     // Update order partial fill info if found
-    let orders: Vec<_> = ORDERS
+    let all_orders: Vec<_> = orders()
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
         .collect::<StdResult<Vec<_>>>()?;
 
-    for (order_id, mut order) in orders {
+    let mut surplus_msgs = vec![];
+    let mut reward_msgs = vec![];
+    for (order_id, mut order) in all_orders {
         if order.escrow_address == escrow_addr {
+            if is_frozen(&order) {
+                return Err(ContractError::OrderFrozen {});
+            }
+            ensure_accepting_fill(&order, Some(amount))?;
+            surplus_msgs = price_improvement_split(deps.branch(), &config, &order, &info, amount, &env.block)?;
             if let Some(ref mut partial_fill) = order.partial_fill {
+                record_dual_fill(partial_fill, amount);
                 partial_fill.filled_amount += amount;
                 partial_fill.remaining_amount -= amount;
-                
-                if partial_fill.remaining_amount.is_zero() {
-                    order.status = OrderStatus::Completed;
-                }
+            }
+            if order.partial_fill.as_ref().map_or(false, |p| p.remaining_amount.is_zero()) {
+                complete_order(deps.storage, &mut order, &env.block)?;
+                reward_msgs = release_relayer_reward(&mut order, &info.sender);
             }
             order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id, &order)?;
+            orders().save(deps.storage, order_id, &order)?;
             break;
         }
     }
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(withdraw_msg))
+        .add_messages(surplus_msgs)
+        .add_messages(reward_msgs)
         .add_attribute("method", "partial_withdraw")
         .add_attribute("escrow_address", escrow_address)
         .add_attribute("amount", amount))
@@ -414,7 +982,7 @@ pub fn execute_cancel(
     let config = CONFIG.load(deps.storage)?;
     
     // Only owner or authorized relayers can cancel escrows
-    if info.sender != config.owner && !config.authorized_relayers.contains(&info.sender) {
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -428,21 +996,34 @@ pub fn execute_cancel(
     };
 
     // Update order status if found
-    let orders: Vec<_> = ORDERS
+    let all_orders: Vec<_> = orders()
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
         .collect::<StdResult<Vec<_>>>()?;
 
-    for (order_id, mut order) in orders {
+    let mut refund_msgs = vec![];
+    for (order_id, mut order) in all_orders {
         if order.escrow_address == escrow_addr {
+            // Same veto window as a cancel through ProcessOrder
+            if let Some(effective_at) = open_cancel_grace(deps.storage, &config, &mut order, &env.block)? {
+                return Ok(Response::new()
+                    .add_attribute("method", "cancel")
+                    .add_attribute("action", "cancel_pending")
+                    .add_attribute("escrow_address", escrow_address)
+                    .add_attribute("order_id", order_id)
+                    .add_attribute("cancel_effective_at", effective_at.to_string()));
+            }
             order.status = OrderStatus::Cancelled;
+            record_event(deps.storage, &env.block, &order)?;
+            refund_msgs = refund_relayer_reward(&mut order);
             order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id, &order)?;
+            orders().save(deps.storage, order_id, &order)?;
             break;
         }
     }
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(cancel_msg))
+        .add_messages(refund_msgs)
         .add_attribute("method", "cancel")
         .add_attribute("escrow_address", escrow_address))
 }
@@ -456,31 +1037,19 @@ pub fn execute_update_price(
     let escrow_addr = deps.api.addr_validate(&escrow_address)?;
 
     // Update Dutch auction price for the order
-    let orders: Vec<_> = ORDERS
+    let all_orders: Vec<_> = orders()
         .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
         .collect::<StdResult<Vec<_>>>()?;
 
-    for (order_id, mut order) in orders {
+    for (order_id, mut order) in all_orders {
         if order.escrow_address == escrow_addr {
             if let Some(ref mut dutch_auction) = order.dutch_auction {
                 let current_time = env.block.time.seconds();
-                let time_elapsed = current_time - dutch_auction.start_time;
-                
-                // Calculate new price: price = initial_price - (decay_rate * time_elapsed)
-                let price_decrease = dutch_auction.price_decay_rate.checked_mul(Uint128::from(time_elapsed))
-                    .map_err(|_| ContractError::InvalidOrderParameters {})?;
-                
-                let new_price = if price_decrease >= dutch_auction.initial_price {
-                    dutch_auction.minimum_price
-                } else {
-                    dutch_auction.initial_price.checked_sub(price_decrease)
-                        .map_err(|_| ContractError::InvalidOrderParameters {})?
-                        .max(dutch_auction.minimum_price)
-                };
+                let new_price = calculate_current_price(dutch_auction, &env.block)?;
                 
                 dutch_auction.current_price = new_price;
                 order.updated_at = current_time;
-                ORDERS.save(deps.storage, order_id, &order)?;
+                orders().save(deps.storage, order_id, &order)?;
             }
             break;
         }
@@ -491,257 +1060,5659 @@ pub fn execute_update_price(
         .add_attribute("escrow_address", escrow_address))
 }
 
-pub fn execute_process_order(
+pub fn execute_update_prices_for_chain(
+    deps: DepsMut,
+    env: Env,
+    chain_id: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(MAX_PRICE_UPDATES).min(MAX_PRICE_UPDATES) as usize;
+    let on_chain = |order: &Order| {
+        order.src_chain_id.as_deref() == Some(chain_id.as_str())
+            || order.dst_chain_id.as_deref() == Some(chain_id.as_str())
+    };
+
+    let targets: Vec<(String, Order)> = orders()
+        .idx
+        .status
+        .prefix(OrderStatus::Active.as_str().to_string())
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, order)| order.dutch_auction.is_some() && on_chain(order))
+        })
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let updated = targets.len();
+    for (order_id, mut order) in targets {
+        if let Some(dutch_auction) = order.dutch_auction.as_mut() {
+            dutch_auction.current_price = calculate_current_price(dutch_auction, &env.block)?;
+        }
+        order.updated_at = env.block.time.seconds();
+        orders().save(deps.storage, order_id, &order)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "update_prices_for_chain")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("updated", updated.to_string()))
+}
+
+pub fn execute_expire_order(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     order_id: String,
-    action: OrderAction,
-    _proof: Option<String>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     
-    // Only authorized relayers can process orders
-    if !config.authorized_relayers.contains(&info.sender) {
-        return Err(ContractError::InvalidRelayer {});
+    // Only owner or authorized relayers can expire orders
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::Unauthorized {});
     }
 
-    let mut order = ORDERS.load(deps.storage, order_id.clone())?;
+    let mut order = orders().load(deps.storage, order_id.clone())?;
 
-    match action {
-        OrderAction::ConfirmSource { src_tx_hash, block_height } => {
-            // Confirm source escrow on destination chain
-            let confirm_msg = WasmMsg::Execute {
-                contract_addr: order.escrow_address.to_string(),
-                msg: to_binary(&destination_escrow::msg::ExecuteMsg::ConfirmSourceEscrow {
-                    src_tx_hash,
-                    block_height,
-                })?,
-                funds: vec![],
-            };
+    if order.status != OrderStatus::Active {
+        return Err(ContractError::InvalidOrderParameters {});
+    }
 
-            order.status = OrderStatus::Matched;
-            order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id.clone(), &order)?;
+    let current_time = env.block.time.seconds();
+    let dutch_auction = order
+        .dutch_auction
+        .as_mut()
+        .ok_or(ContractError::DutchAuctionNotActive {})?;
 
-            Ok(Response::new()
-                .add_message(CosmosMsg::Wasm(confirm_msg))
-                .add_attribute("method", "process_order")
-                .add_attribute("action", "confirm_source")
-                .add_attribute("order_id", order_id))
-        }
-        OrderAction::ExecuteSwap { secret } => {
-            // Execute the swap by withdrawing from escrow
-            let withdraw_msg = WasmMsg::Execute {
+    // The auction only expires once the live price has decayed to the floor
+    let current_price = calculate_current_price(dutch_auction, &env.block)?;
+    if current_price > dutch_auction.minimum_price {
+        return Err(ContractError::OrderNotExpired {});
+    }
+
+    let mut messages = vec![];
+    let disposition = match order.on_expiry {
+        ExpiryAction::CancelRefund => {
+            // The escrow refunds the maker once its timelock passes, or at once while it holds nothing
+            let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &source_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            let unfunded = escrow.deposited_amount.is_zero() && !escrow.safety_deposit_funded;
+            if current_time < escrow.timelock && !unfunded {
+                return Err(ContractError::OrderNotExpired {});
+            }
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: order.escrow_address.to_string(),
-                msg: to_binary(&source_escrow::msg::ExecuteMsg::Withdraw { secret })?,
+                msg: to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?,
                 funds: vec![],
-            };
-
-            order.status = OrderStatus::Completed;
-            order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id.clone(), &order)?;
-
-            Ok(Response::new()
-                .add_message(CosmosMsg::Wasm(withdraw_msg))
-                .add_attribute("method", "process_order")
-                .add_attribute("action", "execute_swap")
-                .add_attribute("order_id", order_id))
+            }));
+            order.status = OrderStatus::Expired;
+            record_event(deps.storage, &env.block, &order)?;
+            messages.extend(refund_relayer_reward(&mut order));
+            "cancel_refund"
         }
-        OrderAction::CancelOrder => {
-            // Cancel the order
-            let cancel_msg = WasmMsg::Execute {
+        ExpiryAction::RestAtMinimum => {
+            // Freeze the price at the floor so the order rests as a fixed-price order
+            dutch_auction.initial_price = dutch_auction.minimum_price;
+            dutch_auction.price_decay_rate = Uint128::zero();
+            dutch_auction.current_price = dutch_auction.minimum_price;
+            dutch_auction.start_time = current_time;
+            dutch_auction.start_height = env.block.height;
+            // The escrow prices fills itself, so it has to stop decaying too
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: order.escrow_address.to_string(),
-                msg: to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?,
+                msg: to_binary(&source_escrow::msg::ExecuteMsg::RestAtMinimum {})?,
                 funds: vec![],
-            };
+            }));
+            "rest_at_minimum"
+        }
+    };
 
-            order.status = OrderStatus::Cancelled;
-            order.updated_at = env.block.time.seconds();
-            ORDERS.save(deps.storage, order_id.clone(), &order)?;
+    order.updated_at = current_time;
+    orders().save(deps.storage, order_id.clone(), &order)?;
 
-            Ok(Response::new()
-                .add_message(CosmosMsg::Wasm(cancel_msg))
-                .add_attribute("method", "process_order")
-                .add_attribute("action", "cancel_order")
-                .add_attribute("order_id", order_id))
+    let mut response = Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "expire_order")
+        .add_attribute("order_id", order_id)
+        .add_attribute("disposition", disposition);
+    if order.status == OrderStatus::Expired {
+        if let Some(relisted) = relist_remainder(deps, env, &info, &mut order)? {
+            response = response
+                .add_submessages(relisted.messages)
+                .add_attribute("relisted_as", order.relisted_as.unwrap_or_default());
         }
     }
+    Ok(response)
 }
 
-pub fn execute_add_relayer(
+pub fn execute_migrate_order_escrow(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    relayer: String,
+    order_id: String,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
+    let config = CONFIG.load(deps.storage)?;
+
     if info.sender != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let relayer_addr = deps.api.addr_validate(&relayer)?;
-    
-    if !config.authorized_relayers.contains(&relayer_addr) {
-        config.authorized_relayers.push(relayer_addr.clone());
-        CONFIG.save(deps.storage, &config)?;
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+
+    if order.status != OrderStatus::Active {
+        return Err(ContractError::InvalidOrderParameters {});
     }
 
-    Ok(Response::new()
-        .add_attribute("method", "add_relayer")
-        .add_attribute("relayer", relayer_addr))
-}
+    let label = format!("{}_migrated_{}", order_id, env.block.height);
 
-pub fn execute_remove_relayer(
-    deps: DepsMut,
-    info: MessageInfo,
-    relayer: String,
-) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    // Rebuild the escrow from its stored parameters; only unfunded escrows can move
+    let (cancel_msg, create_msg) = match order.escrow_type {
+        EscrowType::Source => {
+            let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &source_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            if !escrow.deposited_amount.is_zero() {
+                return Err(ContractError::OrderFunded {});
+            }
 
-    let relayer_addr = deps.api.addr_validate(&relayer)?;
-    config.authorized_relayers.retain(|addr| addr != &relayer_addr);
-    CONFIG.save(deps.storage, &config)?;
+            let dutch_auction = order.dutch_auction.as_ref();
+            let create_msg = escrow_factory::msg::ExecuteMsg::CreateSourceEscrow {
+                maker: escrow.maker.to_string(),
+                taker: escrow.taker.map(|t| t.to_string()),
+                secret_hash: escrow.secret_hash,
+                timelock: escrow.timelock,
+                dst_chain_id: escrow.dst_chain_id,
+                dst_asset: escrow.dst_asset,
+                dst_amount: escrow.dst_amount,
+                src_amount: escrow.src_amount,
+                initial_price: dutch_auction.map(|d| d.initial_price),
+                price_decay_rate: dutch_auction.map(|d| d.price_decay_rate),
+                minimum_price: dutch_auction.map(|d| d.minimum_price),
+                allow_partial_fill: escrow.allow_partial_fill,
+                minimum_fill_amount: order.partial_fill.as_ref().and_then(|p| p.minimum_fill_amount),
+                dust_threshold: escrow.dust_threshold,
+                refund_addr: escrow.refund_addr.map(|a| a.to_string()),
+                safety_deposit: escrow.safety_deposit,
+                safety_deposit_swap: escrow.safety_deposit_swap,
+                ibc_forward: escrow.ibc_forward,
+                allowed_cw20: escrow.allowed_cw20.map(|list| list.iter().map(|a| a.to_string()).collect()),
+                basket: escrow.basket,
+                withdraw_whitelist: escrow
+                    .withdraw_whitelist
+                    .map(|list| list.iter().map(|a| a.to_string()).collect()),
+                on_complete_msg: escrow.on_complete_msg.map(|(contract, msg)| (contract.to_string(), msg)),
+                max_total_extension: Some(escrow.max_total_extension.saturating_sub(escrow.total_extension)),
+                hash_algorithm: Some(escrow.hash_algorithm),
+                // Keep the stages at the same absolute times on the replacement escrow
+                timelocks: Some(escrow.timelocks.rebased(env.block.time.seconds().saturating_sub(escrow.created_at))),
+                case_insensitive_denoms: escrow.case_insensitive_denoms,
+                merkle_root: escrow.merkle_root,
+                tranche_count: escrow.tranche_count,
+                price_decimals: dutch_auction.map(|d| d.price_decimals),
+                label,
+            };
+            (to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?, create_msg)
+        }
+        EscrowType::Destination => {
+            let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &destination_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            if !escrow.deposited_amount.is_zero() {
+                return Err(ContractError::OrderFunded {});
+            }
 
+            let create_msg = escrow_factory::msg::ExecuteMsg::CreateDestinationEscrow {
+                taker: escrow.taker.to_string(),
+                maker: escrow.maker.to_string(),
+                secret_hash: escrow.secret_hash,
+                timelock: escrow.timelock,
+                src_chain_id: escrow.src_chain_id,
+                src_escrow_address: escrow.src_escrow_address,
+                expected_amount: escrow.expected_amount,
+                allow_partial_fill: escrow.allow_partial_fill,
+                minimum_fill_amount: escrow.minimum_fill_amount,
+                claim_addr: escrow.claim_addr.map(|a| a.to_string()),
+                allowed_cw20: escrow.allowed_cw20.map(|list| list.iter().map(|a| a.to_string()).collect()),
+                hash_algorithm: Some(escrow.hash_algorithm),
+                label,
+            };
+            (to_binary(&destination_escrow::msg::ExecuteMsg::Cancel {})?, create_msg)
+        }
+    };
+
+    // The order keeps the old address until the factory reply points it at the replacement
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+    let create_msg = create_escrow_sub_msg(
+        deps.storage,
+        &order_id,
+        WasmMsg::Execute {
+            contract_addr: config.escrow_factory.to_string(),
+            msg: to_binary(&create_msg)?,
+            funds: vec![],
+        },
+    )?;
+
+    // The resolver ordered the old escrow, so it may retire it while unfunded
     Ok(Response::new()
-        .add_attribute("method", "remove_relayer")
-        .add_attribute("relayer", relayer_addr))
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: order.escrow_address.to_string(),
+            msg: cancel_msg,
+            funds: vec![],
+        }))
+        .add_submessage(create_msg)
+        .add_attribute("method", "migrate_order_escrow")
+        .add_attribute("order_id", order_id)
+        .add_attribute("old_escrow", order.escrow_address))
 }
 
-pub fn execute_update_owner(
+pub fn execute_update_secret_hash(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_owner: String,
+    order_id: String,
+    new_secret_hash: String,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != config.owner {
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+
+    if info.sender != order.maker {
         return Err(ContractError::Unauthorized {});
     }
 
-    let new_owner_addr = deps.api.addr_validate(&new_owner)?;
-    config.owner = new_owner_addr.clone();
-    CONFIG.save(deps.storage, &config)?;
+    if order.status != OrderStatus::Active {
+        return Err(ContractError::InvalidOrderParameters {});
+    }
+
+    // Reject once the escrow holds funds locked against the old hash
+    let (deposited_amount, update_msg) = match order.escrow_type {
+        EscrowType::Source => {
+            let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &source_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            let msg = source_escrow::msg::ExecuteMsg::UpdateSecretHash {
+                new_secret_hash: new_secret_hash.clone(),
+            };
+            (escrow.deposited_amount, to_binary(&msg)?)
+        }
+        EscrowType::Destination => {
+            let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &destination_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            let msg = destination_escrow::msg::ExecuteMsg::UpdateSecretHash {
+                new_secret_hash: new_secret_hash.clone(),
+            };
+            (escrow.deposited_amount, to_binary(&msg)?)
+        }
+    };
+    if !deposited_amount.is_zero() {
+        return Err(ContractError::OrderFunded {});
+    }
+
+    let update_msg = WasmMsg::Execute {
+        contract_addr: order.escrow_address.to_string(),
+        msg: update_msg,
+        funds: vec![],
+    };
+
+    order.secret_hash = new_secret_hash;
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
 
     Ok(Response::new()
-        .add_attribute("method", "update_owner")
-        .add_attribute("new_owner", new_owner_addr))
+        .add_message(CosmosMsg::Wasm(update_msg))
+        .add_attribute("method", "update_secret_hash")
+        .add_attribute("order_id", order_id))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Order { order_id } => to_binary(&query_order(deps, order_id)?),
-        QueryMsg::ActiveOrders { start_after, limit } => {
-            to_binary(&query_active_orders(deps, start_after, limit)?)
+pub fn execute_cancel_both(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only owner or authorized relayers can unwind orders
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let order = orders().load(deps.storage, order_id.clone())?;
+    let mut leg_ids = vec![order_id.clone()];
+    if let Some(counterparty_order_id) = order.counterparty_order_id {
+        leg_ids.push(counterparty_order_id);
+    }
+
+    let current_time = env.block.time.seconds();
+    let mut dst_cancel_msgs = vec![];
+    let mut src_cancel_msgs = vec![];
+    let mut cancelled = vec![];
+    let mut pending = vec![];
+    let mut refunds = vec![];
+
+    for leg_id in leg_ids {
+        let mut leg = orders().load(deps.storage, leg_id.clone())?;
+        if leg.status == OrderStatus::Completed || leg.status == OrderStatus::Cancelled {
+            continue;
         }
-        QueryMsg::CurrentPrice { escrow_address } => {
-            to_binary(&query_current_price(deps, env, escrow_address)?)
+
+        let (timelock, cancel_msg) = match leg.escrow_type {
+            EscrowType::Source => {
+                let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                    leg.escrow_address.to_string(),
+                    &source_escrow::msg::QueryMsg::Escrow {},
+                )?;
+                (escrow.timelock, to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?)
+            }
+            EscrowType::Destination => {
+                let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                    leg.escrow_address.to_string(),
+                    &destination_escrow::msg::QueryMsg::Escrow {},
+                )?;
+                (escrow.timelock, to_binary(&destination_escrow::msg::ExecuteMsg::Cancel {})?)
+            }
+        };
+
+        if current_time < timelock {
+            pending.push(leg_id);
+            continue;
         }
-        QueryMsg::IsAuthorizedRelayer { relayer } => {
-            to_binary(&query_is_authorized_relayer(deps, relayer)?)
+
+        // Each escrow accepts the cancel from the resolver that ordered it once its timelock has passed
+        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: leg.escrow_address.to_string(),
+            msg: cancel_msg,
+            funds: vec![],
+        });
+        // Destination refunds the taker before the source refunds the maker
+        match leg.escrow_type {
+            EscrowType::Destination => dst_cancel_msgs.push(msg),
+            EscrowType::Source => src_cancel_msgs.push(msg),
+        }
+
+        // A partially filled leg only refunds its unfilled remainder; filled portions were already released
+        if let Some(partial_fill) = &leg.partial_fill {
+            refunds.push(format!("{}:{}", leg_id, partial_fill.remaining_amount));
         }
+
+        leg.status = OrderStatus::Cancelled;
+        record_event(deps.storage, &env.block, &leg)?;
+        src_cancel_msgs.extend(refund_relayer_reward(&mut leg));
+        leg.updated_at = current_time;
+        orders().save(deps.storage, leg_id.clone(), &leg)?;
+        cancelled.push(leg_id);
     }
-}
 
-fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(ConfigResponse {
-        owner: config.owner,
-        escrow_factory: config.escrow_factory,
-        authorized_relayers: config.authorized_relayers,
-    })
-}
+    if cancelled.is_empty() {
+        return Err(ContractError::OrderNotExpired {});
+    }
 
-fn query_order(deps: Deps, order_id: String) -> StdResult<OrderResponse> {
-    let order = ORDERS.load(deps.storage, order_id)?;
-    Ok(OrderResponse {
-        order_id: order.order_id,
-        escrow_address: order.escrow_address,
-        maker: order.maker,
-        taker: order.taker,
-        status: order.status,
-        created_at: order.created_at,
-        updated_at: order.updated_at,
-        dutch_auction: order.dutch_auction,
-        partial_fill: order.partial_fill,
-    })
+    Ok(Response::new()
+        .add_messages(dst_cancel_msgs)
+        .add_messages(src_cancel_msgs)
+        .add_attribute("method", "cancel_both")
+        .add_attribute("order_id", order_id)
+        .add_attribute("cancelled", cancelled.join(","))
+        .add_attribute("pending", pending.join(","))
+        .add_attribute("refunds", refunds.join(",")))
 }
 
-fn query_active_orders(
-    deps: Deps,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<OrderListResponse> {
-    let limit = limit.unwrap_or(30).min(100) as usize;
-    let start = start_after.as_ref().map(|s| cosmwasm_std::Bound::exclusive(s.as_str()));
+pub fn execute_sync_both_legs(deps: DepsMut, env: Env, order_id: String) -> Result<Response, ContractError> {
+    let order = orders().load(deps.storage, order_id.clone())?;
+    let counterparty_order_id = order
+        .counterparty_order_id
+        .clone()
+        .ok_or(ContractError::CounterpartyNotLinked {})?;
+    let counterparty = orders().load(deps.storage, counterparty_order_id)?;
+    let (src_leg, dst_leg) = match (&order.escrow_type, &counterparty.escrow_type) {
+        (EscrowType::Source, EscrowType::Destination) => (order, counterparty),
+        (EscrowType::Destination, EscrowType::Source) => (counterparty, order),
+        _ => return Err(ContractError::InvalidOrderParameters {}),
+    };
 
-    let orders: StdResult<Vec<_>> = ORDERS
-        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
-        .take(limit)
-        .map(|item| {
-            item.map(|(_, order)| OrderResponse {
-                order_id: order.order_id,
-                escrow_address: order.escrow_address,
-                maker: order.maker,
-                taker: order.taker,
-                status: order.status,
-                created_at: order.created_at,
-                updated_at: order.updated_at,
-                dutch_auction: order.dutch_auction,
-                partial_fill: order.partial_fill,
-            })
-        })
-        .collect();
+    let src_escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        src_leg.escrow_address.to_string(),
+        &source_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    let dst_escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        dst_leg.escrow_address.to_string(),
+        &destination_escrow::msg::QueryMsg::Escrow {},
+    )?;
 
-    Ok(OrderListResponse {
-        orders: orders?,
-    })
+    // Either leg cancelled unwinds the swap; it only completes once both legs have paid out
+    let status = if src_escrow.status == source_escrow::msg::EscrowStatus::Cancelled
+        || dst_escrow.status == destination_escrow::msg::EscrowStatus::Cancelled
+    {
+        Some(OrderStatus::Cancelled)
+    } else if src_escrow.status == source_escrow::msg::EscrowStatus::Withdrawn
+        && dst_escrow.status == destination_escrow::msg::EscrowStatus::Withdrawn
+    {
+        Some(OrderStatus::Completed)
+    } else {
+        None
+    };
+
+    let mut synced = vec![];
+    let mut refund_msgs = vec![];
+    if let Some(status) = &status {
+        for mut leg in [src_leg, dst_leg] {
+            if matches!(leg.status, OrderStatus::Completed | OrderStatus::Cancelled | OrderStatus::Expired) {
+                continue;
+            }
+            match status {
+                OrderStatus::Completed => complete_order(deps.storage, &mut leg, &env.block)?,
+                _ => {
+                    leg.status = status.clone();
+                    record_event(deps.storage, &env.block, &leg)?;
+                }
+            }
+            // Settled out-of-band, so no relayer earned the reward; it goes back to its payer
+            refund_msgs.extend(refund_relayer_reward(&mut leg));
+            leg.updated_at = env.block.time.seconds();
+            orders().save(deps.storage, leg.order_id.clone(), &leg)?;
+            synced.push(leg.order_id);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(refund_msgs)
+        .add_attribute("method", "sync_both_legs")
+        .add_attribute("order_id", order_id)
+        .add_attribute("status", status.as_ref().map_or("unchanged", |status| status.as_str()))
+        .add_attribute("synced", synced.join(",")))
 }
 
-fn query_current_price(deps: Deps, env: Env, escrow_address: String) -> StdResult<PriceResponse> {
-    let escrow_addr = deps.api.addr_validate(&escrow_address)?;
-    
-    // Find order with matching escrow address
-    let orders: Vec<_> = ORDERS
-        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .collect::<StdResult<Vec<_>>>()?;
+pub fn execute_process_order(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    action: OrderAction,
+    proof: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // Cancellation stays open while paused so makers can recover funds
+    if !matches!(action, OrderAction::CancelOrder) {
+        ensure_not_paused(&config)?;
+    }
+    let mut order = orders().load(deps.storage, order_id.clone())?;
 
-    for (_, order) in orders {
-        if order.escrow_address == escrow_addr {
-            if let Some(dutch_auction) = order.dutch_auction {
-                let current_time = env.block.time.seconds();
-                let time_elapsed = current_time - dutch_auction.start_time;
-                
-                return Ok(PriceResponse {
-                    current_price: dutch_auction.current_price,
-                    initial_price: Some(dutch_auction.initial_price),
-                    minimum_price: Some(dutch_auction.minimum_price),
-                    time_elapsed,
-                });
+    // Only relayers serving the order's counterparty chain can process it
+    if !is_chain_relayer(deps.storage, &config, order_chain(&order), &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::InvalidRelayer {});
+    }
+
+    if !has_relayer_priority(&order, &info.sender, env.block.time.seconds()) {
+        return Err(ContractError::NotAssignedRelayer {});
+    }
+
+    match action {
+        OrderAction::ConfirmSource { src_tx_hash, block_height } => {
+            ensure_source_confirmable(deps.as_ref(), &config, &order, proof.as_deref(), block_height)?;
+
+            // Confirm source escrow on destination chain
+            let confirm_msg = WasmMsg::Execute {
+                contract_addr: order.escrow_address.to_string(),
+                msg: to_binary(&destination_escrow::msg::ExecuteMsg::ConfirmSourceEscrow {
+                    src_tx_hash,
+                    block_height,
+                })?,
+                funds: vec![],
+            };
+
+            order.status = OrderStatus::Matched;
+            record_event(deps.storage, &env.block, &order)?;
+            order.updated_at = env.block.time.seconds();
+            orders().save(deps.storage, order_id.clone(), &order)?;
+
+            Ok(Response::new()
+                .add_message(CosmosMsg::Wasm(confirm_msg))
+                .add_attribute("method", "process_order")
+                .add_attribute("action", "confirm_source")
+                .add_attribute("order_id", order_id))
+        }
+        OrderAction::ExecuteSwap { secret } => {
+            if is_frozen(&order) {
+                return Err(ContractError::OrderFrozen {});
             }
+            ensure_accepting_fill(&order, None)?;
+            ensure_settle_price(&order, &env.block)?;
+            // Execute the swap by withdrawing from escrow
+            let withdraw_msg = WasmMsg::Execute {
+                contract_addr: order.escrow_address.to_string(),
+                msg: to_binary(&source_escrow::msg::ExecuteMsg::Withdraw { secret })?,
+                funds: vec![],
+            };
+
+            complete_order(deps.storage, &mut order, &env.block)?;
+            let fee_msgs = collect_swap_fee(deps.branch(), &env, &config, &mut order, &info)?;
+            let reward_msgs = release_relayer_reward(&mut order, &info.sender);
+            order.updated_at = env.block.time.seconds();
+            orders().save(deps.storage, order_id.clone(), &order)?;
+
+            Ok(Response::new()
+                .add_message(CosmosMsg::Wasm(withdraw_msg))
+                .add_messages(fee_msgs)
+                .add_messages(reward_msgs)
+                .add_attribute("method", "process_order")
+                .add_attribute("action", "execute_swap")
+                .add_attribute("order_id", order_id)
+                .add_attribute("fast_fill", is_fast_fill(&config, &order).to_string()))
         }
-    }
+        OrderAction::PartialExecuteSwap { secret, amount } => {
+            if order.escrow_type != EscrowType::Source {
+                return Err(ContractError::InvalidOrderParameters {});
+            }
+            let dst_order_id = order
+                .counterparty_order_id
+                .clone()
+                .ok_or(ContractError::CounterpartyNotLinked {})?;
+            let mut dst_order = orders().load(deps.storage, dst_order_id.clone())?;
+            if is_frozen(&order) || is_frozen(&dst_order) {
+                return Err(ContractError::OrderFrozen {});
+            }
+            ensure_accepting_fill(&order, Some(amount))?;
+            ensure_settle_price(&order, &env.block)?;
 
-    Ok(PriceResponse {
-        current_price: Uint128::zero(),
-        initial_price: None,
-        minimum_price: None,
-        time_elapsed: 0,
-    })
+            let src_fill = order.partial_fill.as_mut().ok_or(ContractError::PartialFillNotAllowed {})?;
+            let dst_fill = dst_order.partial_fill.as_mut().ok_or(ContractError::PartialFillNotAllowed {})?;
+
+            if amount.is_zero() || amount > src_fill.remaining_amount {
+                return Err(ContractError::InvalidOrderParameters {});
+            }
+            let clearing_dust = src_fill
+                .dust_threshold
+                .map_or(false, |dust| src_fill.remaining_amount <= dust);
+            if clearing_dust {
+                if amount != src_fill.remaining_amount {
+                    return Err(ContractError::InvalidOrderParameters {});
+                }
+            } else if let Some(min_fill) = src_fill.minimum_fill_amount {
+                // The final fill may be smaller than the minimum
+                if amount < min_fill && amount != src_fill.remaining_amount {
+                    return Err(ContractError::InvalidOrderParameters {});
+                }
+            }
+
+            // Release the destination share at the settlement price; the last fill takes the rest
+            let dst_amount = record_dual_fill(src_fill, amount);
+
+            src_fill.filled_amount += amount;
+            src_fill.remaining_amount -= amount;
+            dst_fill.filled_amount += dst_amount;
+            dst_fill.remaining_amount -= dst_amount;
+            dst_fill.src_filled = src_fill.src_filled;
+            dst_fill.src_remaining = src_fill.src_remaining;
+            dst_fill.dst_filled = src_fill.dst_filled;
+            dst_fill.dst_remaining = src_fill.dst_remaining;
+            let src_done = src_fill.remaining_amount.is_zero();
+            let dst_done = dst_fill.remaining_amount.is_zero();
+
+            let src_withdraw_msg = WasmMsg::Execute {
+                contract_addr: order.escrow_address.to_string(),
+                msg: to_binary(&source_escrow::msg::ExecuteMsg::PartialWithdraw {
+                    secret: secret.clone(),
+                    amount,
+                })?,
+                funds: vec![],
+            };
+            let dst_withdraw_msg = WasmMsg::Execute {
+                contract_addr: dst_order.escrow_address.to_string(),
+                msg: to_binary(&destination_escrow::msg::ExecuteMsg::PartialWithdraw {
+                    secret,
+                    amount: dst_amount,
+                })?,
+                funds: vec![],
+            };
+
+            let mut reward_msgs = vec![];
+            if src_done {
+                complete_order(deps.storage, &mut order, &env.block)?;
+                reward_msgs.extend(release_relayer_reward(&mut order, &info.sender));
+            }
+            if dst_done {
+                complete_order(deps.storage, &mut dst_order, &env.block)?;
+                reward_msgs.extend(release_relayer_reward(&mut dst_order, &info.sender));
+            }
+            order.updated_at = env.block.time.seconds();
+            dst_order.updated_at = env.block.time.seconds();
+            orders().save(deps.storage, order_id.clone(), &order)?;
+            orders().save(deps.storage, dst_order_id.clone(), &dst_order)?;
+
+            Ok(Response::new()
+                .add_message(CosmosMsg::Wasm(src_withdraw_msg))
+                .add_message(CosmosMsg::Wasm(dst_withdraw_msg))
+                .add_messages(reward_msgs)
+                .add_attribute("method", "process_order")
+                .add_attribute("action", "partial_execute_swap")
+                .add_attribute("order_id", order_id)
+                .add_attribute("dst_order_id", dst_order_id)
+                .add_attribute("src_amount", amount)
+                .add_attribute("dst_amount", dst_amount))
+        }
+        OrderAction::CancelOrder => {
+            if let Some(effective_at) = open_cancel_grace(deps.storage, &config, &mut order, &env.block)? {
+                return Ok(Response::new()
+                    .add_attribute("method", "process_order")
+                    .add_attribute("action", "cancel_pending")
+                    .add_attribute("order_id", order_id)
+                    .add_attribute("cancel_effective_at", effective_at.to_string()));
+            }
+
+            // Cancel the order
+            let cancel_msg = WasmMsg::Execute {
+                contract_addr: order.escrow_address.to_string(),
+                msg: to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?,
+                funds: vec![],
+            };
+
+            order.status = OrderStatus::Cancelled;
+            record_event(deps.storage, &env.block, &order)?;
+            let refund_msgs = refund_relayer_reward(&mut order);
+            order.updated_at = env.block.time.seconds();
+            orders().save(deps.storage, order_id.clone(), &order)?;
+
+            let mut response = Response::new()
+                .add_message(CosmosMsg::Wasm(cancel_msg))
+                .add_messages(refund_msgs)
+                .add_attribute("method", "process_order")
+                .add_attribute("action", "cancel_order")
+                .add_attribute("order_id", order_id);
+            if let Some(relisted) = relist_remainder(deps, env, &info, &mut order)? {
+                response = response
+                    .add_submessages(relisted.messages)
+                    .add_attribute("relisted_as", order.relisted_as.unwrap_or_default());
+            }
+            Ok(response)
+        }
+    }
 }
 
-fn query_is_authorized_relayer(deps: Deps, relayer: String) -> StdResult<RelayerResponse> {
+pub fn execute_batch_confirm_source(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    confirmations: Vec<(String, String)>,
+    block_height: u64,
+    proof: Option<String>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let relayer_addr = deps.api.addr_validate(&relayer)?;
-    
-    Ok(RelayerResponse {
-        is_authorized: config.authorized_relayers.contains(&relayer_addr),
-    })
+    ensure_not_paused(&config)?;
+
+    if confirmations.is_empty() || confirmations.len() > MAX_BATCH_CONFIRMATIONS {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    // Every entry passes the single confirmation's checks before any order is matched
+    let mut pending: Vec<(Order, String)> = vec![];
+    for (order_id, src_tx_hash) in confirmations {
+        if pending.iter().any(|(order, _)| order.order_id == order_id) {
+            return Err(ContractError::InvalidOrderParameters {});
+        }
+        let order = orders().load(deps.storage, order_id)?;
+        if !is_chain_relayer(deps.storage, &config, order_chain(&order), &info.sender, env.block.time.seconds())? {
+            return Err(ContractError::InvalidRelayer {});
+        }
+        if !has_relayer_priority(&order, &info.sender, env.block.time.seconds()) {
+            return Err(ContractError::NotAssignedRelayer {});
+        }
+        ensure_source_confirmable(deps.as_ref(), &config, &order, proof.as_deref(), block_height)?;
+        pending.push((order, src_tx_hash));
+    }
+
+    let mut messages = vec![];
+    let mut confirmed = vec![];
+    for (mut order, src_tx_hash) in pending {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: order.escrow_address.to_string(),
+            msg: to_binary(&destination_escrow::msg::ExecuteMsg::ConfirmSourceEscrow {
+                src_tx_hash,
+                block_height,
+            })?,
+            funds: vec![],
+        }));
+
+        order.status = OrderStatus::Matched;
+        record_event(deps.storage, &env.block, &order)?;
+        order.updated_at = env.block.time.seconds();
+        orders().save(deps.storage, order.order_id.clone(), &order)?;
+        confirmed.push(order.order_id);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "batch_confirm_source")
+        .add_attribute("block_height", block_height.to_string())
+        .add_attribute("order_ids", confirmed.join(",")))
 }
 
+pub fn execute_add_relayer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    relayer: String,
+    ttl: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let relayer_addr = deps.api.addr_validate(&relayer)?;
+    
+    if !config.authorized_relayers.contains(&relayer_addr) {
+        config.authorized_relayers.push(relayer_addr.clone());
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    // Re-adding a relayer renews it; without a TTL the entry never expires
+    let expires_at = ttl.map(|ttl| env.block.time.seconds() + ttl);
+    match expires_at {
+        Some(expires_at) => RELAYER_EXPIRIES.save(deps.storage, &relayer_addr, &expires_at)?,
+        None => RELAYER_EXPIRIES.remove(deps.storage, &relayer_addr),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "add_relayer")
+        .add_attribute("relayer", relayer_addr)
+        .add_attribute("expires_at", expires_at.map_or("never".to_string(), |t| t.to_string())))
+}
+
+pub fn execute_remove_relayer(
+    deps: DepsMut,
+    info: MessageInfo,
+    relayer: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let relayer_addr = deps.api.addr_validate(&relayer)?;
+    config.authorized_relayers.retain(|addr| addr != &relayer_addr);
+    CONFIG.save(deps.storage, &config)?;
+    RELAYER_EXPIRIES.remove(deps.storage, &relayer_addr);
+
+    Ok(Response::new()
+        .add_attribute("method", "remove_relayer")
+        .add_attribute("relayer", relayer_addr))
+}
+
+pub fn execute_set_chain_relayers(
+    deps: DepsMut,
+    info: MessageInfo,
+    chain_id: String,
+    relayers: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_identifier(&chain_id)?;
+    let relayers = relayers
+        .iter()
+        .map(|relayer| deps.api.addr_validate(relayer))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if relayers.is_empty() {
+        CHAIN_RELAYERS.remove(deps.storage, &chain_id);
+    } else {
+        CHAIN_RELAYERS.save(deps.storage, &chain_id, &relayers)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "set_chain_relayers")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("relayer_count", relayers.len().to_string()))
+}
+
+pub fn execute_update_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner_addr = deps.api.addr_validate(&new_owner)?;
+    config.owner = new_owner_addr.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_owner")
+        .add_attribute("new_owner", new_owner_addr))
+}
+
+pub fn execute_register_taker(
+    deps: DepsMut,
+    info: MessageInfo,
+    taker: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let taker_addr = deps.api.addr_validate(&taker)?;
+    REGISTERED_TAKERS.save(deps.storage, &taker_addr, &true)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "register_taker")
+        .add_attribute("taker", taker_addr))
+}
+
+pub fn execute_deregister_taker(
+    deps: DepsMut,
+    info: MessageInfo,
+    taker: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let taker_addr = deps.api.addr_validate(&taker)?;
+    REGISTERED_TAKERS.remove(deps.storage, &taker_addr);
+
+    Ok(Response::new()
+        .add_attribute("method", "deregister_taker")
+        .add_attribute("taker", taker_addr))
+}
+
+pub fn execute_set_fee_exempt(
+    deps: DepsMut,
+    info: MessageInfo,
+    maker: String,
+    exempt: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let maker_addr = deps.api.addr_validate(&maker)?;
+    if exempt {
+        FEE_EXEMPT.save(deps.storage, &maker_addr, &true)?;
+    } else {
+        FEE_EXEMPT.remove(deps.storage, &maker_addr);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "set_fee_exempt")
+        .add_attribute("maker", maker_addr)
+        .add_attribute("exempt", exempt.to_string()))
+}
+
+pub fn execute_set_require_registered_taker(
+    deps: DepsMut,
+    info: MessageInfo,
+    required: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.require_registered_taker = required;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_require_registered_taker")
+        .add_attribute("required", required.to_string()))
+}
+
+pub fn execute_update_source_head(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    chain_id: String,
+    height: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::InvalidRelayer {});
+    }
+
+    // Relayers may lag each other; the head only moves forward
+    let head = SOURCE_HEADS.may_load(deps.storage, &chain_id)?.unwrap_or(0).max(height);
+    SOURCE_HEADS.save(deps.storage, &chain_id, &head)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_source_head")
+        .add_attribute("chain_id", chain_id)
+        .add_attribute("height", head.to_string()))
+}
+
+pub fn execute_set_min_confirmations(
+    deps: DepsMut,
+    info: MessageInfo,
+    min_confirmations: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.min_confirmations = min_confirmations;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_min_confirmations")
+        .add_attribute("min_confirmations", min_confirmations.to_string()))
+}
+
+pub fn execute_set_fast_fill_rebate(
+    deps: DepsMut,
+    info: MessageInfo,
+    window: u64,
+    rebate_bps: u16,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if u128::from(rebate_bps) > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidFeeConfig {});
+    }
+
+    config.fast_fill_window = window;
+    config.fast_fill_rebate_bps = rebate_bps;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_fast_fill_rebate")
+        .add_attribute("window", window.to_string())
+        .add_attribute("rebate_bps", rebate_bps.to_string()))
+}
+
+pub fn execute_set_order_frozen(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only owner or authorized relayers can freeze orders
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    order.frozen = frozen;
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_order_frozen")
+        .add_attribute("order_id", order_id)
+        .add_attribute("frozen", frozen.to_string()))
+}
+
+pub fn execute_set_order_accepting_fills(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    accepting: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Same authority as freezing
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    order.accepting_fills = accepting;
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_order_accepting_fills")
+        .add_attribute("order_id", order_id)
+        .add_attribute("accepting", accepting.to_string()))
+}
+
+pub fn execute_set_cancel_grace_period(
+    deps: DepsMut,
+    info: MessageInfo,
+    seconds: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.cancel_grace_period = seconds;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_cancel_grace_period")
+        .add_attribute("seconds", seconds.to_string()))
+}
+
+pub fn execute_assign_relayer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    relayer: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+
+    if info.sender != config.owner && info.sender != order.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let now = env.block.time.seconds();
+    let relayer = relayer.map(|relayer| deps.api.addr_validate(&relayer)).transpose()?;
+    if let Some(relayer) = &relayer {
+        if !is_active_relayer(deps.storage, &config, relayer, now)? {
+            return Err(ContractError::InvalidRelayer {});
+        }
+    }
+    order.exclusive_until = relayer.as_ref().map(|_| now + config.relayer_exclusive_window);
+    order.assigned_relayer = relayer;
+    order.updated_at = now;
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "assign_relayer")
+        .add_attribute("order_id", order_id)
+        .add_attribute(
+            "relayer",
+            order.assigned_relayer.map_or("none".to_string(), |relayer| relayer.to_string()),
+        ))
+}
+
+pub fn execute_set_relayer_exclusive_window(
+    deps: DepsMut,
+    info: MessageInfo,
+    seconds: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.relayer_exclusive_window = seconds;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_relayer_exclusive_window")
+        .add_attribute("seconds", seconds.to_string()))
+}
+
+pub fn execute_set_proof_required(
+    deps: DepsMut,
+    info: MessageInfo,
+    required: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.proof_required = required;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_proof_required")
+        .add_attribute("required", required.to_string()))
+}
+
+pub fn execute_set_refund_overpayment(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.refund_overpayment = enabled;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_refund_overpayment")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+pub fn execute_set_default_hash_algo(
+    deps: DepsMut,
+    info: MessageInfo,
+    hash_algo: HashAlgo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.default_hash_algo = hash_algo.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_default_hash_algo")
+        .add_attribute("hash_algo", hash_algo.as_str()))
+}
+
+pub fn execute_set_require_funded_destination(
+    deps: DepsMut,
+    info: MessageInfo,
+    required: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.require_funded_destination = required;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_require_funded_destination")
+        .add_attribute("required", required.to_string()))
+}
+
+pub fn execute_raise_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    evidence_hash: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    let now = env.block.time.seconds();
+
+    let is_party = info.sender == order.maker || order.taker.as_ref() == Some(&info.sender);
+    if !is_party && !is_active_relayer(deps.storage, &config, &info.sender, now)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if order.dispute.as_ref().map_or(false, |dispute| dispute.outcome.is_none()) {
+        return Err(ContractError::DisputeOpen {});
+    }
+
+    order.dispute = Some(DisputeInfo {
+        raised_by: info.sender.clone(),
+        evidence_hash: evidence_hash.clone(),
+        raised_at: now,
+        outcome: None,
+        resolved_at: None,
+    });
+    order.updated_at = now;
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "raise_dispute")
+        .add_attribute("order_id", order_id)
+        .add_attribute("raised_by", info.sender)
+        .add_attribute("evidence_hash", evidence_hash))
+}
+
+pub fn execute_resolve_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    outcome: DisputeOutcome,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    let now = env.block.time.seconds();
+    let dispute = order
+        .dispute
+        .as_mut()
+        .filter(|dispute| dispute.outcome.is_none())
+        .ok_or(ContractError::NoOpenDispute {})?;
+    dispute.outcome = Some(outcome.clone());
+    dispute.resolved_at = Some(now);
+    order.updated_at = now;
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "resolve_dispute")
+        .add_attribute("order_id", order_id)
+        .add_attribute("outcome", format!("{:?}", outcome)))
+}
+
+pub fn execute_cancel_with_secret(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+
+    if info.sender != order.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !matches!(order.status, OrderStatus::Active | OrderStatus::Matched) {
+        return Err(ContractError::InvalidOrderParameters {});
+    }
+
+    let secret_hash: String = Sha256::digest(secret.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+    if secret_hash != order.secret_hash.trim_start_matches("0x").to_lowercase() {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    // Proof of the secret skips the grace window and any relayer assignment
+    let cancel_msg = match order.escrow_type {
+        EscrowType::Source => to_binary(&source_escrow::msg::ExecuteMsg::Cancel {})?,
+        EscrowType::Destination => to_binary(&destination_escrow::msg::ExecuteMsg::Cancel {})?,
+    };
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: order.escrow_address.to_string(),
+        msg: cancel_msg,
+        funds: vec![],
+    })];
+
+    messages.extend(refund_relayer_reward(&mut order));
+    order.status = OrderStatus::Cancelled;
+    record_event(deps.storage, &env.block, &order)?;
+    order.cancel_effective_at = None;
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "cancel_with_secret")
+        .add_attribute("order_id", order_id)
+        .add_attribute("maker", maker))
+}
+
+pub fn execute_veto_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    order_id: String,
+) -> Result<Response, ContractError> {
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+
+    if info.sender != order.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match order.cancel_effective_at {
+        Some(effective_at) if env.block.time.seconds() < effective_at => {}
+        _ => return Err(ContractError::NoPendingCancel {}),
+    }
+
+    order.cancel_effective_at = None;
+    order.updated_at = env.block.time.seconds();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "veto_cancel")
+        .add_attribute("order_id", order_id))
+}
+
+pub fn execute_set_price_improvement_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    maker_bps: u16,
+    collector: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if u128::from(maker_bps) > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidFeeConfig {});
+    }
+
+    config.price_improvement_maker_bps = maker_bps;
+    config.price_improvement_collector = collector.map(|c| deps.api.addr_validate(&c)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_price_improvement_split")
+        .add_attribute("maker_bps", maker_bps.to_string()))
+}
+
+pub fn execute_update_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_bps: u16,
+    fee_collector: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if u128::from(fee_bps) > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidFeeConfig {});
+    }
+
+    config.fee_bps = fee_bps;
+    config.fee_collector = deps.api.addr_validate(&fee_collector)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee")
+        .add_attribute("fee_bps", fee_bps.to_string())
+        .add_attribute("fee_collector", config.fee_collector))
+}
+
+pub fn execute_set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.paused = paused;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_paused")
+        .add_attribute("paused", paused.to_string()))
+}
+
+pub fn execute_withdraw_fees(deps: DepsMut, info: MessageInfo, to: String) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Swap fees belong to the owner and fee collector, price improvement to its own collector
+    let takes_swap_fees = info.sender == config.owner || info.sender == config.fee_collector;
+    let takes_surplus = config.price_improvement_collector.as_ref() == Some(&info.sender);
+    if !takes_swap_fees && !takes_surplus {
+        return Err(ContractError::Unauthorized {});
+    }
+    let to = deps.api.addr_validate(&to)?;
+
+    let mut ledgers = vec![];
+    if takes_swap_fees {
+        ledgers.push(ACCRUED_FEES);
+    }
+    if takes_surplus {
+        ledgers.push(ACCRUED_SURPLUS);
+    }
+    let mut native: Vec<Coin> = vec![];
+    for ledger in ledgers {
+        let accrued = ledger
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (denom, amount) in accrued {
+            ledger.remove(deps.storage, &denom);
+            match native.iter_mut().find(|coin| coin.denom == denom) {
+                Some(coin) => coin.amount += amount,
+                None => native.push(Coin { denom, amount }),
+            }
+        }
+    }
+    let cw20 = if takes_swap_fees {
+        ACCRUED_CW20_FEES
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?
+    } else {
+        vec![]
+    };
+    if native.is_empty() && cw20.is_empty() {
+        return Err(ContractError::NoFeesAccrued {});
+    }
+
+    let mut messages = vec![];
+    if !native.is_empty() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: native,
+        }));
+    }
+    for (token, amount) in cw20 {
+        ACCRUED_CW20_FEES.remove(deps.storage, &token);
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: to.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "withdraw_fees")
+        .add_attribute("to", to))
+}
+
+pub fn execute_archive_orders(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    before_ts: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only owner or authorized relayers can sweep orders
+    if info.sender != config.owner && !is_active_relayer(deps.storage, &config, &info.sender, env.block.time.seconds())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let limit = limit.unwrap_or(30).min(MAX_ARCHIVE_LIMIT) as usize;
+    let terminal: Vec<(String, Order)> = orders()
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, order)) => {
+                matches!(
+                    order.status,
+                    OrderStatus::Completed | OrderStatus::Cancelled | OrderStatus::Expired
+                ) && order.updated_at < before_ts
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut archived = vec![];
+    for (order_id, order) in terminal {
+        ARCHIVED_ORDERS.save(deps.storage, order_id.clone(), &order)?;
+        orders().remove(deps.storage, order_id.clone())?;
+        archived.push(order_id);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "archive_orders")
+        .add_attribute("count", archived.len().to_string())
+        .add_attribute("order_ids", archived.join(",")))
+}
+
+pub fn execute_import_orders(
+    deps: DepsMut,
+    info: MessageInfo,
+    imported: Vec<ImportedOrder>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if imported.is_empty() || imported.len() > MAX_IMPORT_ORDERS {
+        return Err(ContractError::BatchTooLarge {});
+    }
+
+    let mut order_ids = vec![];
+    for entry in imported {
+        if !matches!(
+            entry.status,
+            OrderStatus::Completed | OrderStatus::Cancelled | OrderStatus::Expired
+        ) {
+            return Err(ContractError::InvalidOrderParameters {});
+        }
+        // Never shadow an order this resolver already knows about
+        if orders().has(deps.storage, entry.order_id.clone())
+            || ARCHIVED_ORDERS.has(deps.storage, entry.order_id.clone())
+        {
+            return Err(ContractError::InvalidOrderParameters {});
+        }
+
+        // Only the archive is written; no escrow messages are dispatched
+        let order = Order {
+            order_id: entry.order_id.clone(),
+            escrow_address: deps.api.addr_validate(&entry.escrow_address)?,
+            escrow_type: entry.escrow_type,
+            maker: deps.api.addr_validate(&entry.maker)?,
+            taker: entry.taker.map(|t| deps.api.addr_validate(&t)).transpose()?,
+            secret_hash: entry.secret_hash,
+            src_amount: entry.src_amount,
+            dst_amount: entry.dst_amount,
+            status: entry.status,
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+            dutch_auction: None,
+            partial_fill: None,
+            lop_order_data: None,
+            on_expiry: ExpiryAction::CancelRefund,
+            src_refund_addr: None,
+            dst_claim_addr: None,
+            counterparty_order_id: None,
+            src_chain_id: entry.src_chain_id,
+            dst_chain_id: entry.dst_chain_id,
+            filled_at: entry.filled_at,
+            frozen: false,
+            accepting_fills: true,
+            cancel_effective_at: None,
+            assigned_relayer: None,
+            exclusive_until: None,
+            relayer_reward: None,
+            reward_payer: None,
+            dispute: None,
+            relist: None,
+            relisted_as: None,
+            hash_algo: HashAlgo::default(),
+            fee_paid: None,
+        };
+        ARCHIVED_ORDERS.save(deps.storage, entry.order_id.clone(), &order)?;
+        order_ids.push(entry.order_id);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "import_orders")
+        .add_attribute("count", order_ids.len().to_string())
+        .add_attribute("order_ids", order_ids.join(",")))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        FUNDING_REPLY_ID => handle_escrow_funded_reply(deps),
+        id if id >= ESCROW_REPLY_ID_BASE => handle_escrow_created_reply(deps, msg),
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
+    }
+}
+
+/// Wraps an escrow creation in a reply-on-success submessage whose id maps back to `order_id`
+fn create_escrow_sub_msg(storage: &mut dyn Storage, order_id: &str, wasm_msg: WasmMsg) -> StdResult<SubMsg> {
+    let count = ESCROW_REPLY_COUNT.may_load(storage)?.unwrap_or(0);
+    ESCROW_REPLY_COUNT.save(storage, &(count + 1))?;
+
+    let reply_id = ESCROW_REPLY_ID_BASE + count;
+    PENDING_ESCROWS.save(storage, reply_id, &order_id.to_string())?;
+    Ok(SubMsg::reply_on_success(wasm_msg, reply_id))
+}
+
+/// Points the order at the escrow the factory instantiated for it
+fn handle_escrow_created_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let reply_id = msg.id;
+    let order_id = PENDING_ESCROWS.load(deps.storage, reply_id)?;
+    PENDING_ESCROWS.remove(deps.storage, reply_id);
+
+    // The factory's instantiate submessage surfaces as an `instantiate` event in this reply
+    let result = msg.result.into_result().map_err(StdError::generic_err)?;
+    let escrow_address = result
+        .events
+        .iter()
+        .filter(|event| event.ty == "instantiate")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "_contract_address")
+        .ok_or_else(|| StdError::generic_err("Escrow address missing from factory reply"))?;
+    let escrow_address = deps.api.addr_validate(&escrow_address.value)?;
+
+    let mut order = orders().load(deps.storage, order_id.clone())?;
+    order.escrow_address = escrow_address.clone();
+    orders().save(deps.storage, order_id.clone(), &order)?;
+
+    let mut response = Response::new();
+    // Fund the escrow in the same transaction; the deposit's reply checks the funds arrived
+    if let Some(principal) = PENDING_FUNDING.may_load(deps.storage, &order_id)? {
+        PENDING_FUNDING.remove(deps.storage, &order_id);
+        FUNDING_CHECK.save(deps.storage, &(order_id.clone(), principal.clone()))?;
+        let deposit_msg = WasmMsg::Execute {
+            contract_addr: escrow_address.to_string(),
+            msg: to_binary(&source_escrow::msg::ExecuteMsg::Deposit {})?,
+            funds: vec![principal],
+        };
+        response = response.add_submessage(SubMsg::reply_on_success(deposit_msg, FUNDING_REPLY_ID));
+    }
+
+    Ok(response
+        .add_attribute("method", "handle_escrow_created_reply")
+        .add_attribute("order_id", order_id)
+        .add_attribute("escrow_address", escrow_address))
+}
+
+/// Confirms the escrow holds exactly the principal deposited at deploy. Failing here reverts the whole
+/// deploy, escrow creation included, so an order is never left with an unfunded escrow.
+fn handle_escrow_funded_reply(deps: DepsMut) -> Result<Response, ContractError> {
+    let (order_id, principal) = FUNDING_CHECK.load(deps.storage)?;
+    FUNDING_CHECK.remove(deps.storage);
+
+    let order = orders().load(deps.storage, order_id.clone())?;
+    let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        order.escrow_address.to_string(),
+        &source_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    if escrow.deposited_amount != principal.amount || escrow.deposited_denom.as_ref() != Some(&principal.denom) {
+        return Err(ContractError::EscrowFundingMismatch {});
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "handle_escrow_funded_reply")
+        .add_attribute("order_id", order_id)
+        .add_attribute("deposited", principal.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Order { order_id } => to_binary(&query_order(deps, order_id)?),
+        QueryMsg::ActiveOrders { start_after, limit } => {
+            to_binary(&query_active_orders(deps, start_after, limit)?)
+        }
+        QueryMsg::OrdersByStatus { status, start_after, limit } => {
+            to_binary(&query_orders_by_status(deps, status, start_after, limit)?)
+        }
+        QueryMsg::OrdersByMaker { maker, start_after, limit } => {
+            to_binary(&query_orders_by_maker(deps, maker, start_after, limit)?)
+        }
+        QueryMsg::ExportActiveOrders { cursor, page_size } => {
+            to_binary(&query_export_active_orders(deps, cursor, page_size)?)
+        }
+        QueryMsg::EventsSince { height, start_after_seq, limit } => {
+            to_binary(&query_events_since(deps, height, start_after_seq, limit)?)
+        }
+        QueryMsg::PreviewDeploySrc { maker, taker, src_refund_addr, label, .. } => {
+            to_binary(&query_preview_deploy_src(deps, env, maker, taker, src_refund_addr, label)?)
+        }
+        QueryMsg::CurrentPrice { escrow_address } => {
+            to_binary(&query_current_price(deps, env, escrow_address)?)
+        }
+        QueryMsg::OrdersByPrice { ascending, limit } => {
+            to_binary(&query_orders_by_price(deps, env, ascending, limit)?)
+        }
+        QueryMsg::EvaluateFill { escrow_address, offered_price } => {
+            to_binary(&query_evaluate_fill(deps, env, escrow_address, offered_price)?)
+        }
+        QueryMsg::IsAuthorizedRelayer { relayer } => {
+            to_binary(&query_is_authorized_relayer(deps, env, relayer)?)
+        }
+        QueryMsg::RelayerScope { relayer, start_after, limit } => {
+            to_binary(&query_relayer_scope(deps, env, relayer, start_after, limit)?)
+        }
+        QueryMsg::IsRegisteredTaker { taker } => {
+            to_binary(&query_is_registered_taker(deps, taker)?)
+        }
+        QueryMsg::SourceHead { chain_id } => to_binary(&query_source_head(deps, chain_id)?),
+        QueryMsg::ArchivedOrder { order_id } => to_binary(&query_archived_order(deps, order_id)?),
+        QueryMsg::PerformanceStats {} => to_binary(&query_performance_stats(deps)?),
+        QueryMsg::AccruedFees {} => to_binary(&query_accrued_fees(deps)?),
+        QueryMsg::Features {} => to_binary(&query_features(deps)?),
+        QueryMsg::Health {} => to_binary(&query_health(deps)?),
+        QueryMsg::TimelockSchedule { order_id } => to_binary(&query_timelock_schedule(deps, order_id)?),
+        QueryMsg::SearchOrders { maker, status, chain, start_after, limit } => {
+            to_binary(&query_search_orders(deps, maker, status, chain, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner,
+        escrow_factory: config.escrow_factory,
+        authorized_relayers: config.authorized_relayers,
+        require_registered_taker: config.require_registered_taker,
+        min_confirmations: config.min_confirmations,
+        fast_fill_window: config.fast_fill_window,
+        fast_fill_rebate_bps: config.fast_fill_rebate_bps,
+        cancel_grace_period: config.cancel_grace_period,
+        price_improvement_maker_bps: config.price_improvement_maker_bps,
+        price_improvement_collector: config.price_improvement_collector,
+        relayer_exclusive_window: config.relayer_exclusive_window,
+        proof_required: config.proof_required,
+        refund_overpayment: config.refund_overpayment,
+        require_funded_destination: config.require_funded_destination,
+        default_hash_algo: config.default_hash_algo,
+        fee_bps: config.fee_bps,
+        fee_collector: config.fee_collector,
+        paused: config.paused,
+    })
+}
+
+fn query_source_head(deps: Deps, chain_id: String) -> StdResult<SourceHeadResponse> {
+    let height = SOURCE_HEADS.may_load(deps.storage, &chain_id)?;
+    Ok(SourceHeadResponse { chain_id, height })
+}
+
+fn query_order(deps: Deps, order_id: String) -> StdResult<OrderResponse> {
+    let order = orders().load(deps.storage, order_id)?;
+    Ok(to_order_response(deps.storage, order))
+}
+
+fn query_archived_order(deps: Deps, order_id: String) -> StdResult<OrderResponse> {
+    let order = ARCHIVED_ORDERS.load(deps.storage, order_id)?;
+    Ok(to_order_response(deps.storage, order))
+}
+
+fn query_active_orders(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OrderListResponse> {
+    query_orders_by_status(deps, OrderStatus::Active, start_after, limit)
+}
+
+fn query_orders_by_status(
+    deps: Deps,
+    status: OrderStatus,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OrderListResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+
+    let orders: StdResult<Vec<_>> = orders()
+        .idx
+        .status
+        .prefix(status.as_str().to_string())
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, order)| to_order_response(deps.storage, order)))
+        .collect();
+
+    Ok(OrderListResponse {
+        orders: orders?,
+    })
+}
+
+fn query_orders_by_maker(
+    deps: Deps,
+    maker: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OrderListResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+    let maker = deps.api.addr_validate(&maker)?;
+
+    let orders: StdResult<Vec<_>> = orders()
+        .idx
+        .maker
+        .prefix(maker.to_string())
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, order)| to_order_response(deps.storage, order)))
+        .collect();
+
+    Ok(OrderListResponse {
+        orders: orders?,
+    })
+}
+
+/// Pages through live (active or matched) orders as summaries, in order-id order. Each live status is
+/// read through the status index, one extra entry past the page to tell whether another page follows.
+fn query_export_active_orders(
+    deps: Deps,
+    cursor: Option<String>,
+    page_size: Option<u32>,
+) -> StdResult<ExportOrdersResponse> {
+    let page_size = page_size.unwrap_or(DEFAULT_EXPORT_PAGE_SIZE).min(MAX_EXPORT_PAGE_SIZE) as usize;
+
+    let mut summaries = vec![];
+    for status in [OrderStatus::Active, OrderStatus::Matched] {
+        for item in orders().idx.status.prefix(status.as_str().to_string()).range(
+            deps.storage,
+            cursor.clone().map(cw_storage_plus::Bound::exclusive),
+            None,
+            cosmwasm_std::Order::Ascending,
+        ).take(page_size + 1) {
+            let (_, order) = item?;
+            summaries.push(OrderSummary {
+                order_id: order.order_id,
+                status: order.status,
+                maker: order.maker,
+                escrow_address: order.escrow_address,
+                updated_at: order.updated_at,
+            });
+        }
+    }
+    summaries.sort_by(|a, b| a.order_id.cmp(&b.order_id));
+
+    let next_cursor = if summaries.len() > page_size {
+        summaries.truncate(page_size);
+        summaries.last().map(|order| order.order_id.clone())
+    } else {
+        None
+    };
+
+    Ok(ExportOrdersResponse { orders: summaries, next_cursor })
+}
+
+/// Replays order events from `height` on, in the order they were recorded
+fn query_events_since(
+    deps: Deps,
+    height: u64,
+    start_after_seq: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EventsSinceResponse> {
+    let limit = limit.unwrap_or(DEFAULT_EXPORT_PAGE_SIZE).min(MAX_EXPORT_PAGE_SIZE) as usize;
+    let start = match start_after_seq {
+        Some(seq) => cw_storage_plus::Bound::exclusive((height, seq)),
+        None => cw_storage_plus::Bound::inclusive((height, 0)),
+    };
+
+    let events = ORDER_EVENTS
+        .range(deps.storage, Some(start), None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|((height, seq), event)| OrderEvent {
+                height,
+                seq,
+                order_id: event.order_id,
+                status: event.status,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EventsSinceResponse { events })
+}
+
+fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let native = ACCRUED_FEES
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    let cw20 = ACCRUED_CW20_FEES
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| {
+            item.map(|(address, amount)| cw20::Cw20Coin {
+                address: address.to_string(),
+                amount,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let price_improvement = ACCRUED_SURPLUS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AccruedFeesResponse { native, cw20, price_improvement })
+}
+
+fn query_performance_stats(deps: Deps) -> StdResult<PerformanceStatsResponse> {
+    let stats = PERFORMANCE.may_load(deps.storage)?.unwrap_or_default();
+    if stats.completed_count == 0 {
+        return Ok(PerformanceStatsResponse {
+            avg_completion_seconds: 0,
+            median_bucket: 0,
+            completed_count: 0,
+        });
+    }
+
+    // Walk the histogram until half of the completions are covered
+    let mut seen = 0;
+    let mut median_bucket = u64::MAX;
+    for (i, count) in stats.bucket_counts.iter().enumerate() {
+        seen += count;
+        if seen * 2 >= stats.completed_count {
+            median_bucket = COMPLETION_BUCKETS.get(i).copied().unwrap_or(u64::MAX);
+            break;
+        }
+    }
+
+    Ok(PerformanceStatsResponse {
+        avg_completion_seconds: stats.total_completion_seconds / stats.completed_count,
+        median_bucket,
+        completed_count: stats.completed_count,
+    })
+}
+
+fn query_features(deps: Deps) -> StdResult<FeaturesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let version = cw2::get_contract_version(deps.storage)?;
+
+    Ok(FeaturesResponse {
+        contract: version.contract,
+        schema_version: version.version,
+        supports_partial_fill: true,
+        supports_dutch_auction: true,
+        supports_merkle_secrets: false,
+        supports_signed_orders: true,
+        supports_price_improvement: config.price_improvement_collector.is_some(),
+        hash_algos: vec![HashAlgo::Sha256.as_str().to_string(), HashAlgo::Keccak256.as_str().to_string()],
+    })
+}
+
+fn query_timelock_schedule(deps: Deps, order_id: String) -> StdResult<TimelockScheduleResponse> {
+    let order = orders().load(deps.storage, order_id.clone())?;
+    let (created_at, timelocks) = match order.escrow_type {
+        EscrowType::Source => {
+            let escrow: source_escrow::msg::EscrowResponse = deps
+                .querier
+                .query_wasm_smart(order.escrow_address.to_string(), &source_escrow::msg::QueryMsg::Escrow {})?;
+            (escrow.created_at, escrow.timelocks)
+        }
+        EscrowType::Destination => {
+            let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+                order.escrow_address.to_string(),
+                &destination_escrow::msg::QueryMsg::Escrow {},
+            )?;
+            (escrow.created_at, Timelocks::from_timelock(escrow.created_at, escrow.timelock))
+        }
+    };
+
+    Ok(TimelockScheduleResponse {
+        order_id,
+        finality_at: created_at + timelocks.finality,
+        exclusive_until: created_at + timelocks.exclusive_withdrawal,
+        public_until: created_at + timelocks.public_withdrawal,
+        cancel_at: created_at + timelocks.cancellation,
+    })
+}
+
+fn query_health(deps: Deps) -> StdResult<HealthResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    // An unreachable factory is reported, not propagated
+    let factory: Option<escrow_factory::msg::ConfigResponse> = deps
+        .querier
+        .query_wasm_smart(config.escrow_factory.to_string(), &escrow_factory::msg::QueryMsg::Config {})
+        .ok();
+    let active_orders = orders()
+        .idx
+        .status
+        .prefix(OrderStatus::Active.as_str().to_string())
+        .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .count() as u64;
+
+    Ok(HealthResponse {
+        factory_reachable: factory.is_some(),
+        source_escrow_code_id: factory.as_ref().map(|f| f.source_escrow_code_id),
+        destination_escrow_code_id: factory.as_ref().map(|f| f.destination_escrow_code_id),
+        active_orders,
+    })
+}
+
+fn query_search_orders(
+    deps: Deps,
+    maker: Option<String>,
+    status: Option<OrderStatus>,
+    chain: Option<String>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OrderListResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+    let maker = maker.map(|m| deps.api.addr_validate(&m)).transpose()?;
+
+    // Drive iteration off the most selective index available and post-filter the rest
+    let store = orders();
+    let candidates: Box<dyn Iterator<Item = StdResult<(String, Order)>>> = match (&maker, &status) {
+        (Some(maker), _) => store.idx.maker.prefix(maker.to_string()).range(
+            deps.storage,
+            start,
+            None,
+            cosmwasm_std::Order::Ascending,
+        ),
+        (None, Some(status)) => store.idx.status.prefix(status.as_str().to_string()).range(
+            deps.storage,
+            start,
+            None,
+            cosmwasm_std::Order::Ascending,
+        ),
+        (None, None) => store.range(deps.storage, start, None, cosmwasm_std::Order::Ascending),
+    };
+
+    let matches = |order: &Order| {
+        maker.as_ref().map_or(true, |maker| &order.maker == maker)
+            && status.as_ref().map_or(true, |status| &order.status == status)
+            && chain.as_ref().map_or(true, |chain| {
+                order.src_chain_id.as_ref() == Some(chain) || order.dst_chain_id.as_ref() == Some(chain)
+            })
+    };
+    let found: StdResult<Vec<_>> = candidates
+        .filter(|item| item.as_ref().map_or(true, |(_, order)| matches(order)))
+        .take(limit)
+        .map(|item| item.map(|(_, order)| to_order_response(deps.storage, order)))
+        .collect();
+
+    Ok(OrderListResponse { orders: found? })
+}
+
+/// Ranks active auction orders by live price. Every active order is loaded and priced before
+/// sorting, so gas grows with the active book rather than with `limit`.
+fn query_orders_by_price(
+    deps: Deps,
+    env: Env,
+    ascending: bool,
+    limit: Option<u32>,
+) -> StdResult<OrdersByPriceResponse> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+
+    let mut priced = vec![];
+    for item in orders().idx.status.prefix(OrderStatus::Active.as_str().to_string()).range(
+        deps.storage,
+        None,
+        None,
+        cosmwasm_std::Order::Ascending,
+    ) {
+        let (_, order) = item?;
+        if let Some(dutch_auction) = &order.dutch_auction {
+            let current_price = calculate_current_price(dutch_auction, &env.block)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            priced.push((current_price, order));
+        }
+    }
+
+    // Stable sort keeps order-id order among equal prices
+    priced.sort_by(|(a, _), (b, _)| if ascending { a.cmp(b) } else { b.cmp(a) });
+    let orders = priced
+        .into_iter()
+        .take(limit)
+        .map(|(current_price, order)| PricedOrder {
+            order: to_order_response(deps.storage, order),
+            current_price,
+        })
+        .collect();
+
+    Ok(OrdersByPriceResponse { orders })
+}
+
+fn query_current_price(deps: Deps, env: Env, escrow_address: String) -> StdResult<PriceResponse> {
+    let escrow_addr = deps.api.addr_validate(&escrow_address)?;
+    
+    // Find order with matching escrow address
+    let orders: Vec<_> = orders()
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (_, order) in orders {
+        if order.escrow_address == escrow_addr {
+            if let Some(dutch_auction) = order.dutch_auction {
+                let current_time = env.block.time.seconds();
+                let time_elapsed = current_time.saturating_sub(dutch_auction.start_time);
+                
+                return Ok(PriceResponse {
+                    current_price: dutch_auction.current_price,
+                    initial_price: Some(dutch_auction.initial_price),
+                    minimum_price: Some(dutch_auction.minimum_price),
+                    time_elapsed,
+                    price_decimals: dutch_auction.price_decimals,
+                });
+            }
+        }
+    }
+
+    Ok(PriceResponse {
+        current_price: Uint128::zero(),
+        initial_price: None,
+        minimum_price: None,
+        time_elapsed: 0,
+        price_decimals: DEFAULT_PRICE_DECIMALS,
+    })
+}
+
+fn query_preview_deploy_src(
+    deps: Deps,
+    env: Env,
+    maker: String,
+    taker: Option<String>,
+    src_refund_addr: Option<String>,
+    label: String,
+) -> StdResult<PreviewDeployResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Run the same address checks a deploy would
+    deps.api.addr_validate(&maker)?;
+    let taker_addr = taker.as_ref().map(|t| deps.api.addr_validate(t)).transpose()?;
+    ensure_taker_registered(deps, &config, taker_addr.as_ref())
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    src_refund_addr.map(|a| deps.api.addr_validate(&a)).transpose()?;
+
+    let order_count = ORDER_COUNT.load(deps.storage)?;
+
+    Ok(PreviewDeployResponse {
+        order_id: format!("order_{}", order_count + 1),
+        // The factory salts with its caller, which is this contract
+        salt: escrow_factory::contract::escrow_salt(&env.contract.address, &env, &label),
+    })
+}
+
+fn query_evaluate_fill(
+    deps: Deps,
+    env: Env,
+    escrow_address: String,
+    offered_price: Uint128,
+) -> StdResult<EvaluateFillResponse> {
+    let escrow_addr = deps.api.addr_validate(&escrow_address)?;
+
+    let orders: Vec<_> = orders()
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let dutch_auction = orders
+        .into_iter()
+        .find(|(_, order)| order.escrow_address == escrow_addr)
+        .and_then(|(_, order)| order.dutch_auction)
+        .ok_or_else(|| StdError::not_found("dutch auction"))?;
+
+    // Priced live rather than from the cached current_price
+    let current_price = calculate_current_price(&dutch_auction, &env.block)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(EvaluateFillResponse {
+        current_price,
+        accepted: offered_price >= current_price,
+        shortfall: current_price.saturating_sub(offered_price),
+    })
+}
+
+fn query_is_authorized_relayer(deps: Deps, env: Env, relayer: String) -> StdResult<RelayerResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let relayer_addr = deps.api.addr_validate(&relayer)?;
+    
+    Ok(RelayerResponse {
+        is_authorized: is_active_relayer(deps.storage, &config, &relayer_addr, env.block.time.seconds())?,
+        expires_at: RELAYER_EXPIRIES.may_load(deps.storage, &relayer_addr)?,
+    })
+}
+
+fn query_relayer_scope(
+    deps: Deps,
+    env: Env,
+    relayer: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RelayerScopeResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let relayer_addr = deps.api.addr_validate(&relayer)?;
+    if !is_active_relayer(deps.storage, &config, &relayer_addr, env.block.time.seconds())? {
+        return Ok(RelayerScopeResponse {
+            is_authorized: false,
+            actionable_count: 0,
+            order_ids: vec![],
+        });
+    }
+
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+    let now = env.block.time.seconds();
+    let actionable = |order: &Order| {
+        !is_frozen(order)
+            && matches!(order.status, OrderStatus::Active | OrderStatus::Matched)
+            && has_relayer_priority(order, &relayer_addr, now)
+    };
+
+    let mut actionable_count = 0u64;
+    for status in [OrderStatus::Active, OrderStatus::Matched] {
+        for item in orders().idx.status.prefix(status.as_str().to_string()).range(
+            deps.storage,
+            None,
+            None,
+            cosmwasm_std::Order::Ascending,
+        ) {
+            if actionable(&item?.1) {
+                actionable_count += 1;
+            }
+        }
+    }
+
+    let order_ids = orders()
+        .range(deps.storage, start, None, cosmwasm_std::Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(_, order)| actionable(order)))
+        .take(limit)
+        .map(|item| item.map(|(order_id, _)| order_id))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RelayerScopeResponse {
+        is_authorized: true,
+        actionable_count,
+        order_ids,
+    })
+}
+
+fn query_is_registered_taker(deps: Deps, taker: String) -> StdResult<TakerResponse> {
+    let taker_addr = deps.api.addr_validate(&taker)?;
+
+    Ok(TakerResponse {
+        is_registered: REGISTERED_TAKERS.has(deps.storage, &taker_addr),
+    })
+}
+
+/// Rejects a deploy whose taker is missing or unregistered when registration is required.
+fn ensure_taker_registered(
+    deps: Deps,
+    config: &Config,
+    taker: Option<&Addr>,
+) -> Result<(), ContractError> {
+    if !config.require_registered_taker {
+        return Ok(());
+    }
+    match taker {
+        Some(taker) if REGISTERED_TAKERS.has(deps.storage, taker) => Ok(()),
+        _ => Err(ContractError::TakerNotRegistered {}),
+    }
+}
+
+/// Deploys the unfilled remainder of a cancelled or expired order as a fresh order, when re-listing is set.
+fn relist_remainder(
+    deps: DepsMut,
+    env: Env,
+    info: &MessageInfo,
+    order: &mut Order,
+) -> Result<Option<Response>, ContractError> {
+    let (relist, partial_fill) = match (order.relist.clone(), order.partial_fill.clone()) {
+        (Some(relist), Some(partial_fill)) if !partial_fill.remaining_amount.is_zero() => (relist, partial_fill),
+        _ => return Ok(None),
+    };
+    let dst_chain_id = order.dst_chain_id.clone().ok_or(ContractError::InvalidOrderParameters {})?;
+
+    order.relisted_as = Some(format!("order_{}", ORDER_COUNT.load(deps.storage)? + 1));
+    orders().save(deps.storage, order.order_id.clone(), order)?;
+
+    let relisted = execute_deploy_src(
+        deps,
+        env,
+        MessageInfo {
+            sender: info.sender.clone(),
+            funds: vec![],
+        },
+        DeploySrcParams {
+            maker: order.maker.to_string(),
+            taker: order.taker.as_ref().map(|taker| taker.to_string()),
+            secret_hash: relist.secret_hash,
+            timelock: relist.timelock,
+            dst_chain_id,
+            dst_asset: relist.dst_asset,
+            dst_amount: partial_fill.dst_remaining,
+            src_amount: order.src_amount.map(|_| partial_fill.src_remaining),
+            initial_price: relist.initial_price,
+            price_decay_rate: relist.price_decay_rate,
+            minimum_price: relist.minimum_price,
+            decay_basis: order.dutch_auction.as_ref().map(|d| d.decay_basis.clone()),
+            price_decimals: order.dutch_auction.as_ref().map(|d| d.price_decimals),
+            allow_partial_fill: true,
+            minimum_fill_amount: partial_fill
+                .minimum_fill_amount
+                .filter(|min_fill| *min_fill <= partial_fill.remaining_amount),
+            dust_threshold: partial_fill.dust_threshold,
+            on_expiry: Some(order.on_expiry.clone()),
+            src_refund_addr: order.src_refund_addr.as_ref().map(|addr| addr.to_string()),
+            hash_algo: Some(order.hash_algo.clone()),
+            label: relist.label,
+            ..DeploySrcParams::default()
+        },
+    )?;
+    Ok(Some(relisted))
+}
+
+/// Opens a grace window the maker can veto before a relayer's cancel takes effect, returning its end
+/// when this request opened it. A request inside an open window fails; once it lapses, or with no
+/// grace period configured, the cancel may go ahead.
+fn open_cancel_grace(
+    storage: &mut dyn Storage,
+    config: &Config,
+    order: &mut Order,
+    block: &BlockInfo,
+) -> Result<Option<u64>, ContractError> {
+    let now = block.time.seconds();
+    match order.cancel_effective_at {
+        None if config.cancel_grace_period > 0 => {
+            let effective_at = now + config.cancel_grace_period;
+            order.cancel_effective_at = Some(effective_at);
+            order.updated_at = now;
+            orders().save(storage, order.order_id.clone(), order)?;
+            Ok(Some(effective_at))
+        }
+        Some(effective_at) if now < effective_at => Err(ContractError::CancelPending {}),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the relayer reward of an order no relayer completed to whoever attached it
+fn refund_relayer_reward(order: &mut Order) -> Vec<CosmosMsg> {
+    // Orders stored before the payer was recorded were funded by their maker
+    let payer = order.reward_payer.clone().unwrap_or_else(|| order.maker.clone());
+    release_relayer_reward(order, &payer)
+}
+
+/// Pays out the order's escrowed relayer reward, at most once
+fn release_relayer_reward(order: &mut Order, recipient: &Addr) -> Vec<CosmosMsg> {
+    order
+        .relayer_reward
+        .take()
+        .map(|reward| {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![reward],
+            })
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Marks an order completed and folds its time-to-completion into the performance stats.
+fn complete_order(storage: &mut dyn Storage, order: &mut Order, block: &BlockInfo) -> StdResult<()> {
+    let now = block.time.seconds();
+    order.status = OrderStatus::Completed;
+    order.filled_at = Some(now);
+    record_event(storage, block, order)?;
+
+    let duration = now.saturating_sub(order.created_at);
+    let mut stats = PERFORMANCE.may_load(storage)?.unwrap_or_default();
+    stats.bucket_counts.resize(COMPLETION_BUCKETS.len() + 1, 0);
+    let bucket = COMPLETION_BUCKETS
+        .iter()
+        .position(|bound| duration <= *bound)
+        .unwrap_or(COMPLETION_BUCKETS.len());
+    stats.bucket_counts[bucket] += 1;
+    stats.completed_count += 1;
+    stats.total_completion_seconds += duration;
+    PERFORMANCE.save(storage, &stats)
+}
+
+/// Appends an order's new status to the height-keyed event index.
+fn record_event(storage: &mut dyn Storage, block: &BlockInfo, order: &Order) -> StdResult<()> {
+    let seq = EVENT_COUNT.may_load(storage)?.unwrap_or(0);
+    EVENT_COUNT.save(storage, &(seq + 1))?;
+    ORDER_EVENTS.save(
+        storage,
+        (block.height, seq),
+        &OrderEventRef {
+            order_id: order.order_id.clone(),
+            status: order.status.clone(),
+        },
+    )
+}
+
+/// Routes the price improvement of a partial fill, `(executed_price - minimum_price) * amount`,
+/// which the caller attaches in the escrowed asset, to the maker and accrues the protocol's share for
+/// the collector. With overpayment refunds on, anything attached above the live surplus goes back to the caller.
+fn price_improvement_split(
+    deps: DepsMut,
+    config: &Config,
+    order: &Order,
+    info: &MessageInfo,
+    amount: Uint128,
+    block: &BlockInfo,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let dutch_auction = match (&config.price_improvement_collector, &order.dutch_auction) {
+        (Some(_), Some(dutch_auction)) => dutch_auction,
+        _ => return Ok(vec![]),
+    };
+
+    let executed_price = calculate_current_price(dutch_auction, block)?;
+    let surplus = executed_price.saturating_sub(dutch_auction.minimum_price).checked_mul(amount)
+        .map_err(|_| ContractError::InvalidOrderParameters {})?;
+    if surplus.is_zero() && !config.refund_overpayment {
+        return Ok(vec![]);
+    }
+
+    // The price may have decayed further between the caller's quote and execution
+    let payment = match info.funds.as_slice() {
+        [] if surplus.is_zero() => return Ok(vec![]),
+        [coin] if coin.amount == surplus => coin,
+        [coin] if config.refund_overpayment && coin.amount > surplus => coin,
+        _ => return Err(ContractError::SurplusMismatch {}),
+    };
+    // The surplus is priced in the escrowed asset, so it must be paid in that asset
+    let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        order.escrow_address.to_string(),
+        &source_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    if native_escrow_denom(escrow).as_ref() != Some(&payment.denom) {
+        return Err(ContractError::SurplusMismatch {});
+    }
+    let maker_share = if FEE_EXEMPT.has(deps.storage, &order.maker) {
+        surplus
+    } else {
+        surplus.multiply_ratio(config.price_improvement_maker_bps, BPS_DENOMINATOR)
+    };
+    let collector_share = surplus - maker_share;
+    let overpayment = payment.amount - surplus;
+
+    if !collector_share.is_zero() {
+        ACCRUED_SURPLUS.update(deps.storage, &payment.denom, |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default() + collector_share)
+        })?;
+    }
+
+    let mut messages = vec![];
+    for (recipient, share) in [(&order.maker, maker_share), (&info.sender, overpayment)] {
+        if !share.is_zero() {
+            messages.push(CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin {
+                    denom: payment.denom.clone(),
+                    amount: share,
+                }],
+            }));
+        }
+    }
+    Ok(messages)
+}
+
+/// Charges the protocol fee on a completed swap to the executing relayer, in the escrow's asset.
+/// Native fees are attached to the call; CW20 fees are pulled from the relayer's allowance. A fast
+/// fill's rebate goes to the maker, and the collector's share stays in the resolver where it
+/// accrues for `WithdrawFees`.
+fn collect_swap_fee(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    order: &mut Order,
+    info: &MessageInfo,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    // Rounds down, so orders too small to owe a whole unit settle fee-free
+    let amount = order.src_amount.unwrap_or(order.dst_amount);
+    let fee = if FEE_EXEMPT.has(deps.storage, &order.maker) {
+        Uint128::zero()
+    } else {
+        amount.multiply_ratio(config.fee_bps, BPS_DENOMINATOR)
+    };
+    if fee.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        order.escrow_address.to_string(),
+        &source_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    let rebate = fast_fill_rebate(config, order, fee);
+    let collector_share = fee - rebate;
+
+    let mut messages = vec![];
+    let denom = if let Some(token) = escrow.cw20_contract {
+        if !collector_share.is_zero() {
+            ACCRUED_CW20_FEES.update(deps.storage, &token, |accrued| -> StdResult<_> {
+                Ok(accrued.unwrap_or_default() + collector_share)
+            })?;
+        }
+        let shares = [(env.contract.address.clone(), collector_share), (order.maker.clone(), rebate)];
+        for (recipient, share) in shares {
+            if !share.is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: token.to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: recipient.to_string(),
+                        amount: share,
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        }
+        token.to_string()
+    } else {
+        let denom = native_escrow_denom(escrow).ok_or(ContractError::FeeMismatch {})?;
+        match info.funds.as_slice() {
+            [coin] if coin.denom == denom && coin.amount == fee => {}
+            _ => return Err(ContractError::FeeMismatch {}),
+        }
+        if !collector_share.is_zero() {
+            ACCRUED_FEES.update(deps.storage, &denom, |accrued| -> StdResult<_> {
+                Ok(accrued.unwrap_or_default() + collector_share)
+            })?;
+        }
+        if !rebate.is_zero() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: order.maker.to_string(),
+                amount: vec![Coin { denom: denom.clone(), amount: rebate }],
+            }));
+        }
+        denom
+    };
+
+    order.fee_paid = Some(Coin { denom, amount: fee });
+    Ok(messages)
+}
+
+/// Native denom a source escrow holds; basket escrows express fill amounts in their first asset
+fn native_escrow_denom(escrow: source_escrow::msg::EscrowResponse) -> Option<String> {
+    escrow
+        .deposited_denom
+        .or_else(|| escrow.basket.and_then(|basket| basket.first().map(|asset| asset.denom.clone())))
+}
+
+/// Records a fill of `src_amount` in both assets' units, converting at the settlement price; a fill
+/// that clears the source side takes the destination remainder. Returns the destination amount.
+fn record_dual_fill(partial_fill: &mut PartialFillInfo, src_amount: Uint128) -> Uint128 {
+    let dst_amount = if src_amount >= partial_fill.src_remaining {
+        partial_fill.dst_remaining
+    } else {
+        src_amount.multiply_ratio(
+            partial_fill.dst_filled + partial_fill.dst_remaining,
+            partial_fill.src_filled + partial_fill.src_remaining,
+        )
+    };
+    partial_fill.src_filled += src_amount;
+    partial_fill.src_remaining = partial_fill.src_remaining.saturating_sub(src_amount);
+    partial_fill.dst_filled += dst_amount;
+    partial_fill.dst_remaining -= dst_amount;
+    dst_amount
+}
+
+/// Frozen orders, or orders under an open dispute, only accept cancellation
+/// Rejects a fill on an order that stopped accepting fills, unless it clears the remainder of a started
+/// partial fill. `amount` is `None` for a swap that takes whatever remains.
+fn ensure_accepting_fill(order: &Order, amount: Option<Uint128>) -> Result<(), ContractError> {
+    let clears_remainder = order.partial_fill.as_ref().map_or(false, |partial_fill| {
+        !partial_fill.filled_amount.is_zero() && amount.map_or(true, |amount| amount == partial_fill.remaining_amount)
+    });
+    if order.accepting_fills || clears_remainder {
+        Ok(())
+    } else {
+        Err(ContractError::OrderNotAcceptingFills {})
+    }
+}
+
+fn ensure_not_paused(config: &Config) -> Result<(), ContractError> {
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+fn is_frozen(order: &Order) -> bool {
+    order.frozen || order.dispute.as_ref().map_or(false, |dispute| dispute.outcome.is_none())
+}
+
+/// Whether the address is an authorized relayer whose entry has not expired
+fn is_active_relayer(storage: &dyn Storage, config: &Config, relayer: &Addr, now: u64) -> StdResult<bool> {
+    if !config.authorized_relayers.contains(relayer) {
+        return Ok(false);
+    }
+    Ok(RELAYER_EXPIRIES
+        .may_load(storage, relayer)?
+        .map_or(true, |expires_at| now < expires_at))
+}
+
+/// Whether the relayer serves `chain_id`, either through that chain's set or the global one
+fn is_chain_relayer(
+    storage: &dyn Storage,
+    config: &Config,
+    chain_id: Option<&str>,
+    relayer: &Addr,
+    now: u64,
+) -> StdResult<bool> {
+    if is_active_relayer(storage, config, relayer, now)? {
+        return Ok(true);
+    }
+    match chain_id {
+        Some(chain_id) => Ok(CHAIN_RELAYERS
+            .may_load(storage, chain_id)?
+            .map_or(false, |relayers| relayers.contains(relayer))),
+        None => Ok(false),
+    }
+}
+
+/// The counterparty chain of an order: the source chain of a destination order and vice versa
+fn order_chain(order: &Order) -> Option<&str> {
+    order.src_chain_id.as_deref().or(order.dst_chain_id.as_deref())
+}
+
+/// Whether the relayer may act on the order, given any exclusive assignment still in force
+fn has_relayer_priority(order: &Order, relayer: &Addr, now: u64) -> bool {
+    match (&order.assigned_relayer, order.exclusive_until) {
+        (Some(assigned), Some(exclusive_until)) if now < exclusive_until => assigned == relayer,
+        _ => true,
+    }
+}
+
+/// Whether the order filled within the configured fast-fill window of its creation.
+fn is_fast_fill(config: &Config, order: &Order) -> bool {
+    match order.filled_at {
+        Some(filled_at) => {
+            config.fast_fill_window > 0
+                && filled_at.saturating_sub(order.created_at) <= config.fast_fill_window
+        }
+        None => false,
+    }
+}
+
+/// Share of a protocol fee refunded to the maker for a fast fill.
+pub fn fast_fill_rebate(config: &Config, order: &Order, fee: Uint128) -> Uint128 {
+    if !is_fast_fill(config, order) {
+        return Uint128::zero();
+    }
+    fee.multiply_ratio(config.fast_fill_rebate_bps, BPS_DENOMINATOR)
+}
+
+/// Rejects a source confirmation at a block within `min_confirmations` of the tracked head.
+fn ensure_confirmation_depth(
+    deps: Deps,
+    config: &Config,
+    order: &Order,
+    block_height: u64,
+) -> Result<(), ContractError> {
+    if config.min_confirmations == 0 {
+        return Ok(());
+    }
+    let head = match &order.src_chain_id {
+        Some(chain_id) => SOURCE_HEADS.may_load(deps.storage, chain_id)?,
+        None => None,
+    };
+    match head {
+        Some(head) if head.saturating_sub(block_height) >= config.min_confirmations => Ok(()),
+        _ => Err(ContractError::InsufficientConfirmations {}),
+    }
+}
+
+/// Checks a source confirmation must pass before matching a destination order, singly or in a batch.
+fn ensure_source_confirmable(
+    deps: Deps,
+    config: &Config,
+    order: &Order,
+    proof: Option<&str>,
+    block_height: u64,
+) -> Result<(), ContractError> {
+    if order.escrow_type != EscrowType::Destination || order.status != OrderStatus::Active {
+        return Err(ContractError::InvalidOrderParameters {});
+    }
+    if config.proof_required && proof.map_or(true, |p| p.trim().is_empty()) {
+        return Err(ContractError::ProofRequired {});
+    }
+    ensure_confirmation_depth(deps, config, order, block_height)?;
+    ensure_source_funded(deps, order)?;
+    ensure_destination_funded(deps, config, order)
+}
+
+/// A linked source escrow may be topped up over several deposits; it only counts as confirmed once it
+/// holds its full expected amount.
+fn ensure_source_funded(deps: Deps, order: &Order) -> Result<(), ContractError> {
+    let src_order = match &order.counterparty_order_id {
+        Some(src_order_id) => orders().load(deps.storage, src_order_id.clone())?,
+        None => return Ok(()),
+    };
+    if src_order.escrow_type != EscrowType::Source {
+        return Ok(());
+    }
+    let escrow: source_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        src_order.escrow_address.to_string(),
+        &source_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    let funded = escrow
+        .src_amount
+        .map_or(!escrow.deposited_amount.is_zero(), |src_amount| escrow.deposited_amount == src_amount);
+    if !funded {
+        return Err(ContractError::SourceNotFunded {});
+    }
+    Ok(())
+}
+
+/// Confirming a source against an unfunded destination would let the maker's funds be claimed
+/// before the taker's side is locked up.
+fn ensure_destination_funded(deps: Deps, config: &Config, order: &Order) -> Result<(), ContractError> {
+    if !config.require_funded_destination {
+        return Ok(());
+    }
+    let escrow: destination_escrow::msg::EscrowResponse = deps.querier.query_wasm_smart(
+        order.escrow_address.to_string(),
+        &destination_escrow::msg::QueryMsg::Escrow {},
+    )?;
+    if escrow.deposited_amount < escrow.expected_amount {
+        return Err(ContractError::DestinationNotFunded {});
+    }
+    Ok(())
+}
+
+fn to_order_response(storage: &dyn Storage, order: Order) -> OrderResponse {
+    let fee_exempt = FEE_EXEMPT.has(storage, &order.maker);
+    OrderResponse {
+        order_id: order.order_id,
+        escrow_address: order.escrow_address,
+        escrow_type: order.escrow_type,
+        maker: order.maker,
+        taker: order.taker,
+        secret_hash: order.secret_hash,
+        src_amount: order.src_amount,
+        dst_amount: order.dst_amount,
+        status: order.status,
+        created_at: order.created_at,
+        updated_at: order.updated_at,
+        dutch_auction: order.dutch_auction,
+        partial_fill: order.partial_fill,
+        on_expiry: order.on_expiry,
+        src_refund_addr: order.src_refund_addr,
+        dst_claim_addr: order.dst_claim_addr,
+        counterparty_order_id: order.counterparty_order_id,
+        src_chain_id: order.src_chain_id,
+        dst_chain_id: order.dst_chain_id,
+        filled_at: order.filled_at,
+        frozen: order.frozen,
+        accepting_fills: order.accepting_fills,
+        cancel_effective_at: order.cancel_effective_at,
+        assigned_relayer: order.assigned_relayer,
+        exclusive_until: order.exclusive_until,
+        relayer_reward: order.relayer_reward,
+        dispute: order.dispute,
+        relist: order.relist,
+        relisted_as: order.relisted_as,
+        hash_algo: order.hash_algo,
+        fee_exempt,
+        fee_paid: order.fee_paid,
+    }
+}
+
+/// Rejects a swap while the live auction price is below the maker's settle floor
+fn ensure_settle_price(order: &Order, block: &BlockInfo) -> Result<(), ContractError> {
+    if let Some(auction) = &order.dutch_auction {
+        if let Some(min_settle_price) = auction.min_settle_price {
+            if calculate_current_price(auction, block)? < min_settle_price {
+                return Err(ContractError::MinimumPriceReached {});
+            }
+        }
+    }
+    Ok(())
+}
+
+fn calculate_current_price(
+    dutch_auction: &DutchAuctionInfo,
+    block: &BlockInfo,
+) -> Result<Uint128, ContractError> {
+    let elapsed = match dutch_auction.decay_basis {
+        DecayBasis::Time => block.time.seconds().saturating_sub(dutch_auction.start_time),
+        DecayBasis::Height => block.height.saturating_sub(dutch_auction.start_height),
+    };
+
+    // Calculate new price: price = initial_price - (decay_rate * elapsed)
+    let price_decrease = dutch_auction.price_decay_rate.checked_mul(Uint128::from(elapsed))
+        .map_err(|_| ContractError::InvalidOrderParameters {})?;
+
+    let new_price = if price_decrease >= dutch_auction.initial_price {
+        dutch_auction.minimum_price
+    } else {
+        dutch_auction.initial_price.checked_sub(price_decrease)
+            .map_err(|_| ContractError::InvalidOrderParameters {})?
+            .max(dutch_auction.minimum_price)
+    };
+
+    Ok(new_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{ContractResult, OwnedDeps, SystemResult};
+
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: "owner".to_string(),
+            escrow_factory: "factory".to_string(),
+            authorized_relayers: vec!["relayer".to_string()],
+            require_registered_taker: false,
+            min_confirmations: 0,
+            fee_bps: 0,
+            fee_collector: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps
+    }
+
+    fn deploy_dst_msg(label: &str) -> ExecuteMsg {
+        ExecuteMsg::DeployDst {
+            taker: "taker".to_string(),
+            maker: "maker".to_string(),
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            src_chain_id: "osmosis-1".to_string(),
+            src_escrow_address: "src_escrow".to_string(),
+            expected_amount: Uint128::from(100u128),
+            dst_claim_addr: None,
+            allowed_cw20: None,
+            src_order_id: None,
+            hash_algo: None,
+            label: label.to_string(),
+        }
+    }
+
+    fn deploy_src_msg(on_expiry: Option<ExpiryAction>) -> ExecuteMsg {
+        ExecuteMsg::DeploySrc {
+            maker: "maker".to_string(),
+            taker: Some("taker".to_string()),
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            initial_price: Some(Uint128::from(200u128)),
+            price_decay_rate: Some(Uint128::from(1u128)),
+            minimum_price: Some(Uint128::from(100u128)),
+            decay_basis: None,
+            min_settle_price: None,
+            price_decimals: None,
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            dust_threshold: None,
+            lop_order_data: None,
+            on_expiry,
+            src_refund_addr: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            ibc_forward: None,
+            allowed_cw20: None,
+            relayer_reward: None,
+            principal: None,
+            relist: None,
+            hash_algo: None,
+            label: "order".to_string(),
+        }
+    }
+
+    // sha256("secret"), a hashlock the real escrows accept
+    const LIVE_SECRET_HASH: &str = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+
+    fn source_escrow_contract() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty>> {
+        Box::new(
+            cw_multi_test::ContractWrapper::new(
+                source_escrow::contract::execute,
+                source_escrow::contract::instantiate,
+                source_escrow::contract::query,
+            )
+            .with_reply(source_escrow::contract::reply),
+        )
+    }
+
+    /// Multi-test app running the resolver against a real factory and escrows, with "relayer" authorized
+    /// and the maker and taker holding uatom. Returns the app, the factory and the resolver.
+    fn multi_test_suite() -> (cw_multi_test::App, Addr, Addr) {
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::new(|router, _, storage| {
+            for account in ["maker", "taker"] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(account), cosmwasm_std::coins(1_000, "uatom"))
+                    .unwrap();
+            }
+        });
+        let factory_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                escrow_factory::contract::execute,
+                escrow_factory::contract::instantiate,
+                escrow_factory::contract::query,
+            )
+            .with_reply(escrow_factory::contract::reply),
+        ));
+        let src_code = app.store_code(source_escrow_contract());
+        let dst_code = app.store_code(Box::new(ContractWrapper::new(
+            destination_escrow::contract::execute,
+            destination_escrow::contract::instantiate,
+            destination_escrow::contract::query,
+        )));
+        let resolver_code = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply)));
+
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                Addr::unchecked("owner"),
+                &escrow_factory::msg::InstantiateMsg {
+                    owner: "owner".to_string(),
+                    source_escrow_code_id: src_code,
+                    destination_escrow_code_id: dst_code,
+                },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+        let resolver = app
+            .instantiate_contract(
+                resolver_code,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    owner: "owner".to_string(),
+                    escrow_factory: factory.to_string(),
+                    authorized_relayers: vec!["relayer".to_string()],
+                    require_registered_taker: false,
+                    min_confirmations: 0,
+                    fee_bps: 0,
+                    fee_collector: None,
+                },
+                &[],
+                "resolver",
+                None,
+            )
+            .unwrap();
+        (app, factory, resolver)
+    }
+
+    fn multi_test_order(app: &cw_multi_test::App, resolver: &Addr, order_id: &str) -> OrderResponse {
+        app.wrap()
+            .query_wasm_smart(resolver, &QueryMsg::Order { order_id: order_id.to_string() })
+            .unwrap()
+    }
+
+    #[test]
+    fn expire_order_cancels_and_refunds() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&source_escrow_response(100)).unwrap()))
+        });
+
+        // Price has not reached the floor yet
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::ExpireOrder { order_id: "order_1".to_string() },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OrderNotExpired {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExpireOrder { order_id: "order_1".to_string() },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Expired, order.status);
+    }
+
+    #[test]
+    fn expire_order_rests_at_minimum() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            deploy_src_msg(Some(ExpiryAction::RestAtMinimum)),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExpireOrder { order_id: "order_1".to_string() },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => assert!(matches!(
+                cosmwasm_std::from_binary(msg).unwrap(),
+                source_escrow::msg::ExecuteMsg::RestAtMinimum {}
+            )),
+            _ => panic!("unexpected message"),
+        }
+
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Active, order.status);
+        let dutch_auction = order.dutch_auction.unwrap();
+        assert_eq!(Uint128::from(100u128), dutch_auction.current_price);
+        assert_eq!(Uint128::zero(), dutch_auction.price_decay_rate);
+    }
+
+    #[test]
+    fn batch_confirm_source_matches_all_orders() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for label in ["a", "b", "c"] {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg(label)).unwrap();
+        }
+
+        let confirmations = (1..=3)
+            .map(|i| (format!("order_{}", i), format!("tx_{}", i)))
+            .collect();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::BatchConfirmSource { confirmations, block_height: 42, proof: None },
+        )
+        .unwrap();
+        assert_eq!(3, res.messages.len());
+
+        for i in 1..=3 {
+            let order = orders().load(&deps.storage, format!("order_{}", i)).unwrap();
+            assert_eq!(OrderStatus::Matched, order.status);
+        }
+    }
+
+    #[test]
+    fn batch_confirm_source_applies_single_confirmation_checks() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("a")).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("c")).unwrap();
+        let batch = |ids: &[&str], proof: Option<&str>| ExecuteMsg::BatchConfirmSource {
+            confirmations: ids.iter().map(|id| (id.to_string(), format!("tx_{}", id))).collect(),
+            block_height: 42,
+            proof: proof.map(str::to_string),
+        };
+
+        // Source legs and repeated ids are rejected
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), batch(&["order_1", "order_2"], None)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOrderParameters {}));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), batch(&["order_1", "order_1"], None)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOrderParameters {}));
+
+        // Chain relayers may confirm their chain's orders only, and strict mode and the pause guard apply
+        let owner = mock_info("owner", &[]);
+        for (chain_id, relayer) in [("osmosis-1", "osmo_relayer"), ("ethereum-1", "eth_relayer")] {
+            let chain_relayers = ExecuteMsg::SetChainRelayers {
+                chain_id: chain_id.to_string(),
+                relayers: vec![relayer.to_string()],
+            };
+            execute(deps.as_mut(), mock_env(), owner.clone(), chain_relayers).unwrap();
+        }
+        let err = execute(deps.as_mut(), mock_env(), mock_info("eth_relayer", &[]), batch(&["order_1"], None))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRelayer {}));
+        let osmo_relayer = mock_info("osmo_relayer", &[]);
+
+        execute(deps.as_mut(), mock_env(), owner.clone(), ExecuteMsg::SetProofRequired { required: true }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), osmo_relayer.clone(), batch(&["order_1"], None)).unwrap_err();
+        assert!(matches!(err, ContractError::ProofRequired {}));
+
+        execute(deps.as_mut(), mock_env(), owner.clone(), ExecuteMsg::SetPaused { paused: true }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), osmo_relayer.clone(), batch(&["order_1"], Some("proof")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        execute(deps.as_mut(), mock_env(), owner, ExecuteMsg::SetPaused { paused: false }).unwrap();
+
+        execute(deps.as_mut(), mock_env(), osmo_relayer, batch(&["order_1", "order_3"], Some("proof"))).unwrap();
+        assert_eq!(OrderStatus::Matched, orders().load(&deps.storage, "order_1".to_string()).unwrap().status);
+    }
+
+    #[test]
+    fn confirm_source_requires_min_confirmations() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("dst")).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetMinConfirmations { min_confirmations: 10 },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::UpdateSourceHead { chain_id: "osmosis-1".to_string(), height: 105 },
+        )
+        .unwrap();
+
+        let confirm = |block_height: u64| ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height },
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), confirm(100)).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientConfirmations {}));
+
+        execute(deps.as_mut(), mock_env(), info, confirm(95)).unwrap();
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Matched, order.status);
+    }
+
+    #[test]
+    fn deploy_forwards_payout_addresses() {
+        let mut deps = setup();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { src_refund_addr, .. } = &mut msg {
+            *src_refund_addr = Some("refund".to_string());
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                match cosmwasm_std::from_binary(msg).unwrap() {
+                    escrow_factory::msg::ExecuteMsg::CreateSourceEscrow { refund_addr, .. } => {
+                        assert_eq!(Some("refund".to_string()), refund_addr)
+                    }
+                    _ => panic!("unexpected factory message"),
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(Some(cosmwasm_std::Addr::unchecked("refund")), order.src_refund_addr);
+        assert_eq!(None, order.dst_claim_addr);
+    }
+
+    #[test]
+    fn partial_execute_swap_fills_both_legs() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+
+        let mut src_msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { allow_partial_fill, .. } = &mut src_msg {
+            *allow_partial_fill = true;
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), src_msg).unwrap();
+
+        let mut dst_msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { expected_amount, src_order_id, .. } = &mut dst_msg {
+            *expected_amount = Uint128::from(300u128);
+            *src_order_id = Some("order_1".to_string());
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), dst_msg).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ProcessOrder {
+                order_id: "order_1".to_string(),
+                action: OrderAction::PartialExecuteSwap {
+                    secret: "secret".to_string(),
+                    amount: Uint128::from(50u128),
+                },
+                proof: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+
+        match &res.messages[1].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                match cosmwasm_std::from_binary(msg).unwrap() {
+                    destination_escrow::msg::ExecuteMsg::PartialWithdraw { amount, .. } => {
+                        assert_eq!(Uint128::from(150u128), amount)
+                    }
+                    _ => panic!("unexpected destination message"),
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        let src_order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        let src_fill = src_order.partial_fill.unwrap();
+        assert_eq!(Uint128::from(50u128), src_fill.filled_amount);
+        assert_eq!(Uint128::from(50u128), src_fill.remaining_amount);
+        assert_eq!(OrderStatus::Active, src_order.status);
+        assert_eq!(Some("order_2".to_string()), src_order.counterparty_order_id);
+
+        let dst_order = query_order(deps.as_ref(), "order_2".to_string()).unwrap();
+        let dst_fill = dst_order.partial_fill.unwrap();
+        assert_eq!(Uint128::from(150u128), dst_fill.filled_amount);
+        assert_eq!(Uint128::from(150u128), dst_fill.remaining_amount);
+        assert_eq!(Some("order_1".to_string()), dst_order.counterparty_order_id);
+    }
+
+    #[test]
+    fn unregistered_taker_rejected_only_when_required() {
+        let mut deps = setup();
+        let owner = mock_info("owner", &[]);
+        let relayer = mock_info("relayer", &[]);
+
+        // Not required: any taker deploys
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_dst_msg("a")).unwrap();
+
+        let msg = ExecuteMsg::SetRequireRegisteredTaker { required: true };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_dst_msg("b")).unwrap_err();
+        assert!(matches!(err, ContractError::TakerNotRegistered {}));
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap_err();
+        assert!(matches!(err, ContractError::TakerNotRegistered {}));
+
+        let msg = ExecuteMsg::RegisterTaker { taker: "taker".to_string() };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_dst_msg("c")).unwrap();
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap();
+
+        let msg = ExecuteMsg::DeregisterTaker { taker: "taker".to_string() };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), relayer, deploy_dst_msg("d")).unwrap_err();
+        assert!(matches!(err, ContractError::TakerNotRegistered {}));
+    }
+
+    #[test]
+    fn price_decimals_default_and_round_trip() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        let price = query_current_price(deps.as_ref(), mock_env(), "pending".to_string()).unwrap();
+        assert_eq!(6, price.price_decimals);
+
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { price_decimals, .. } = &mut msg {
+            *price_decimals = Some(18);
+        }
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match cosmwasm_std::from_binary(msg).unwrap() {
+                escrow_factory::msg::ExecuteMsg::CreateSourceEscrow { price_decimals, .. } => {
+                    assert_eq!(Some(18), price_decimals)
+                }
+                _ => panic!("unexpected factory message"),
+            },
+            _ => panic!("unexpected message"),
+        }
+        let mut order = orders().load(&deps.storage, "order_2".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow_2");
+        orders().save(deps.as_mut().storage, "order_2".to_string(), &order).unwrap();
+        let price = query_current_price(deps.as_ref(), mock_env(), "escrow_2".to_string()).unwrap();
+        assert_eq!(18, price.price_decimals);
+
+        // Escrows without an auction report the default
+        let price = query_current_price(deps.as_ref(), mock_env(), "unknown".to_string()).unwrap();
+        assert_eq!(6, price.price_decimals);
+    }
+
+    #[test]
+    fn price_tolerates_block_time_before_auction_start() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.minus_seconds(100);
+        let price = query_current_price(deps.as_ref(), env.clone(), "pending".to_string()).unwrap();
+        assert_eq!(0, price.time_elapsed);
+
+        let order = orders().load(deps.as_ref().storage, "order_1".to_string()).unwrap();
+        let auction = order.dutch_auction.unwrap();
+        let current_price = calculate_current_price(&auction, &env.block).unwrap();
+        assert_eq!(Uint128::from(200u128), current_price);
+    }
+
+    #[test]
+    fn evaluate_fill_prices_offer_against_live_auction() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+
+        let accepted = query_evaluate_fill(
+            deps.as_ref(),
+            mock_env(),
+            "pending".to_string(),
+            Uint128::from(250u128),
+        )
+        .unwrap();
+        assert_eq!(Uint128::from(200u128), accepted.current_price);
+        assert!(accepted.accepted);
+        assert_eq!(Uint128::zero(), accepted.shortfall);
+
+        // 30s of decay at 1/s, without any UpdatePrice refreshing the cached price
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(30);
+        let rejected =
+            query_evaluate_fill(deps.as_ref(), env, "pending".to_string(), Uint128::from(150u128))
+                .unwrap();
+        assert_eq!(Uint128::from(170u128), rejected.current_price);
+        assert!(!rejected.accepted);
+        assert_eq!(Uint128::from(20u128), rejected.shortfall);
+    }
+
+    #[test]
+    fn preview_deploy_src_matches_actual_deploy() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_dst_msg("first")).unwrap();
+
+        let env = mock_env();
+        let preview = query_preview_deploy_src(
+            deps.as_ref(),
+            env.clone(),
+            "maker".to_string(),
+            Some("taker".to_string()),
+            None,
+            "order".to_string(),
+        )
+        .unwrap();
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), deploy_src_msg(None))
+            .unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr("order_id", preview.order_id.clone())));
+        assert_eq!("order_2", preview.order_id);
+
+        // The factory derives its salt from the caller (this contract), block time and label
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                match cosmwasm_std::from_binary(msg).unwrap() {
+                    escrow_factory::msg::ExecuteMsg::CreateSourceEscrow { label, .. } => {
+                        let salt = escrow_factory::contract::escrow_salt(&env.contract.address, &env, &label);
+                        assert_eq!(salt, preview.salt);
+                    }
+                    _ => panic!("unexpected factory message"),
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    fn source_escrow_response(deposited_amount: u128) -> source_escrow::msg::EscrowResponse {
+        source_escrow::msg::EscrowResponse {
+            maker: cosmwasm_std::Addr::unchecked("maker"),
+            taker: Some(cosmwasm_std::Addr::unchecked("taker")),
+            refund_addr: None,
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            deposited_amount: Uint128::from(deposited_amount),
+            deposited_denom: None,
+            cw20_contract: None,
+            allowed_cw20: None,
+            status: source_escrow::msg::EscrowStatus::Active,
+            created_at: 0,
+            allow_partial_fill: false,
+            filled_amount: Uint128::zero(),
+            remaining_amount: Uint128::from(deposited_amount),
+            dust_threshold: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            safety_deposit_funded: false,
+            ibc_forward: None,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: 0,
+            total_extension: 0,
+            hash_algorithm: HashAlgo::Sha256,
+            timelocks: Timelocks::from_timelock(0, 1000),
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            last_used_index: None,
+            tranche_count: None,
+            cancelled_tranches: vec![],
+        }
+    }
+
+    fn destination_escrow_response(timelock: u64) -> destination_escrow::msg::EscrowResponse {
+        destination_escrow::msg::EscrowResponse {
+            taker: cosmwasm_std::Addr::unchecked("taker"),
+            maker: cosmwasm_std::Addr::unchecked("maker"),
+            claim_addr: None,
+            secret_hash: "hash123".to_string(),
+            timelock,
+            src_chain_id: "osmosis-1".to_string(),
+            src_escrow_address: "src_escrow".to_string(),
+            expected_amount: Uint128::from(100u128),
+            deposited_amount: Uint128::from(100u128),
+            deposited_denom: None,
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            filled_amount: Uint128::zero(),
+            remaining_amount: Uint128::from(100u128),
+            cw20_contract: None,
+            allowed_cw20: None,
+            status: destination_escrow::msg::EscrowStatus::Active,
+            created_at: 0,
+            src_confirmed: true,
+            src_tx_hash: None,
+            src_block_height: None,
+            hash_algorithm: HashAlgo::Sha256,
+        }
+    }
+
+    /// Answers every escrow query with a funded source escrow holding `denom`
+    fn mock_escrow_denom(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, denom: &str) {
+        let mut escrow = source_escrow_response(100);
+        escrow.deposited_denom = Some(denom.to_string());
+        deps.querier
+            .update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap())));
+    }
+
+    /// Deploys a linked source/destination pair at `src_escrow` and `dst_escrow`.
+    fn setup_linked_orders(
+        dst_timelock: u64,
+        allow_partial_fill: bool,
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        let mut src_msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { allow_partial_fill: partial, .. } = &mut src_msg {
+            *partial = allow_partial_fill;
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), src_msg).unwrap();
+        let mut dst_msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { src_order_id, .. } = &mut dst_msg {
+            *src_order_id = Some("order_1".to_string());
+        }
+        execute(deps.as_mut(), mock_env(), info, dst_msg).unwrap();
+
+        for (order_id, escrow) in [("order_1", "src_escrow"), ("order_2", "dst_escrow")] {
+            let mut order = orders().load(deps.as_ref().storage, order_id.to_string()).unwrap();
+            order.escrow_address = cosmwasm_std::Addr::unchecked(escrow);
+            orders().save(deps.as_mut().storage, order_id.to_string(), &order).unwrap();
+        }
+
+        deps.querier.update_wasm(move |query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, .. } if contract_addr == "src_escrow" => {
+                SystemResult::Ok(ContractResult::Ok(to_binary(&source_escrow_response(100)).unwrap()))
+            }
+            _ => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&destination_escrow_response(dst_timelock)).unwrap(),
+            )),
+        });
+        deps
+    }
+
+    #[test]
+    fn confirm_source_waits_for_fully_funded_source() {
+        let mut deps = setup_linked_orders(1000, false);
+        let source_deposit = |deposited: u128| {
+            move |query: &cosmwasm_std::WasmQuery| match query {
+                cosmwasm_std::WasmQuery::Smart { contract_addr, .. } if contract_addr == "src_escrow" => {
+                    let mut escrow = source_escrow_response(deposited);
+                    escrow.src_amount = Some(Uint128::from(100u128));
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+                }
+                _ => SystemResult::Ok(ContractResult::Ok(to_binary(&destination_escrow_response(1000)).unwrap())),
+            }
+        };
+        let confirm = ExecuteMsg::ProcessOrder {
+            order_id: "order_2".to_string(),
+            action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height: 95 },
+            proof: None,
+        };
+
+        deps.querier.update_wasm(source_deposit(60));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), confirm.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::SourceNotFunded {}));
+
+        deps.querier.update_wasm(source_deposit(100));
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), confirm).unwrap();
+        assert_eq!(OrderStatus::Matched, orders().load(&deps.storage, "order_2".to_string()).unwrap().status);
+    }
+
+    #[test]
+    fn cancel_both_unwinds_destination_then_source() {
+        let mut deps = setup_linked_orders(1000, false);
+        let msg = ExecuteMsg::CancelBoth { order_id: "order_1".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        assert_eq!(2, res.messages.len());
+        match (&res.messages[0].msg, &res.messages[1].msg) {
+            (
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: first, .. }),
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: second, .. }),
+            ) => {
+                assert_eq!("dst_escrow", first);
+                assert_eq!("src_escrow", second);
+            }
+            _ => panic!("unexpected messages"),
+        }
+        for order_id in ["order_1", "order_2"] {
+            let order = query_order(deps.as_ref(), order_id.to_string()).unwrap();
+            assert_eq!(OrderStatus::Cancelled, order.status);
+        }
+    }
+
+    #[test]
+    fn cancel_both_reports_leg_still_timelocked() {
+        let far_future = mock_env().block.time.seconds() + 1000;
+        let mut deps = setup_linked_orders(far_future, false);
+        let msg = ExecuteMsg::CancelBoth { order_id: "order_2".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        assert_eq!(1, res.messages.len());
+        assert!(res.attributes.contains(&cosmwasm_std::attr("cancelled", "order_1")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("pending", "order_2")));
+
+        let dst_order = query_order(deps.as_ref(), "order_2".to_string()).unwrap();
+        assert_eq!(OrderStatus::Active, dst_order.status);
+    }
+
+    #[test]
+    fn archive_orders_moves_terminal_orders() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for label in ["a", "b"] {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg(label)).unwrap();
+        }
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.status = OrderStatus::Completed;
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+
+        let before_ts = mock_env().block.time.seconds() + 1;
+        let msg = ExecuteMsg::ArchiveOrders { before_ts, limit: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr("order_ids", "order_1")));
+
+        let archived = query_archived_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Completed, archived.status);
+        assert!(query_order(deps.as_ref(), "order_1".to_string()).is_err());
+
+        let active = query_active_orders(deps.as_ref(), None, None).unwrap();
+        assert_eq!(1, active.orders.len());
+        assert_eq!("order_2", active.orders[0].order_id);
+    }
+
+    #[test]
+    fn orders_filter_by_status_and_maker() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for maker in ["maker", "maker", "other"] {
+            let mut msg = deploy_src_msg(None);
+            if let ExecuteMsg::DeploySrc { maker: m, .. } = &mut msg {
+                *m = maker.to_string();
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+
+        let withdraw = ExecuteMsg::Withdraw { escrow_address: "escrow".to_string(), secret: "secret".to_string() };
+        execute(deps.as_mut(), mock_env(), info, withdraw).unwrap();
+
+        let ids = |list: OrderListResponse| list.orders.into_iter().map(|o| o.order_id).collect::<Vec<_>>();
+        assert_eq!(vec!["order_2", "order_3"], ids(query_active_orders(deps.as_ref(), None, None).unwrap()));
+        assert_eq!(
+            vec!["order_1"],
+            ids(query_orders_by_status(deps.as_ref(), OrderStatus::Completed, None, None).unwrap())
+        );
+        assert_eq!(
+            vec!["order_1", "order_2"],
+            ids(query_orders_by_maker(deps.as_ref(), "maker".to_string(), None, None).unwrap())
+        );
+        assert_eq!(
+            vec!["order_2"],
+            ids(query_orders_by_maker(deps.as_ref(), "maker".to_string(), Some("order_1".to_string()), None).unwrap())
+        );
+        assert!(query_orders_by_status(deps.as_ref(), OrderStatus::Cancelled, None, None).unwrap().orders.is_empty());
+    }
+
+    fn imported_order(order_id: &str, status: OrderStatus) -> ImportedOrder {
+        ImportedOrder {
+            order_id: order_id.to_string(),
+            escrow_address: "old_escrow".to_string(),
+            escrow_type: EscrowType::Source,
+            maker: "maker".to_string(),
+            taker: Some("taker".to_string()),
+            secret_hash: "hash123".to_string(),
+            src_amount: None,
+            dst_amount: Uint128::from(100u128),
+            status,
+            created_at: 100,
+            updated_at: 200,
+            src_chain_id: None,
+            dst_chain_id: Some("ethereum-1".to_string()),
+            filled_at: Some(200),
+        }
+    }
+
+    #[test]
+    fn import_orders_seeds_archive_only() {
+        let mut deps = setup();
+        let owner = mock_info("owner", &[]);
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_dst_msg("dst")).unwrap();
+
+        let import = ExecuteMsg::ImportOrders {
+            orders: vec![
+                imported_order("legacy_1", OrderStatus::Completed),
+                imported_order("legacy_2", OrderStatus::Cancelled),
+            ],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), import.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), import.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(
+            OrderStatus::Cancelled,
+            query_archived_order(deps.as_ref(), "legacy_2".to_string()).unwrap().status
+        );
+        let active = query_active_orders(deps.as_ref(), None, None).unwrap();
+        assert_eq!(vec!["order_1"], active.orders.iter().map(|o| o.order_id.as_str()).collect::<Vec<_>>());
+
+        // Re-importing or importing a live order is rejected
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), import).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOrderParameters {}));
+        let live = ExecuteMsg::ImportOrders { orders: vec![imported_order("legacy_3", OrderStatus::Active)] };
+        let err = execute(deps.as_mut(), mock_env(), owner, live).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOrderParameters {}));
+    }
+
+    #[test]
+    fn fast_fill_earns_rebate() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetFastFillRebate { window: 60, rebate_bps: 2500 },
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(30);
+        let swap = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+            proof: None,
+        };
+        let res = execute(deps.as_mut(), env, info, swap).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr("fast_fill", "true")));
+
+        let config = CONFIG.load(&deps.storage).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(
+            Uint128::from(250u128),
+            fast_fill_rebate(&config, &order, Uint128::from(1000u128))
+        );
+
+        // Filled after the window closes
+        order.filled_at = Some(order.created_at + 120);
+        assert!(fast_fill_rebate(&config, &order, Uint128::from(1000u128)).is_zero());
+    }
+
+    #[test]
+    fn search_orders_applies_all_filters() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("dst")).unwrap();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { maker, .. } = &mut msg {
+            *maker = "other".to_string();
+        }
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.status = OrderStatus::Completed;
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+
+        let search = |maker: Option<&str>,
+                      status: Option<OrderStatus>,
+                      chain: Option<&str>,
+                      start_after: Option<&str>,
+                      limit: Option<u32>|
+         -> Vec<String> {
+            query_search_orders(
+                deps.as_ref(),
+                maker.map(|m| m.to_string()),
+                status,
+                chain.map(|c| c.to_string()),
+                start_after.map(|s| s.to_string()),
+                limit,
+            )
+            .unwrap()
+            .orders
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect()
+        };
+
+        assert_eq!(vec!["order_1", "order_2"], search(Some("maker"), None, None, None, None));
+        assert_eq!(
+            vec!["order_2"],
+            search(Some("maker"), Some(OrderStatus::Active), None, None, None)
+        );
+        assert_eq!(
+            vec!["order_3"],
+            search(None, Some(OrderStatus::Active), Some("ethereum-1"), None, None)
+        );
+        assert_eq!(vec!["order_2"], search(None, None, Some("osmosis-1"), None, None));
+        assert_eq!(vec!["order_1"], search(Some("maker"), None, None, None, Some(1)));
+        assert_eq!(vec!["order_2"], search(Some("maker"), None, None, Some("order_1"), None));
+    }
+
+    #[test]
+    fn deploy_src_rejects_oversized_minimum_fill() {
+        let mut deps = setup();
+        let with_min_fill = |amount: u128| {
+            let mut msg = deploy_src_msg(None);
+            if let ExecuteMsg::DeploySrc { allow_partial_fill, minimum_fill_amount, .. } = &mut msg {
+                *allow_partial_fill = true;
+                *minimum_fill_amount = Some(Uint128::from(amount));
+            }
+            msg
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), with_min_fill(101))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::MinimumFillTooLarge {}));
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), with_min_fill(100)).unwrap();
+    }
+
+    #[test]
+    fn frozen_order_blocks_withdraw_only() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for _ in 0..2 {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        }
+        for (order_id, escrow) in [("order_1", "escrow_1"), ("order_2", "escrow_2")] {
+            let mut order = orders().load(&deps.storage, order_id.to_string()).unwrap();
+            order.escrow_address = cosmwasm_std::Addr::unchecked(escrow);
+            orders().save(deps.as_mut().storage, order_id.to_string(), &order).unwrap();
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::SetOrderFrozen { order_id: "order_1".to_string(), frozen: true },
+        )
+        .unwrap();
+
+        let withdraw = |escrow: &str| ExecuteMsg::Withdraw {
+            escrow_address: escrow.to_string(),
+            secret: "secret".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), withdraw("escrow_1")).unwrap_err();
+        assert!(matches!(err, ContractError::OrderFrozen {}));
+        execute(deps.as_mut(), mock_env(), info.clone(), withdraw("escrow_2")).unwrap();
+
+        // Cancellation stays available for the frozen order
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, cancel).unwrap();
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Cancelled, order.status);
+        assert!(order.frozen);
+    }
+
+    #[test]
+    fn paused_fills_still_allow_cancel_and_remainder() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        let mut partial = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { allow_partial_fill, .. } = &mut partial {
+            *allow_partial_fill = true;
+        }
+        for _ in 0..2 {
+            execute(deps.as_mut(), mock_env(), info.clone(), partial.clone()).unwrap();
+        }
+        for (order_id, escrow) in [("order_1", "escrow_1"), ("order_2", "escrow_2")] {
+            let mut order = orders().load(&deps.storage, order_id.to_string()).unwrap();
+            order.escrow_address = cosmwasm_std::Addr::unchecked(escrow);
+            orders().save(deps.as_mut().storage, order_id.to_string(), &order).unwrap();
+        }
+        let set_accepting = |order_id: &str, accepting: bool| ExecuteMsg::SetOrderAcceptingFills {
+            order_id: order_id.to_string(),
+            accepting,
+        };
+        let fill = |escrow: &str, amount: u128| ExecuteMsg::PartialWithdraw {
+            escrow_address: escrow.to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(amount),
+        };
+
+        execute(deps.as_mut(), mock_env(), info.clone(), set_accepting("order_1", false)).unwrap();
+        assert!(!query_order(deps.as_ref(), "order_1".to_string()).unwrap().accepting_fills);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), fill("escrow_1", 10)).unwrap_err();
+        assert!(matches!(err, ContractError::OrderNotAcceptingFills {}));
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), cancel).unwrap();
+        assert_eq!(OrderStatus::Cancelled, query_order(deps.as_ref(), "order_1".to_string()).unwrap().status);
+
+        // A started order can still clear its remainder, but nothing smaller
+        execute(deps.as_mut(), mock_env(), info.clone(), fill("escrow_2", 10)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), set_accepting("order_2", false)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), fill("escrow_2", 10)).unwrap_err();
+        assert!(matches!(err, ContractError::OrderNotAcceptingFills {}));
+        execute(deps.as_mut(), mock_env(), info, fill("escrow_2", 90)).unwrap();
+        assert_eq!(OrderStatus::Completed, query_order(deps.as_ref(), "order_2".to_string()).unwrap().status);
+    }
+
+    #[test]
+    fn pause_blocks_deploys_but_not_cancels() {
+        let mut deps = setup();
+        let relayer = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+
+        let pause = |paused: bool| ExecuteMsg::SetPaused { paused };
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), pause(true)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), pause(true)).unwrap();
+        assert!(query_config(deps.as_ref()).unwrap().paused);
+
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_dst_msg("dst")).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        let withdraw = ExecuteMsg::Withdraw { escrow_address: "escrow".to_string(), secret: "secret".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), withdraw).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+
+        let cancel = ExecuteMsg::Cancel { escrow_address: "escrow".to_string() };
+        execute(deps.as_mut(), mock_env(), relayer.clone(), cancel).unwrap();
+        assert_eq!(OrderStatus::Cancelled, query_order(deps.as_ref(), "order_1".to_string()).unwrap().status);
+
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), pause(false)).unwrap();
+        execute(deps.as_mut(), mock_env(), relayer, deploy_src_msg(None)).unwrap();
+    }
+
+    #[test]
+    fn performance_stats_track_completion_times() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for _ in 0..3 {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        }
+
+        for (i, elapsed) in [(1, 30u64), (2, 90), (3, 600)] {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            let swap = ExecuteMsg::ProcessOrder {
+                order_id: format!("order_{}", i),
+                action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+                proof: None,
+            };
+            execute(deps.as_mut(), env, info.clone(), swap).unwrap();
+        }
+
+        let stats = query_performance_stats(deps.as_ref()).unwrap();
+        assert_eq!(3, stats.completed_count);
+        assert_eq!(240, stats.avg_completion_seconds);
+        assert_eq!(300, stats.median_bucket);
+    }
+
+    #[test]
+    fn maker_can_veto_pending_cancel() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetCancelGracePeriod { seconds: 60 },
+        )
+        .unwrap();
+
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), cancel.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), cancel.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CancelPending {}));
+
+        let veto = ExecuteMsg::VetoCancel { order_id: "order_1".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), veto.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), veto).unwrap();
+
+        // The vetoed cancel is dropped; a retry only opens a new window
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(120);
+        let res = execute(deps.as_mut(), later, info, cancel).unwrap();
+        assert!(res.messages.is_empty());
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Active, order.status);
+    }
+
+    #[test]
+    fn direct_escrow_cancel_respects_maker_veto() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        let grace = ExecuteMsg::SetCancelGracePeriod { seconds: 60 };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), grace).unwrap();
+
+        // Cancelling by escrow address opens the same veto window as ProcessOrder
+        let cancel = ExecuteMsg::Cancel { escrow_address: "escrow".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), cancel.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), cancel.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CancelPending {}));
+
+        let veto = ExecuteMsg::VetoCancel { order_id: "order_1".to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), veto).unwrap();
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(120);
+        let res = execute(deps.as_mut(), later, info, cancel).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(OrderStatus::Active, query_order(deps.as_ref(), "order_1".to_string()).unwrap().status);
+    }
+
+    #[test]
+    fn unvetoed_cancel_completes_after_grace_period() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetCancelGracePeriod { seconds: 60 },
+        )
+        .unwrap();
+
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), cancel.clone()).unwrap();
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(60);
+        let res = execute(deps.as_mut(), later, info, cancel).unwrap();
+        assert_eq!(1, res.messages.len());
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Cancelled, order.status);
+    }
+
+    #[test]
+    fn relayer_scope_lists_actionable_orders() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for _ in 0..3 {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        }
+        let mut order = orders().load(&deps.storage, "order_2".to_string()).unwrap();
+        order.status = OrderStatus::Completed;
+        orders().save(deps.as_mut().storage, "order_2".to_string(), &order).unwrap();
+
+        let scope = query_relayer_scope(deps.as_ref(), mock_env(), "relayer".to_string(), None, Some(1)).unwrap();
+        assert!(scope.is_authorized);
+        assert_eq!(2, scope.actionable_count);
+        assert_eq!(vec!["order_1".to_string()], scope.order_ids);
+
+        let page = query_relayer_scope(
+            deps.as_ref(),
+            mock_env(),
+            "relayer".to_string(),
+            Some("order_1".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(vec!["order_3".to_string()], page.order_ids);
+
+        let outsider = query_relayer_scope(deps.as_ref(), mock_env(), "outsider".to_string(), None, None).unwrap();
+        assert!(!outsider.is_authorized);
+        assert!(outsider.order_ids.is_empty());
+    }
+
+    #[test]
+    fn partial_withdraw_splits_price_improvement() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetPriceImprovementSplit {
+                maker_bps: 4000,
+                collector: Some("collector".to_string()),
+            },
+        )
+        .unwrap();
+
+        mock_escrow_denom(&mut deps, "uusdc");
+        let withdraw = ExecuteMsg::PartialWithdraw {
+            escrow_address: "escrow".to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(10u128),
+        };
+
+        // Price decayed from 200 to 160: (160 - 100) * 10 = 600 surplus
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(40);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), withdraw.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::SurplusMismatch {}));
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("relayer", &cosmwasm_std::coins(600, "uusdc")),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: cosmwasm_std::coins(240, "uusdc"),
+            }),
+            res.messages[1].msg
+        );
+        // The collector's share accrues for WithdrawFees, apart from swap fees
+        let fees = query_accrued_fees(deps.as_ref()).unwrap();
+        assert_eq!(cosmwasm_std::coins(360, "uusdc"), fees.price_improvement);
+        assert!(fees.native.is_empty());
+
+        // At the floor there is no surplus to route
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(150);
+        let res = execute(deps.as_mut(), env, mock_info("relayer", &[]), withdraw).unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn price_improvement_must_be_paid_in_escrowed_asset() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        let split = ExecuteMsg::SetPriceImprovementSplit { maker_bps: 4000, collector: Some("collector".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), split).unwrap();
+        mock_escrow_denom(&mut deps, "uusdc");
+
+        let withdraw = ExecuteMsg::PartialWithdraw {
+            escrow_address: "escrow".to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(10u128),
+        };
+        // The right amount of the wrong asset is still a mismatch
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(40);
+        let payer = mock_info("relayer", &cosmwasm_std::coins(600, "ujunk"));
+        let err = execute(deps.as_mut(), env.clone(), payer, withdraw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::SurplusMismatch {}));
+        assert!(query_accrued_fees(deps.as_ref()).unwrap().price_improvement.is_empty());
+
+        let payer = mock_info("relayer", &cosmwasm_std::coins(600, "uusdc"));
+        execute(deps.as_mut(), env, payer, withdraw).unwrap();
+    }
+
+    #[test]
+    fn fee_exempt_maker_keeps_full_price_improvement() {
+        let mut deps = setup();
+        let relayer = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { maker, .. } = &mut msg {
+            *maker = "partner".to_string();
+        }
+        execute(deps.as_mut(), mock_env(), relayer, msg).unwrap();
+        for (order_id, escrow) in [("order_1", "escrow_1"), ("order_2", "escrow_2")] {
+            let mut order = orders().load(&deps.storage, order_id.to_string()).unwrap();
+            order.escrow_address = cosmwasm_std::Addr::unchecked(escrow);
+            orders().save(deps.as_mut().storage, order_id.to_string(), &order).unwrap();
+        }
+        let owner = mock_info("owner", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetPriceImprovementSplit { maker_bps: 4000, collector: Some("collector".to_string()) },
+        )
+        .unwrap();
+        let exempt = ExecuteMsg::SetFeeExempt { maker: "partner".to_string(), exempt: true };
+        execute(deps.as_mut(), mock_env(), owner, exempt).unwrap();
+        assert!(query_order(deps.as_ref(), "order_2".to_string()).unwrap().fee_exempt);
+        assert!(!query_order(deps.as_ref(), "order_1".to_string()).unwrap().fee_exempt);
+
+        // (160 - 100) * 10 = 600 surplus on each order
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(40);
+        mock_escrow_denom(&mut deps, "uusdc");
+        let withdraw = |escrow: &str| ExecuteMsg::PartialWithdraw {
+            escrow_address: escrow.to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(10u128),
+        };
+        let payer = mock_info("relayer", &cosmwasm_std::coins(600, "uusdc"));
+
+        let res = execute(deps.as_mut(), env.clone(), payer.clone(), withdraw("escrow_2")).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "partner".to_string(),
+                amount: cosmwasm_std::coins(600, "uusdc"),
+            }),
+            res.messages[1].msg
+        );
+        assert!(query_accrued_fees(deps.as_ref()).unwrap().price_improvement.is_empty());
+
+        execute(deps.as_mut(), env, payer, withdraw("escrow_1")).unwrap();
+        assert_eq!(
+            cosmwasm_std::coins(360, "uusdc"),
+            query_accrued_fees(deps.as_ref()).unwrap().price_improvement
+        );
+    }
+
+    fn signed_src_order() -> SignedSrcOrder {
+        SignedSrcOrder {
+            resolver: "cosmos2contract".to_string(),
+            maker: "maker".to_string(),
+            taker: Some("taker".to_string()),
+            secret_hash: "hash123".to_string(),
+            timelock: 1000,
+            dst_chain_id: "ethereum-1".to_string(),
+            dst_asset: "ETH".to_string(),
+            dst_amount: Uint128::from(100u128),
+            src_amount: None,
+            initial_price: Some(Uint128::from(200u128)),
+            price_decay_rate: Some(Uint128::from(1u128)),
+            minimum_price: Some(Uint128::from(100u128)),
+            allow_partial_fill: false,
+            minimum_fill_amount: None,
+            nonce: 1,
+            label: "order".to_string(),
+        }
+    }
+
+    #[test]
+    fn deploy_src_signed_verifies_maker_signature() {
+        let mut deps = setup();
+        let pubkey = Binary::from_base64("AxHapNqb5lYnfsx7S5xUDch1ZIn1HCpmzaFx+N0zERqB").unwrap();
+        let signature = Binary::from_base64(
+            "dSJCkgBAtmS8E082UqZvyc8KfTUwDMRGpHKOHVgtLu5G/qqFpDJMcX/Lr0gUPebMGRwVRP5y4LrlBOBqbPEpjA==",
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("maker", &[]),
+            ExecuteMsg::RegisterMakerPubkey { pubkey },
+        )
+        .unwrap();
+
+        // Tampering with any signed field invalidates the signature
+        let tampered = SignedSrcOrder {
+            dst_amount: Uint128::from(1u128),
+            ..signed_src_order()
+        };
+        let msg = ExecuteMsg::DeploySrcSigned { order: tampered, maker_signature: signature.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        let msg = ExecuteMsg::DeploySrcSigned { order: signed_src_order(), maker_signature: signature };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg.clone()).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr(
+            "signed_order_hash",
+            "15adbac36c6fd2674d2cf68f8042f8f35b8152e4f4f20edf12664cb7be80d3fa"
+        )));
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(cosmwasm_std::Addr::unchecked("maker"), order.maker);
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::SignedOrderUsed {}));
+    }
+
+    #[test]
+    fn withdraw_signed_verifies_maker_authorization() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let pubkey = Binary::from_base64("A/EvJz5G0tyokLneofJKQqZZBKu9STwMLw6+mzdJRs5+").unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("maker", &[]),
+            ExecuteMsg::RegisterMakerPubkey { pubkey },
+        )
+        .unwrap();
+        let signature = Binary::from_base64(
+            "vhk9TJePqs3mhlk8X2OaZvFJqqkaVqgLbfCjynvDLi9yXqmnrtgRMhc70Vf1G7teUIxh+N6cyzFyQ7EM9wW9kQ==",
+        )
+        .unwrap();
+        let withdraw = |secret: &str| ExecuteMsg::WithdrawSigned {
+            order_id: "order_1".to_string(),
+            signature: signature.clone(),
+            secret: secret.to_string(),
+        };
+
+        // The signature covers the secret, so a relayer cannot swap it out
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), withdraw("other")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        // Like an executed swap, it waits out another relayer's exclusive window and pays the protocol fee
+        let owner = mock_info("owner", &[]);
+        let add = ExecuteMsg::AddRelayer { relayer: "relayer2".to_string(), ttl: None };
+        execute(deps.as_mut(), mock_env(), owner.clone(), add).unwrap();
+        let window = ExecuteMsg::SetRelayerExclusiveWindow { seconds: 60 };
+        execute(deps.as_mut(), mock_env(), owner.clone(), window).unwrap();
+        let assign = ExecuteMsg::AssignRelayer { order_id: "order_1".to_string(), relayer: Some("relayer2".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), assign).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), withdraw("secret")).unwrap_err();
+        assert!(matches!(err, ContractError::NotAssignedRelayer {}));
+
+        deps.querier.update_wasm(|_| {
+            let mut escrow = source_escrow_response(100);
+            escrow.deposited_denom = Some("uatom".to_string());
+            SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+        });
+        let update = ExecuteMsg::UpdateFee { fee_bps: 250, fee_collector: "collector".to_string() };
+        execute(deps.as_mut(), mock_env(), owner, update).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer2", &[]), withdraw("secret")).unwrap_err();
+        assert!(matches!(err, ContractError::FeeMismatch {}));
+
+        let relayer = mock_info("relayer2", &cosmwasm_std::coins(2, "uatom"));
+        let res = execute(deps.as_mut(), mock_env(), relayer.clone(), withdraw("secret")).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match cosmwasm_std::from_binary(msg).unwrap() {
+                source_escrow::msg::ExecuteMsg::Withdraw { secret } => assert_eq!("secret", secret),
+                _ => panic!("unexpected escrow message"),
+            },
+            _ => panic!("unexpected message"),
+        }
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Completed, order.status);
+        assert_eq!(cosmwasm_std::coins(2, "uatom"), query_accrued_fees(deps.as_ref()).unwrap().native);
+
+        let err = execute(deps.as_mut(), mock_env(), relayer, withdraw("secret")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOrderParameters {}));
+    }
+
+    #[test]
+    fn assigned_relayer_has_exclusive_window() {
+        let mut deps = setup();
+        let owner = mock_info("owner", &[]);
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::AddRelayer { relayer: "relayer2".to_string(), ttl: None },
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), owner, ExecuteMsg::SetRelayerExclusiveWindow { seconds: 60 })
+            .unwrap();
+
+        let assign = ExecuteMsg::AssignRelayer {
+            order_id: "order_1".to_string(),
+            relayer: Some("relayer".to_string()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer2", &[]), assign.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), assign).unwrap();
+
+        let scope = query_relayer_scope(deps.as_ref(), mock_env(), "relayer2".to_string(), None, None).unwrap();
+        assert!(scope.order_ids.is_empty());
+
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer2", &[]), cancel.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NotAssignedRelayer {}));
+
+        // Once the exclusive window lapses any relayer may process the order
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(60);
+        execute(deps.as_mut(), later, mock_info("relayer2", &[]), cancel).unwrap();
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Cancelled, order.status);
+    }
+
+    #[test]
+    fn relayer_entries_expire_and_renew() {
+        let mut deps = setup();
+        let owner = mock_info("owner", &[]);
+        let add = ExecuteMsg::AddRelayer { relayer: "relayer2".to_string(), ttl: Some(100) };
+        execute(deps.as_mut(), mock_env(), owner.clone(), add.clone()).unwrap();
+
+        let active = query_is_authorized_relayer(deps.as_ref(), mock_env(), "relayer2".to_string()).unwrap();
+        assert!(active.is_authorized);
+        assert_eq!(Some(mock_env().block.time.seconds() + 100), active.expires_at);
+        execute(deps.as_mut(), mock_env(), mock_info("relayer2", &[]), deploy_src_msg(None)).unwrap();
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(100);
+        let err = execute(deps.as_mut(), expired.clone(), mock_info("relayer2", &[]), deploy_src_msg(None))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = query_is_authorized_relayer(deps.as_ref(), expired.clone(), "relayer2".to_string()).unwrap();
+        assert!(!res.is_authorized);
+
+        // Re-adding renews the entry from the current block time
+        execute(deps.as_mut(), expired.clone(), owner, add).unwrap();
+        execute(deps.as_mut(), expired, mock_info("relayer2", &[]), deploy_src_msg(None)).unwrap();
+    }
+
+    #[test]
+    fn features_reflect_build_and_config() {
+        let mut deps = setup();
+        let features = query_features(deps.as_ref()).unwrap();
+        assert_eq!(CONTRACT_NAME, features.contract);
+        assert_eq!(CONTRACT_VERSION, features.schema_version);
+        assert!(features.supports_partial_fill && features.supports_dutch_auction);
+        assert!(!features.supports_merkle_secrets);
+        assert!(!features.supports_price_improvement);
+        assert_eq!(vec!["sha256".to_string(), "keccak256".to_string()], features.hash_algos);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetPriceImprovementSplit { maker_bps: 5000, collector: Some("collector".to_string()) },
+        )
+        .unwrap();
+        assert!(query_features(deps.as_ref()).unwrap().supports_price_improvement);
+    }
+
+    #[test]
+    fn relayer_reward_paid_on_completion_and_refunded_on_cancel() {
+        let mut deps = setup();
+        let reward = Coin::new(50, "uatom");
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { relayer_reward, .. } = &mut msg {
+            *relayer_reward = Some(reward.clone());
+        }
+        // Anything but exactly the reward coin is rejected rather than stranded in the resolver
+        let wrong_funds = [
+            vec![],
+            cosmwasm_std::coins(60, "uatom"),
+            vec![reward.clone(), Coin::new(1, "uusdc")],
+        ];
+        for funds in wrong_funds {
+            let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &funds), msg.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::MissingRelayerReward {}));
+        }
+
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[reward.clone()]), msg.clone()).unwrap();
+        // The owner funds the second order's reward, and gets it back if no relayer earns it
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[reward.clone()]), msg).unwrap();
+
+        let swap = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+            proof: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "relayer".to_string(),
+                amount: vec![reward.clone()],
+            }),
+            res.messages[1].msg
+        );
+        assert_eq!(None, query_order(deps.as_ref(), "order_1".to_string()).unwrap().relayer_reward);
+
+        let cancel = ExecuteMsg::ProcessOrder {
+            order_id: "order_2".to_string(),
+            action: OrderAction::CancelOrder,
+            proof: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), cancel).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: vec![reward],
+            }),
+            res.messages[1].msg
+        );
+    }
+
+    #[test]
+    fn dispute_freezes_order_until_resolved() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+
+        let raise = ExecuteMsg::RaiseDispute {
+            order_id: "order_1".to_string(),
+            evidence_hash: "evidence".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("outsider", &[]), raise.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), raise.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), raise).unwrap_err();
+        assert!(matches!(err, ContractError::DisputeOpen {}));
+
+        let dispute = query_order(deps.as_ref(), "order_1".to_string()).unwrap().dispute.unwrap();
+        assert_eq!("evidence", dispute.evidence_hash);
+        assert_eq!(None, dispute.outcome);
+
+        let swap = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::OrderFrozen {}));
+
+        let resolve = ExecuteMsg::ResolveDispute {
+            order_id: "order_1".to_string(),
+            outcome: DisputeOutcome::Dismissed,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), resolve.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), resolve.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), resolve).unwrap_err();
+        assert!(matches!(err, ContractError::NoOpenDispute {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap).unwrap();
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(Some(DisputeOutcome::Dismissed), order.dispute.unwrap().outcome);
+        assert_eq!(OrderStatus::Completed, order.status);
+    }
+
+    #[test]
+    fn height_based_decay_ignores_block_time() {
+        let mut deps = setup();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { decay_basis, .. } = &mut msg {
+            *decay_basis = Some(DecayBasis::Height);
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        let auction = orders().load(&deps.storage, "order_1".to_string()).unwrap().dutch_auction.unwrap();
+        assert_eq!(mock_env().block.height, auction.start_height);
+
+        // Time passing without new blocks leaves the price untouched
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(50);
+        assert_eq!(Uint128::from(200u128), calculate_current_price(&auction, &env.block).unwrap());
+
+        env.block.height += 30;
+        assert_eq!(Uint128::from(170u128), calculate_current_price(&auction, &env.block).unwrap());
+        env.block.height += 1_000;
+        assert_eq!(Uint128::from(100u128), calculate_current_price(&auction, &env.block).unwrap());
+    }
+
+    #[test]
+    fn maker_cancels_with_secret() {
+        let mut deps = setup();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { secret_hash, .. } = &mut msg {
+            *secret_hash = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b".to_string();
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        let cancel = |secret: &str| ExecuteMsg::CancelWithSecret {
+            order_id: "order_1".to_string(),
+            secret: secret.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), cancel("secret")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), cancel("wrong")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSecret {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), cancel("secret")).unwrap();
+        assert_eq!(1, res.messages.len());
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Cancelled, order.status);
+    }
+
+    #[test]
+    fn deploy_rejects_oversized_chain_id() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { dst_chain_id, .. } = &mut msg {
+            *dst_chain_id = "x".repeat(escrow_common::validation::MAX_IDENTIFIER_LEN + 1);
+        }
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidIdentifier {}));
+
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { dst_asset, .. } = &mut msg {
+            *dst_asset = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string();
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let mut msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { src_chain_id, .. } = &mut msg {
+            *src_chain_id = "osmosis 1".to_string();
+        }
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidIdentifier {}));
+    }
+
+    #[test]
+    fn expired_partial_fill_relists_remainder() {
+        let mut deps = setup();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { allow_partial_fill, relist, .. } = &mut msg {
+            *allow_partial_fill = true;
+            *relist = Some(RelistParams {
+                secret_hash: "hash456".to_string(),
+                timelock: 5000,
+                dst_asset: "ETH".to_string(),
+                initial_price: Some(Uint128::from(150u128)),
+                price_decay_rate: Some(Uint128::from(1u128)),
+                minimum_price: Some(Uint128::from(90u128)),
+                label: "relist".to_string(),
+            });
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        let partial_fill = order.partial_fill.as_mut().unwrap();
+        partial_fill.filled_amount = Uint128::from(40u128);
+        partial_fill.remaining_amount = Uint128::from(60u128);
+        partial_fill.src_filled = Uint128::from(40u128);
+        partial_fill.src_remaining = Uint128::from(60u128);
+        partial_fill.dst_filled = Uint128::from(40u128);
+        partial_fill.dst_remaining = Uint128::from(60u128);
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&source_escrow_response(60)).unwrap()))
+        });
+
+        // The auction reaches its floor after 100 seconds
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("relayer", &[]),
+            ExecuteMsg::ExpireOrder { order_id: "order_1".to_string() },
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+
+        let old = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Expired, old.status);
+        assert_eq!(Some("order_2".to_string()), old.relisted_as);
+        let new = query_order(deps.as_ref(), "order_2".to_string()).unwrap();
+        assert_eq!(Uint128::from(60u128), new.dst_amount);
+        assert_eq!("hash456", new.secret_hash);
+        assert_eq!(Uint128::from(150u128), new.dutch_auction.unwrap().initial_price);
+    }
+
+    #[test]
+    fn health_reports_reachable_factory() {
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let factory_code = app.store_code(Box::new(
+            ContractWrapper::new(
+                escrow_factory::contract::execute,
+                escrow_factory::contract::instantiate,
+                escrow_factory::contract::query,
+            )
+            .with_reply(escrow_factory::contract::reply),
+        ));
+        let resolver_code = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+
+        let factory = app
+            .instantiate_contract(
+                factory_code,
+                Addr::unchecked("owner"),
+                &escrow_factory::msg::InstantiateMsg {
+                    owner: "owner".to_string(),
+                    source_escrow_code_id: 7,
+                    destination_escrow_code_id: 8,
+                },
+                &[],
+                "factory",
+                None,
+            )
+            .unwrap();
+        let instantiate_resolver = |app: &mut App, escrow_factory: String| {
+            app.instantiate_contract(
+                resolver_code,
+                Addr::unchecked("owner"),
+                &InstantiateMsg {
+                    owner: "owner".to_string(),
+                    escrow_factory,
+                    authorized_relayers: vec![],
+                    require_registered_taker: false,
+                    min_confirmations: 0,
+                    fee_bps: 0,
+                    fee_collector: None,
+                },
+                &[],
+                "resolver",
+                None,
+            )
+            .unwrap()
+        };
+
+        let resolver = instantiate_resolver(&mut app, factory.to_string());
+        let health: HealthResponse = app.wrap().query_wasm_smart(&resolver, &QueryMsg::Health {}).unwrap();
+        assert!(health.factory_reachable);
+        assert_eq!(Some(7), health.source_escrow_code_id);
+        assert_eq!(Some(8), health.destination_escrow_code_id);
+        assert_eq!(0, health.active_orders);
+
+        let orphaned = instantiate_resolver(&mut app, "missing".to_string());
+        let health: HealthResponse = app.wrap().query_wasm_smart(&orphaned, &QueryMsg::Health {}).unwrap();
+        assert!(!health.factory_reachable);
+        assert_eq!(None, health.source_escrow_code_id);
+    }
+
+    #[test]
+    fn sync_both_legs_cancels_after_out_of_band_cancel() {
+        use cw_multi_test::Executor;
+
+        let (mut app, _, resolver) = multi_test_suite();
+        let relayer = Addr::unchecked("relayer");
+        let mut src_msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { secret_hash, .. } = &mut src_msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+        }
+        app.execute_contract(relayer.clone(), resolver.clone(), &src_msg, &[]).unwrap();
+        let src_escrow = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        let mut dst_msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { secret_hash, src_escrow_address, src_order_id, .. } = &mut dst_msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+            *src_escrow_address = src_escrow.to_string();
+            *src_order_id = Some("order_1".to_string());
+        }
+        app.execute_contract(relayer, resolver.clone(), &dst_msg, &[]).unwrap();
+        let dst_escrow = multi_test_order(&app, &resolver, "order_2").escrow_address;
+
+        let sync = ExecuteMsg::SyncBothLegs { order_id: "order_2".to_string() };
+        let order_status =
+            |app: &cw_multi_test::App, order_id: &str| multi_test_order(app, &resolver, order_id).status;
+
+        // Both escrows still live: nothing to reconcile
+        app.execute_contract(Addr::unchecked("anyone"), resolver.clone(), &sync, &[]).unwrap();
+        assert_eq!(OrderStatus::Active, order_status(&app, "order_1"));
+
+        // The taker cancels the destination escrow directly, bypassing the resolver
+        app.execute_contract(
+            Addr::unchecked("taker"),
+            dst_escrow,
+            &destination_escrow::msg::ExecuteMsg::Cancel {},
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(Addr::unchecked("anyone"), resolver.clone(), &sync, &[]).unwrap();
+        assert_eq!(OrderStatus::Cancelled, order_status(&app, "order_1"));
+        assert_eq!(OrderStatus::Cancelled, order_status(&app, "order_2"));
+    }
+
+    #[test]
+    fn strict_mode_rejects_proofless_confirmation() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("dst")).unwrap();
+        let confirm = |proof: Option<String>| ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height: 95 },
+            proof,
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::SetProofRequired { required: true },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetProofRequired { required: true },
+        )
+        .unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), confirm(None)).unwrap_err();
+        assert!(matches!(err, ContractError::ProofRequired {}));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), confirm(Some(" ".to_string()))).unwrap_err();
+        assert!(matches!(err, ContractError::ProofRequired {}));
+        execute(deps.as_mut(), mock_env(), info, confirm(Some("proof".to_string()))).unwrap();
+    }
+
+    #[test]
+    fn trusted_mode_accepts_proofless_confirmation() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("dst")).unwrap();
+        assert!(!query_config(deps.as_ref()).unwrap().proof_required);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::ProcessOrder {
+                order_id: "order_1".to_string(),
+                action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height: 95 },
+                proof: None,
+            },
+        )
+        .unwrap();
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Matched, order.status);
+    }
+
+    #[test]
+    fn update_prices_for_chain_only_touches_that_chain() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_src_msg(None)).unwrap();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { dst_chain_id, label, .. } = &mut msg {
+            *dst_chain_id = "osmosis-1".to_string();
+            *label = "other".to_string();
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(50);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("keeper", &[]),
+            ExecuteMsg::UpdatePricesForChain { chain_id: "ethereum-1".to_string(), limit: None },
+        )
+        .unwrap();
+        assert!(res.attributes.iter().any(|attr| attr.key == "updated" && attr.value == "1"));
+
+        let price = |order_id: &str| {
+            query_order(deps.as_ref(), order_id.to_string()).unwrap().dutch_auction.unwrap().current_price
+        };
+        assert_eq!(Uint128::from(150u128), price("order_1"));
+        assert_eq!(Uint128::from(200u128), price("order_2"));
+    }
+
+    #[test]
+    fn orders_by_price_ranks_live_prices() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for (label, initial_price, decay_rate) in [("a", 200u128, 1u128), ("b", 300, 5), ("c", 250, 1)] {
+            let mut msg = deploy_src_msg(None);
+            if let ExecuteMsg::DeploySrc { initial_price: price, price_decay_rate, label: l, .. } = &mut msg {
+                *price = Some(Uint128::from(initial_price));
+                *price_decay_rate = Some(Uint128::from(decay_rate));
+                *l = label.to_string();
+            }
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        // After 50 seconds: order_1 at 150, order_2 floored at 100, order_3 at 200
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(50);
+        let ranked = |ascending: bool, limit: Option<u32>| {
+            query_orders_by_price(deps.as_ref(), env.clone(), ascending, limit)
+                .unwrap()
+                .orders
+                .into_iter()
+                .map(|priced| (priced.order.order_id, priced.current_price.u128()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![("order_2".to_string(), 100), ("order_1".to_string(), 150), ("order_3".to_string(), 200)],
+            ranked(true, None)
+        );
+        assert_eq!(vec![("order_3".to_string(), 200), ("order_1".to_string(), 150)], ranked(false, Some(2)));
+    }
+
+    #[test]
+    fn overpaid_surplus_refunded_after_price_drop() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        let owner = mock_info("owner", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetPriceImprovementSplit {
+                maker_bps: 5000,
+                collector: Some("collector".to_string()),
+            },
+        )
+        .unwrap();
+
+        mock_escrow_denom(&mut deps, "uusdc");
+        let withdraw = ExecuteMsg::PartialWithdraw {
+            escrow_address: "escrow".to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(10u128),
+        };
+        // Quoted at 170, a surplus of 700; by execution the price decayed to 160, a surplus of 600
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(40);
+        let payer = mock_info("relayer", &cosmwasm_std::coins(700, "uusdc"));
+        let err = execute(deps.as_mut(), env.clone(), payer.clone(), withdraw.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::SurplusMismatch {}));
+
+        execute(deps.as_mut(), mock_env(), owner, ExecuteMsg::SetRefundOverpayment { enabled: true }).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), payer, withdraw.clone()).unwrap();
+        assert_eq!(3, res.messages.len());
+        assert_eq!(
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: cosmwasm_std::coins(300, "uusdc"),
+            }),
+            res.messages[1].msg
+        );
+        assert_eq!(
+            CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "relayer".to_string(),
+                amount: cosmwasm_std::coins(100, "uusdc"),
+            }),
+            res.messages[2].msg
+        );
+
+        // Underpaying is still rejected
+        let err = execute(deps.as_mut(), env, mock_info("relayer", &cosmwasm_std::coins(500, "uusdc")), withdraw)
+            .unwrap_err();
+        assert!(matches!(err, ContractError::SurplusMismatch {}));
+    }
+
+    #[test]
+    fn collector_withdraws_accrued_fees() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let mut order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        order.escrow_address = cosmwasm_std::Addr::unchecked("escrow");
+        orders().save(deps.as_mut().storage, "order_1".to_string(), &order).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetPriceImprovementSplit { maker_bps: 5000, collector: Some("collector".to_string()) },
+        )
+        .unwrap();
+
+        mock_escrow_denom(&mut deps, "uusdc");
+        let withdraw = ExecuteMsg::PartialWithdraw {
+            escrow_address: "escrow".to_string(),
+            secret: "secret".to_string(),
+            amount: Uint128::from(10u128),
+        };
+        // Surpluses of (160 - 100) * 10 and (140 - 100) * 10, half of each to the collector
+        for (elapsed, surplus) in [(40u64, 600u128), (60, 400)] {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(elapsed);
+            let payer = mock_info("relayer", &cosmwasm_std::coins(surplus, "uusdc"));
+            execute(deps.as_mut(), env, payer, withdraw.clone()).unwrap();
+        }
+
+        // Two CW20 swaps at 35% of their 100 token amount pull the fee into the resolver
+        deps.querier.update_wasm(|_| {
+            let mut escrow = source_escrow_response(100);
+            escrow.cw20_contract = Some(cosmwasm_std::Addr::unchecked("token"));
+            SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+        });
+        let update = ExecuteMsg::UpdateFee { fee_bps: 3500, fee_collector: "fees".to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update).unwrap();
+        for order_id in ["order_2", "order_3"] {
+            execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+            let swap = ExecuteMsg::ProcessOrder {
+                order_id: order_id.to_string(),
+                action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+                proof: None,
+            };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap).unwrap();
+            assert_eq!(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "token".to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::TransferFrom {
+                        owner: "relayer".to_string(),
+                        recipient: mock_env().contract.address.to_string(),
+                        amount: Uint128::from(35u128),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                res.messages[1].msg
+            );
+        }
+
+        let collect = ExecuteMsg::WithdrawFees { to: "treasury".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), collect.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Each collector sweeps only its own ledger
+        let res = execute(deps.as_mut(), mock_env(), mock_info("collector", &[]), collect.clone()).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: cosmwasm_std::coins(500, "uusdc"),
+            })],
+            res.messages.into_iter().map(|msg| msg.msg).collect::<Vec<_>>()
+        );
+        let err = execute(deps.as_mut(), mock_env(), mock_info("collector", &[]), collect.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NoFeesAccrued {}));
+        assert_eq!(1, query_accrued_fees(deps.as_ref()).unwrap().cw20.len());
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("fees", &[]), collect.clone()).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: "treasury".to_string(),
+                    amount: Uint128::from(70u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })],
+            res.messages.into_iter().map(|msg| msg.msg).collect::<Vec<_>>()
+        );
+
+        let fees = query_accrued_fees(deps.as_ref()).unwrap();
+        assert!(fees.native.is_empty() && fees.cw20.is_empty() && fees.price_improvement.is_empty());
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), collect).unwrap_err();
+        assert!(matches!(err, ContractError::NoFeesAccrued {}));
+    }
+
+    #[test]
+    fn confirm_source_waits_for_funded_destination() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg("dst")).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetRequireFundedDestination { required: true },
+        )
+        .unwrap();
+        let confirm = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height: 95 },
+            proof: None,
+        };
+
+        deps.querier.update_wasm(|_| {
+            let mut escrow = destination_escrow_response(1000);
+            escrow.deposited_amount = Uint128::from(40u128);
+            SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), confirm.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::DestinationNotFunded {}));
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Active, order.status);
+
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&destination_escrow_response(1000)).unwrap()))
+        });
+        execute(deps.as_mut(), mock_env(), info, confirm).unwrap();
+        let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+        assert_eq!(OrderStatus::Matched, order.status);
+    }
+
+    #[test]
+    fn deploys_fall_back_to_default_hash_algo() {
+        let mut deps = setup();
+        let relayer = mock_info("relayer", &[]);
+        execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_src_msg(None)).unwrap();
+        assert_eq!(HashAlgo::Sha256, query_order(deps.as_ref(), "order_1".to_string()).unwrap().hash_algo);
+
+        let set_default = ExecuteMsg::SetDefaultHashAlgo { hash_algo: HashAlgo::Keccak256 };
+        let err = execute(deps.as_mut(), mock_env(), relayer.clone(), set_default.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), set_default).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), relayer.clone(), deploy_dst_msg("dst")).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr("hash_algo", "keccak256")));
+        assert_eq!(HashAlgo::Keccak256, query_order(deps.as_ref(), "order_2".to_string()).unwrap().hash_algo);
+
+        // A per-order algorithm overrides the default
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { hash_algo, .. } = &mut msg {
+            *hash_algo = Some(HashAlgo::Sha256);
+        }
+        execute(deps.as_mut(), mock_env(), relayer, msg).unwrap();
+        assert_eq!(HashAlgo::Sha256, query_order(deps.as_ref(), "order_3".to_string()).unwrap().hash_algo);
+    }
+
+    #[test]
+    fn timelock_schedule_reports_absolute_stages() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        deps.querier.update_wasm(|_| {
+            let mut escrow = source_escrow_response(100);
+            escrow.created_at = 5000;
+            escrow.timelock = 5900;
+            escrow.timelocks = Timelocks {
+                finality: 60,
+                exclusive_withdrawal: 300,
+                public_withdrawal: 600,
+                cancellation: 900,
+            };
+            SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+        });
+
+        let schedule = query_timelock_schedule(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!(5060, schedule.finality_at);
+        assert_eq!(5300, schedule.exclusive_until);
+        assert_eq!(5600, schedule.public_until);
+        assert_eq!(5900, schedule.cancel_at);
+    }
+
+    #[test]
+    fn dual_fill_accounting_tracks_both_assets() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        let mut src_msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { allow_partial_fill, src_amount, .. } = &mut src_msg {
+            *allow_partial_fill = true;
+            *src_amount = Some(Uint128::from(200u128));
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), src_msg).unwrap();
+        let mut dst_msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { expected_amount, src_order_id, .. } = &mut dst_msg {
+            *expected_amount = Uint128::from(300u128);
+            *src_order_id = Some("order_1".to_string());
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), dst_msg).unwrap();
+
+        let fill = |amount: u128| ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::PartialExecuteSwap {
+                secret: "secret".to_string(),
+                amount: Uint128::from(amount),
+            },
+            proof: None,
+        };
+        // Fills are sized in the source asset, 200 of which trade for 300 of the destination asset
+        for (amount, src_filled, dst_filled) in [(80u128, 80u128, 120u128), (120, 200, 300)] {
+            execute(deps.as_mut(), mock_env(), info.clone(), fill(amount)).unwrap();
+
+            let src_fill = query_order(deps.as_ref(), "order_1".to_string()).unwrap().partial_fill.unwrap();
+            let dst_fill = query_order(deps.as_ref(), "order_2".to_string()).unwrap().partial_fill.unwrap();
+            assert_eq!(Uint128::from(src_filled), src_fill.src_filled);
+            assert_eq!(Uint128::from(200 - src_filled), src_fill.src_remaining);
+            assert_eq!(Uint128::from(dst_filled), src_fill.dst_filled);
+            assert_eq!(Uint128::from(300 - dst_filled), src_fill.dst_remaining);
+            // Each leg's own figures match the shared accounting in its asset
+            assert_eq!(src_fill.filled_amount, src_fill.src_filled);
+            assert_eq!(src_fill.remaining_amount, src_fill.src_remaining);
+            assert_eq!(dst_fill.filled_amount, src_fill.dst_filled);
+            assert_eq!(dst_fill.remaining_amount, src_fill.dst_remaining);
+            assert_eq!(
+                (src_fill.src_filled, src_fill.src_remaining, src_fill.dst_filled, src_fill.dst_remaining),
+                (dst_fill.src_filled, dst_fill.src_remaining, dst_fill.dst_filled, dst_fill.dst_remaining)
+            );
+        }
+    }
+
+    #[test]
+    fn export_active_orders_pages_through_every_order() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for i in 0..150 {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg(&format!("dst{}", i))).unwrap();
+        }
+
+        let mut exported = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let page = query_export_active_orders(deps.as_ref(), cursor, Some(40)).unwrap();
+            pages += 1;
+            exported.extend(page.orders);
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(4, pages);
+
+        let full = query_active_orders(deps.as_ref(), None, Some(100)).unwrap();
+        assert_eq!(full.orders[0].order_id, exported[0].order_id);
+        assert_eq!(full.orders[0].escrow_address, exported[0].escrow_address);
+
+        let mut ids: Vec<_> = exported.iter().map(|order| order.order_id.clone()).collect();
+        ids.sort();
+        ids.dedup();
+        let mut expected: Vec<_> = (1..=150).map(|i| format!("order_{}", i)).collect();
+        expected.sort();
+        assert_eq!(expected, ids);
+        assert!(exported.iter().all(|order| order.status == OrderStatus::Active
+            && order.maker == Addr::unchecked("maker")));
+    }
+
+    #[test]
+    fn export_active_orders_skips_terminal_orders() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        for i in 0..7 {
+            execute(deps.as_mut(), mock_env(), info.clone(), deploy_dst_msg(&format!("dst{}", i))).unwrap();
+        }
+        let statuses = [
+            OrderStatus::Completed,
+            OrderStatus::Matched,
+            OrderStatus::Cancelled,
+            OrderStatus::Expired,
+            OrderStatus::Matched,
+        ];
+        for (i, status) in statuses.into_iter().enumerate() {
+            let order_id = format!("order_{}", i + 2);
+            let mut order = orders().load(&deps.storage, order_id.clone()).unwrap();
+            order.status = status;
+            orders().save(deps.as_mut().storage, order_id, &order).unwrap();
+        }
+
+        // Pages of two interleave active and matched orders without surfacing terminal ones
+        let mut exported = vec![];
+        let mut cursor = None;
+        loop {
+            let page = query_export_active_orders(deps.as_ref(), cursor, Some(2)).unwrap();
+            assert!(page.orders.len() <= 2);
+            exported.extend(page.orders.into_iter().map(|order| (order.order_id, order.status)));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            vec![
+                ("order_1".to_string(), OrderStatus::Active),
+                ("order_3".to_string(), OrderStatus::Matched),
+                ("order_6".to_string(), OrderStatus::Matched),
+                ("order_7".to_string(), OrderStatus::Active),
+            ],
+            exported
+        );
+    }
+
+    #[test]
+    fn events_since_replays_transitions_from_height() {
+        let mut deps = setup();
+        let info = mock_info("relayer", &[]);
+        let at_height = |offset: u64| {
+            let mut env = mock_env();
+            env.block.height += offset;
+            env
+        };
+        let start = mock_env().block.height;
+
+        execute(deps.as_mut(), at_height(0), info.clone(), deploy_dst_msg("a")).unwrap();
+        execute(deps.as_mut(), at_height(1), info.clone(), deploy_dst_msg("b")).unwrap();
+        let mut order = orders().load(&deps.storage, "order_2".to_string()).unwrap();
+        order.escrow_address = Addr::unchecked("escrow_b");
+        orders().save(deps.as_mut().storage, "order_2".to_string(), &order).unwrap();
+        let cancel = ExecuteMsg::Cancel { escrow_address: "escrow_b".to_string() };
+        execute(deps.as_mut(), at_height(2), info, cancel).unwrap();
+
+        let all = query_events_since(deps.as_ref(), start, None, None).unwrap();
+        assert_eq!(3, all.events.len());
+
+        let replay = query_events_since(deps.as_ref(), start + 1, None, None).unwrap();
+        let replayed: Vec<_> = replay
+            .events
+            .iter()
+            .map(|event| (event.height, event.order_id.as_str(), event.status.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (start + 1, "order_2", OrderStatus::Active),
+                (start + 2, "order_2", OrderStatus::Cancelled),
+            ],
+            replayed
+        );
+
+        // Resuming after the last seen event picks up where the previous page stopped
+        let page = query_events_since(deps.as_ref(), start + 1, None, Some(1)).unwrap();
+        let last = page.events.last().unwrap();
+        let rest = query_events_since(deps.as_ref(), last.height, Some(last.seq), None).unwrap();
+        assert_eq!(1, rest.events.len());
+        assert_eq!(OrderStatus::Cancelled, rest.events[0].status);
+    }
+
+    #[test]
+    fn chain_relayers_only_serve_their_chain() {
+        let mut deps = setup();
+        let owner = mock_info("owner", &[]);
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_dst_msg("dst")).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetChainRelayers {
+                chain_id: "ethereum-1".to_string(),
+                relayers: vec!["eth_relayer".to_string()],
+            },
+        )
+        .unwrap();
+
+        // order_1 bridges osmosis-1, which the ethereum relayer does not serve
+        let confirm = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ConfirmSource { src_tx_hash: "tx".to_string(), block_height: 1 },
+            proof: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("eth_relayer", &[]), confirm.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRelayer {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("eth_relayer", &[]), deploy_dst_msg("dst2")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            owner.clone(),
+            ExecuteMsg::SetChainRelayers {
+                chain_id: "osmosis-1".to_string(),
+                relayers: vec!["osmo_relayer".to_string()],
+            },
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("osmo_relayer", &[]), confirm).unwrap();
+
+        // Global relayers keep serving every chain
+        execute(deps.as_mut(), mock_env(), owner, ExecuteMsg::AddRelayer { relayer: "eth_relayer".to_string(), ttl: None })
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("eth_relayer", &[]), deploy_dst_msg("dst2")).unwrap();
+    }
+
+    #[test]
+    fn execute_swap_respects_min_settle_price() {
+        let mut deps = setup();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { min_settle_price, .. } = &mut msg {
+            *min_settle_price = Some(Uint128::from(150u128));
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+
+        let swap = |order_id: &str| ExecuteMsg::ProcessOrder {
+            order_id: order_id.to_string(),
+            action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+            proof: None,
+        };
+        // The price decays from 200 by 1 per second
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let err = execute(deps.as_mut(), env, mock_info("relayer", &[]), swap("order_1")).unwrap_err();
+        assert!(matches!(err, ContractError::MinimumPriceReached {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(10);
+        execute(deps.as_mut(), env, mock_info("relayer", &[]), swap("order_2")).unwrap();
+        let order = orders().load(&deps.storage, "order_2".to_string()).unwrap();
+        assert_eq!(OrderStatus::Completed, order.status);
+    }
+
+    #[test]
+    fn execute_swap_charges_protocol_fee() {
+        let swap = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::ExecuteSwap { secret: "secret".to_string() },
+            proof: None,
+        };
+        // The order amount is 100; fees round down to whole units
+        for (fee_bps, fee) in [(0u16, 0u128), (30, 0), (250, 2)] {
+            let mut deps = setup();
+            deps.querier.update_wasm(|_| {
+                let mut escrow = source_escrow_response(100);
+                escrow.deposited_denom = Some("uatom".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&escrow).unwrap()))
+            });
+            let update = ExecuteMsg::UpdateFee { fee_bps, fee_collector: "collector".to_string() };
+            execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update).unwrap();
+            execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+
+            let funds = if fee > 0 { cosmwasm_std::coins(fee, "uatom") } else { vec![] };
+            if fee > 0 {
+                let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap.clone()).unwrap_err();
+                assert!(matches!(err, ContractError::FeeMismatch {}));
+            }
+            let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &funds), swap.clone()).unwrap();
+            // The fee stays in the resolver for the collector to withdraw
+            assert_eq!(1, res.messages.len());
+            let order = orders().load(&deps.storage, "order_1".to_string()).unwrap();
+            let withdraw = ExecuteMsg::WithdrawFees { to: "collector".to_string() };
+            if fee > 0 {
+                assert_eq!(Some(cosmwasm_std::coin(fee, "uatom")), order.fee_paid);
+                assert_eq!(vec![cosmwasm_std::coin(fee, "uatom")], query_accrued_fees(deps.as_ref()).unwrap().native);
+                let res = execute(deps.as_mut(), mock_env(), mock_info("collector", &[]), withdraw).unwrap();
+                assert_eq!(
+                    CosmosMsg::Bank(BankMsg::Send {
+                        to_address: "collector".to_string(),
+                        amount: cosmwasm_std::coins(fee, "uatom"),
+                    }),
+                    res.messages[0].msg
+                );
+            } else {
+                assert_eq!(None, order.fee_paid);
+                let err = execute(deps.as_mut(), mock_env(), mock_info("collector", &[]), withdraw).unwrap_err();
+                assert!(matches!(err, ContractError::NoFeesAccrued {}));
+            }
+        }
+    }
+
+    #[test]
+    fn update_secret_hash_rotates_unfunded_order() {
+        let mut deps = setup();
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), deploy_src_msg(None)).unwrap();
+        let rotate = ExecuteMsg::UpdateSecretHash {
+            order_id: "order_1".to_string(),
+            new_secret_hash: "hash456".to_string(),
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), rotate.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&source_escrow_response(100)).unwrap()))
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), rotate.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::OrderFunded {}));
+
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(to_binary(&source_escrow_response(0)).unwrap()))
+        });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), rotate).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                match cosmwasm_std::from_binary(msg).unwrap() {
+                    source_escrow::msg::ExecuteMsg::UpdateSecretHash { new_secret_hash } => {
+                        assert_eq!("hash456", new_secret_hash)
+                    }
+                    _ => panic!("unexpected escrow message"),
+                }
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        let order = query_order(deps.as_ref(), "order_1".to_string()).unwrap();
+        assert_eq!("hash456", order.secret_hash);
+    }
+
+    #[test]
+    fn cancel_both_refunds_unfilled_remainder_after_partial_fill() {
+        let mut deps = setup_linked_orders(1000, true);
+        let swap = ExecuteMsg::ProcessOrder {
+            order_id: "order_1".to_string(),
+            action: OrderAction::PartialExecuteSwap {
+                secret: "secret".to_string(),
+                amount: Uint128::from(60u128),
+            },
+            proof: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), swap).unwrap();
+
+        let msg = ExecuteMsg::CancelBoth { order_id: "order_1".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("refunds", "order_1:40,order_2:40")));
+
+        let dst_fill = query_order(deps.as_ref(), "order_2".to_string()).unwrap().partial_fill.unwrap();
+        assert_eq!(Uint128::from(60u128), dst_fill.filled_amount);
+    }
+
+    #[test]
+    fn migrate_order_escrow_moves_order_to_upgraded_code() {
+        use cw_multi_test::Executor;
+
+        let (mut app, factory, resolver) = multi_test_suite();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { secret_hash, .. } = &mut msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+        }
+        app.execute_contract(Addr::unchecked("relayer"), resolver.clone(), &msg, &[]).unwrap();
+        let old_escrow = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        assert_ne!("pending", old_escrow.as_str());
+
+        // Ship a new source escrow code and point the factory at it
+        let upgraded_code = app.store_code(source_escrow_contract());
+        let update = escrow_factory::msg::ExecuteMsg::UpdateCodeIds {
+            source_escrow_code_id: Some(upgraded_code),
+            destination_escrow_code_id: None,
+        };
+        app.execute_contract(Addr::unchecked("owner"), factory, &update, &[]).unwrap();
+
+        let migrate = ExecuteMsg::MigrateOrderEscrow { order_id: "order_1".to_string() };
+        let err = app.execute_contract(Addr::unchecked("relayer"), resolver.clone(), &migrate, &[]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+        app.execute_contract(Addr::unchecked("owner"), resolver.clone(), &migrate, &[]).unwrap();
+
+        let new_escrow = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        assert_ne!(old_escrow, new_escrow);
+        assert_eq!(upgraded_code, app.wrap().query_wasm_contract_info(&new_escrow).unwrap().code_id);
+        let escrow = |app: &cw_multi_test::App, address: &Addr| -> source_escrow::msg::EscrowResponse {
+            app.wrap().query_wasm_smart(address, &source_escrow::msg::QueryMsg::Escrow {}).unwrap()
+        };
+        assert_eq!(source_escrow::msg::EscrowStatus::Cancelled, escrow(&app, &old_escrow).status);
+        assert_eq!(source_escrow::msg::EscrowStatus::Active, escrow(&app, &new_escrow).status);
+        assert_eq!(escrow(&app, &old_escrow).secret_hash, escrow(&app, &new_escrow).secret_hash);
+
+        // Funds on the replacement pin the order to it
+        app.execute_contract(
+            Addr::unchecked("maker"),
+            new_escrow,
+            &source_escrow::msg::ExecuteMsg::Deposit {},
+            &cosmwasm_std::coins(100, "uatom"),
+        )
+        .unwrap();
+        let err = app.execute_contract(Addr::unchecked("owner"), resolver, &migrate, &[]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::OrderFunded {})));
+    }
+
+    #[test]
+    fn rotated_secret_hash_gates_the_withdrawal() {
+        use cw_multi_test::Executor;
+
+        let (mut app, _, resolver) = multi_test_suite();
+        let mut msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { secret_hash, timelock, .. } = &mut msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+            *timelock = mock_env().block.time.seconds() + 1000;
+        }
+        app.execute_contract(Addr::unchecked("relayer"), resolver.clone(), &msg, &[]).unwrap();
+
+        // sha256("rotated")
+        let rotated = "f42546d5ecdd452509808b2d6d0413b5a738c70a793b99ccf8ed6f423aac83d3";
+        let update = ExecuteMsg::UpdateSecretHash {
+            order_id: "order_1".to_string(),
+            new_secret_hash: rotated.to_string(),
+        };
+        let err = app.execute_contract(Addr::unchecked("relayer"), resolver.clone(), &update, &[]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+        app.execute_contract(Addr::unchecked("maker"), resolver.clone(), &update, &[]).unwrap();
+
+        let order = multi_test_order(&app, &resolver, "order_1");
+        assert_eq!(rotated, order.secret_hash);
+        let escrow: source_escrow::msg::EscrowResponse = app
+            .wrap()
+            .query_wasm_smart(&order.escrow_address, &source_escrow::msg::QueryMsg::Escrow {})
+            .unwrap();
+        assert_eq!(rotated, escrow.secret_hash);
+
+        app.execute_contract(
+            Addr::unchecked("maker"),
+            order.escrow_address.clone(),
+            &source_escrow::msg::ExecuteMsg::Deposit {},
+            &cosmwasm_std::coins(100, "uatom"),
+        )
+        .unwrap();
+        let withdraw = |secret: &str| source_escrow::msg::ExecuteMsg::Withdraw { secret: secret.to_string() };
+        let err = app
+            .execute_contract(Addr::unchecked("taker"), order.escrow_address.clone(), &withdraw("secret"), &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<source_escrow::ContractError>(),
+            Some(source_escrow::ContractError::InvalidSecret {})
+        ));
+        app.execute_contract(Addr::unchecked("taker"), order.escrow_address, &withdraw("rotated"), &[]).unwrap();
+        assert_eq!(
+            Uint128::from(1_100u128),
+            app.wrap().query_balance("taker", "uatom").unwrap().amount
+        );
+    }
+
+    #[test]
+    fn cancel_both_unwinds_funded_legs() {
+        use cw_multi_test::Executor;
+
+        let (mut app, _, resolver) = multi_test_suite();
+        let relayer = Addr::unchecked("relayer");
+        let mut src_msg = deploy_src_msg(None);
+        if let ExecuteMsg::DeploySrc { secret_hash, timelock, .. } = &mut src_msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+            *timelock = mock_env().block.time.seconds() + 1000;
+        }
+        app.execute_contract(relayer.clone(), resolver.clone(), &src_msg, &[]).unwrap();
+        let src_escrow = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        let mut dst_msg = deploy_dst_msg("dst");
+        if let ExecuteMsg::DeployDst { secret_hash, src_escrow_address, src_order_id, .. } = &mut dst_msg {
+            *secret_hash = LIVE_SECRET_HASH.to_string();
+            *src_escrow_address = src_escrow.to_string();
+            *src_order_id = Some("order_1".to_string());
+        }
+        app.execute_contract(relayer.clone(), resolver.clone(), &dst_msg, &[]).unwrap();
+        let dst_escrow = multi_test_order(&app, &resolver, "order_2").escrow_address;
+
+        let funds = cosmwasm_std::coins(100, "uatom");
+        app.execute_contract(
+            Addr::unchecked("maker"),
+            src_escrow.clone(),
+            &source_escrow::msg::ExecuteMsg::Deposit {},
+            &funds,
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked("taker"),
+            dst_escrow.clone(),
+            &destination_escrow::msg::ExecuteMsg::Deposit {},
+            &funds,
+        )
+        .unwrap();
+
+        // The destination timelock has passed; the source is still locked
+        let cancel = ExecuteMsg::CancelBoth { order_id: "order_1".to_string() };
+        let res = app.execute_contract(relayer.clone(), resolver.clone(), &cancel, &[]).unwrap();
+        assert!(res.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("pending", "order_1")));
+        assert_eq!(OrderStatus::Cancelled, multi_test_order(&app, &resolver, "order_2").status);
+        assert_eq!(OrderStatus::Active, multi_test_order(&app, &resolver, "order_1").status);
+        assert_eq!(Uint128::from(1_000u128), app.wrap().query_balance("taker", "uatom").unwrap().amount);
+
+        app.update_block(|block| block.time = block.time.plus_seconds(1000));
+        app.execute_contract(relayer, resolver.clone(), &cancel, &[]).unwrap();
+        assert_eq!(OrderStatus::Cancelled, multi_test_order(&app, &resolver, "order_1").status);
+        assert_eq!(Uint128::from(1_000u128), app.wrap().query_balance("maker", "uatom").unwrap().amount);
+        let dst: destination_escrow::msg::EscrowResponse = app
+            .wrap()
+            .query_wasm_smart(&dst_escrow, &destination_escrow::msg::QueryMsg::Escrow {})
+            .unwrap();
+        assert_eq!(destination_escrow::msg::EscrowStatus::Cancelled, dst.status);
+    }
+
+    #[test]
+    fn expire_order_dispositions_reach_the_escrow() {
+        use cw_multi_test::Executor;
+
+        let (mut app, _, resolver) = multi_test_suite();
+        let relayer = Addr::unchecked("relayer");
+        let expire = ExecuteMsg::ExpireOrder { order_id: "order_1".to_string() };
+        let deploy = |app: &cw_multi_test::App, on_expiry: ExpiryAction| {
+            let mut msg = deploy_src_msg(Some(on_expiry));
+            if let ExecuteMsg::DeploySrc { secret_hash, timelock, allow_partial_fill, .. } = &mut msg {
+                *secret_hash = LIVE_SECRET_HASH.to_string();
+                *timelock = app.block_info().time.seconds() + 1000;
+                *allow_partial_fill = true;
+            }
+            msg
+        };
+        let fund = |app: &mut cw_multi_test::App, escrow: &Addr| {
+            app.execute_contract(
+                Addr::unchecked("maker"),
+                escrow.clone(),
+                &source_escrow::msg::ExecuteMsg::Deposit {},
+                &cosmwasm_std::coins(100, "uatom"),
+            )
+            .unwrap();
+        };
+
+        // CancelRefund waits out the funded escrow's timelock, then refunds the maker
+        let msg = deploy(&app, ExpiryAction::CancelRefund);
+        app.execute_contract(relayer.clone(), resolver.clone(), &msg, &[]).unwrap();
+        let escrow = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        fund(&mut app, &escrow);
+        app.update_block(|block| block.time = block.time.plus_seconds(150));
+        let err = app.execute_contract(relayer.clone(), resolver.clone(), &expire, &[]).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::OrderNotExpired {})));
+        app.update_block(|block| block.time = block.time.plus_seconds(850));
+        app.execute_contract(relayer.clone(), resolver.clone(), &expire, &[]).unwrap();
+        assert_eq!(OrderStatus::Expired, multi_test_order(&app, &resolver, "order_1").status);
+        assert_eq!(Uint128::from(1_000u128), app.wrap().query_balance("maker", "uatom").unwrap().amount);
+
+        // RestAtMinimum reopens fills the escrow's floor check had stopped
+        let msg = deploy(&app, ExpiryAction::RestAtMinimum);
+        app.execute_contract(relayer.clone(), resolver.clone(), &msg, &[]).unwrap();
+        let escrow = multi_test_order(&app, &resolver, "order_2").escrow_address;
+        fund(&mut app, &escrow);
+        app.update_block(|block| block.time = block.time.plus_seconds(150));
+        let fill = source_escrow::msg::ExecuteMsg::PartialWithdraw {
+            secret: "secret".to_string(),
+            amount: Uint128::from(40u128),
+        };
+        let err = app.execute_contract(Addr::unchecked("taker"), escrow.clone(), &fill, &[]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<source_escrow::ContractError>(),
+            Some(source_escrow::ContractError::MinimumPriceReached {})
+        ));
+        let expire = ExecuteMsg::ExpireOrder { order_id: "order_2".to_string() };
+        app.execute_contract(relayer, resolver.clone(), &expire, &[]).unwrap();
+        app.execute_contract(Addr::unchecked("taker"), escrow.clone(), &fill, &[]).unwrap();
+        let price: source_escrow::msg::PriceResponse =
+            app.wrap().query_wasm_smart(&escrow, &source_escrow::msg::QueryMsg::CurrentPrice {}).unwrap();
+        assert_eq!(Uint128::from(100u128), price.current_price);
+    }
+
+    #[test]
+    fn deploy_src_funds_escrow_atomically() {
+        use cw_multi_test::Executor;
+
+        let (mut app, _, resolver) = multi_test_suite();
+        let relayer = Addr::unchecked("relayer");
+        app.send_tokens(Addr::unchecked("maker"), relayer.clone(), &cosmwasm_std::coins(300, "uatom")).unwrap();
+        let deploy = |amount: u128| {
+            let mut msg = deploy_src_msg(None);
+            if let ExecuteMsg::DeploySrc { secret_hash, principal, .. } = &mut msg {
+                *secret_hash = LIVE_SECRET_HASH.to_string();
+                *principal = Some(cosmwasm_std::coin(amount, "uatom"));
+            }
+            msg
+        };
+
+        let err = app
+            .execute_contract(relayer.clone(), resolver.clone(), &deploy(100), &cosmwasm_std::coins(90, "uatom"))
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::DeployFundsMismatch {})));
+
+        app.execute_contract(relayer.clone(), resolver.clone(), &deploy(100), &cosmwasm_std::coins(100, "uatom"))
+            .unwrap();
+        let escrow_address = multi_test_order(&app, &resolver, "order_1").escrow_address;
+        let escrow: source_escrow::msg::EscrowResponse = app
+            .wrap()
+            .query_wasm_smart(&escrow_address, &source_escrow::msg::QueryMsg::Escrow {})
+            .unwrap();
+        assert_eq!(Uint128::from(100u128), escrow.deposited_amount);
+        assert_eq!(Some("uatom".to_string()), escrow.deposited_denom);
+        assert_eq!(Uint128::from(100u128), app.wrap().query_balance(&escrow_address, "uatom").unwrap().amount);
+
+        // The escrow refuses more than the order's amount, which unwinds the creation too
+        app.execute_contract(relayer.clone(), resolver.clone(), &deploy(150), &cosmwasm_std::coins(150, "uatom"))
+            .unwrap_err();
+        let missing: StdResult<OrderResponse> = app
+            .wrap()
+            .query_wasm_smart(&resolver, &QueryMsg::Order { order_id: "order_2".to_string() });
+        assert!(missing.is_err());
+        assert_eq!(Uint128::from(200u128), app.wrap().query_balance(&relayer, "uatom").unwrap().amount);
+        assert_eq!(Uint128::zero(), app.wrap().query_balance(&resolver, "uatom").unwrap().amount);
+    }
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use escrow_common::validation::ValidationError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -26,5 +27,113 @@ pub enum ContractError {
 
     #[error("Invalid relayer")]
     InvalidRelayer {},
+
+    #[error("Order has not expired")]
+    OrderNotExpired {},
+
+    #[error("Batch too large")]
+    BatchTooLarge {},
+
+    #[error("Order escrow already funded")]
+    OrderFunded {},
+
+    #[error("Order has no linked counterparty")]
+    CounterpartyNotLinked {},
+
+    #[error("Taker not registered")]
+    TakerNotRegistered {},
+
+    #[error("Source block does not have enough confirmations")]
+    InsufficientConfirmations {},
+
+    #[error("Invalid fee configuration")]
+    InvalidFeeConfig {},
+
+    #[error("Resolver is paused")]
+    Paused {},
+
+    #[error("Minimum fill amount exceeds the order amount")]
+    MinimumFillTooLarge {},
+
+    #[error("Order is frozen")]
+    OrderFrozen {},
+
+    #[error("Order is not accepting new fills")]
+    OrderNotAcceptingFills {},
+
+    #[error("Cancel is pending until its grace window ends")]
+    CancelPending {},
+
+    #[error("No pending cancel for order")]
+    NoPendingCancel {},
+
+    #[error("Live auction price is below the maker's minimum settle price")]
+    MinimumPriceReached {},
+
+    #[error("Attached funds do not match the protocol fee")]
+    FeeMismatch {},
+
+    #[error("Attached funds do not match the price improvement surplus")]
+    SurplusMismatch {},
+
+    #[error("Invalid maker signature")]
+    InvalidSignature {},
+
+    #[error("Maker has no registered public key")]
+    MakerPubkeyNotRegistered {},
+
+    #[error("Signed order already deployed")]
+    SignedOrderUsed {},
+
+    #[error("Order is assigned to another relayer")]
+    NotAssignedRelayer {},
+
+    #[error("Exactly the relayer reward must be attached to the deploy")]
+    MissingRelayerReward {},
+
+    #[error("Attached funds must be exactly the relayer reward and the principal")]
+    DeployFundsMismatch {},
+
+    #[error("Escrow did not receive the principal deposited at deploy")]
+    EscrowFundingMismatch {},
+
+    #[error("Order already has an open dispute")]
+    DisputeOpen {},
+
+    #[error("Order has no open dispute")]
+    NoOpenDispute {},
+
+    #[error("Invalid secret")]
+    InvalidSecret {},
+
+    #[error("Invalid chain or asset identifier")]
+    InvalidIdentifier {},
+
+    #[error("Source confirmation requires a proof")]
+    ProofRequired {},
+
+    #[error("Invalid timelock")]
+    InvalidTimelock {},
+
+    #[error("Destination escrow is not fully funded")]
+    DestinationNotFunded {},
+
+    #[error("Source escrow is not fully funded")]
+    SourceNotFunded {},
+
+    #[error("No fees accrued")]
+    NoFeesAccrued {},
 }
 
+impl From<ValidationError> for ContractError {
+    fn from(err: ValidationError) -> Self {
+        match err {
+            ValidationError::InvalidTimelock {} => ContractError::InvalidTimelock {},
+            ValidationError::MinimumFillTooLarge {} => ContractError::MinimumFillTooLarge {},
+            ValidationError::InvalidIdentifier {} => ContractError::InvalidIdentifier {},
+            ValidationError::InvalidAuctionParams {}
+            | ValidationError::InvalidAmount {}
+            | ValidationError::InvalidSecretHash {} => ContractError::InvalidOrderParameters {},
+        }
+    }
+}
@@ -1,11 +1,23 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw20::Cw20Coin;
+use escrow_common::hash::HashAlgo;
+use escrow_factory::msg::EscrowType;
+use source_escrow::msg::{IbcForwardParams, SafetyDepositSwap};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: String,
     pub escrow_factory: String,
     pub authorized_relayers: Vec<String>,
+    /// Reject deploys whose taker has not been registered by the owner
+    pub require_registered_taker: bool,
+    /// Blocks a source confirmation must sit below the tracked source-chain head; 0 disables
+    pub min_confirmations: u64,
+    /// Protocol fee on executed swaps, in basis points of the order amount
+    pub fee_bps: u16,
+    /// Account that withdraws accrued protocol fees; the owner when unset
+    pub fee_collector: Option<String>,
 }
 
 #[cw_serde]
@@ -19,15 +31,43 @@ pub enum ExecuteMsg {
         dst_chain_id: String,
         dst_asset: String,
         dst_amount: Uint128,
+        // Exact principal the maker must deposit into the source escrow
+        src_amount: Option<Uint128>,
         // Dutch auction parameters
         initial_price: Option<Uint128>,
         price_decay_rate: Option<Uint128>,
         minimum_price: Option<Uint128>,
+        // Whether the price decays per second or per block; per second when unset
+        decay_basis: Option<DecayBasis>,
+        // Lowest live auction price the maker accepts a relayer settling at
+        min_settle_price: Option<Uint128>,
+        // Decimals of the auction prices, for display; 6 when unset
+        price_decimals: Option<u8>,
         // Partial fill parameters
         allow_partial_fill: bool,
         minimum_fill_amount: Option<Uint128>,
+        dust_threshold: Option<Uint128>,
         // LOP integration
         lop_order_data: Option<String>,
+        // Disposition once the auction bottoms out unfilled
+        on_expiry: Option<ExpiryAction>,
+        // Payout address for the maker's refund on cancel
+        src_refund_addr: Option<String>,
+        // Safety deposit the source escrow requires alongside the principal
+        safety_deposit: Option<Coin>,
+        safety_deposit_swap: Option<SafetyDepositSwap>,
+        // Forward the taker's proceeds over IBC on withdrawal
+        ibc_forward: Option<IbcForwardParams>,
+        // CW20 contracts the escrow accepts for deposits
+        allowed_cw20: Option<Vec<String>>,
+        // Reward escrowed with the deploy and paid to the relayer that completes the order
+        relayer_reward: Option<Coin>,
+        // Principal attached to the deploy and deposited into the new escrow on the maker's behalf
+        principal: Option<Coin>,
+        // Re-list any unfilled remainder as a new order on cancel or expiry
+        relist: Option<RelistParams>,
+        // Secret hash algorithm; the resolver's default when unset
+        hash_algo: Option<HashAlgo>,
         label: String,
     },
     /// Deploy a new destination escrow
@@ -39,13 +79,39 @@ pub enum ExecuteMsg {
         src_chain_id: String,
         src_escrow_address: String,
         expected_amount: Uint128,
+        // Payout address for the destination claim
+        dst_claim_addr: Option<String>,
+        // CW20 contracts the escrow accepts for deposits
+        allowed_cw20: Option<Vec<String>>,
+        /// Source-leg order to link this destination order to
+        src_order_id: Option<String>,
+        /// Secret hash algorithm; the resolver's default when unset
+        hash_algo: Option<HashAlgo>,
         label: String,
     },
+    /// Deploy a source escrow from an order the maker signed off-chain; the relayer pays gas
+    DeploySrcSigned {
+        order: SignedSrcOrder,
+        /// 64-byte secp256k1 signature over sha256 of the order's JSON encoding
+        maker_signature: Binary,
+    },
+    /// Register the secp256k1 public key used to verify the sender's signed orders
+    RegisterMakerPubkey {
+        pubkey: Binary,
+    },
     /// Withdraw from an escrow using the secret
     Withdraw {
         escrow_address: String,
         secret: String,
     },
+    /// Withdraw a source order on the maker's off-chain authorization, submitted by a relayer
+    WithdrawSigned {
+        order_id: String,
+        /// 64-byte secp256k1 signature over sha256 of the `SignedWithdrawal` JSON encoding
+        signature: Binary,
+        /// The swap secret itself, which the maker signed over; the relayer forwards it to the escrow
+        secret: String,
+    },
     /// Partial withdraw from an escrow
     PartialWithdraw {
         escrow_address: String,
@@ -60,24 +126,213 @@ pub enum ExecuteMsg {
     UpdatePrice {
         escrow_address: String,
     },
+    /// Refresh the auction price of up to `limit` active orders on a chain
+    UpdatePricesForChain {
+        chain_id: String,
+        limit: Option<u32>,
+    },
+    /// Apply the order's expiry disposition once its auction reached the minimum price
+    ExpireOrder {
+        order_id: String,
+    },
+    /// Re-create an unfunded order's escrow with the factory's current code ID
+    MigrateOrderEscrow {
+        order_id: String,
+    },
+    /// Rotate an unfunded order's hashlock (maker only)
+    UpdateSecretHash {
+        order_id: String,
+        new_secret_hash: String,
+    },
+    /// Cancel both legs of a linked order, destination first; legs still timelocked are reported
+    CancelBoth {
+        order_id: String,
+    },
+    /// Reconcile a linked order's status from both escrows: both withdrawn completes it, either
+    /// cancelled cancels it (permissionless)
+    SyncBothLegs {
+        order_id: String,
+    },
     /// Process a cross-chain order (called by relayer)
     ProcessOrder {
         order_id: String,
         action: OrderAction,
         proof: Option<String>,
     },
+    /// Confirm several orders whose source escrows landed in the same block
+    BatchConfirmSource {
+        /// (order_id, src_tx_hash) pairs
+        confirmations: Vec<(String, String)>,
+        block_height: u64,
+        /// Inclusion proof for the block, required in strict mode
+        proof: Option<String>,
+    },
     /// Add authorized relayer
     AddRelayer {
         relayer: String,
+        /// Seconds until the entry expires; permanent when unset
+        ttl: Option<u64>,
     },
     /// Remove authorized relayer
     RemoveRelayer {
         relayer: String,
     },
+    /// Replace the relayers serving a chain; an empty list removes the set (owner only)
+    SetChainRelayers {
+        chain_id: String,
+        relayers: Vec<String>,
+    },
     /// Update owner
     UpdateOwner {
         new_owner: String,
     },
+    /// Register a taker allowed to receive swap proceeds
+    RegisterTaker {
+        taker: String,
+    },
+    /// Deregister a taker
+    DeregisterTaker {
+        taker: String,
+    },
+    /// Add or remove a maker from the protocol fee exemption list (owner only)
+    SetFeeExempt {
+        maker: String,
+        exempt: bool,
+    },
+    /// Toggle whether deploys require a registered taker
+    SetRequireRegisteredTaker {
+        required: bool,
+    },
+    /// Record the latest observed block height of a source chain (called by relayer)
+    UpdateSourceHead {
+        chain_id: String,
+        height: u64,
+    },
+    /// Update the confirmation depth required before confirming a source escrow
+    SetMinConfirmations {
+        min_confirmations: u64,
+    },
+    /// Move terminal orders last updated before `before_ts` into the archive
+    ArchiveOrders {
+        before_ts: u64,
+        limit: Option<u32>,
+    },
+    /// Seed the archive with terminal orders carried over from another resolver (owner only)
+    ImportOrders {
+        orders: Vec<ImportedOrder>,
+    },
+    /// Configure the protocol fee rebate paid to makers whose orders fill quickly
+    SetFastFillRebate {
+        window: u64,
+        rebate_bps: u16,
+    },
+    /// Freeze or unfreeze a single order; frozen orders can be cancelled but not withdrawn
+    SetOrderFrozen {
+        order_id: String,
+        frozen: bool,
+    },
+    /// Stop or resume new fills on a single order; cancellation and clearing the final remainder stay open
+    SetOrderAcceptingFills {
+        order_id: String,
+        accepting: bool,
+    },
+    /// Set how long a relayer-initiated cancel waits for a maker veto
+    SetCancelGracePeriod {
+        seconds: u64,
+    },
+    /// Veto a pending cancel before it takes effect (maker only)
+    VetoCancel {
+        order_id: String,
+    },
+    /// Cancel immediately by proving knowledge of the secret, ahead of any relayer action (maker only)
+    CancelWithSecret {
+        order_id: String,
+        secret: String,
+    },
+    /// Configure how partial-fill price improvement is split between maker and collector
+    SetPriceImprovementSplit {
+        maker_bps: u16,
+        collector: Option<String>,
+    },
+    /// Send the caller's accrued fees to `to` and clear them: swap fees for the owner or fee collector,
+    /// price improvement for the price improvement collector
+    WithdrawFees {
+        to: String,
+    },
+    /// Set the protocol fee charged on executed swaps and its collector (owner only)
+    UpdateFee {
+        fee_bps: u16,
+        fee_collector: String,
+    },
+    /// Stop or resume deploys, withdrawals and order processing; cancels stay available (owner only)
+    SetPaused { paused: bool },
+    /// Assign an order to a preferred relayer, or clear the assignment (owner or maker)
+    AssignRelayer {
+        order_id: String,
+        relayer: Option<String>,
+    },
+    /// Set how long an assignment stays exclusive before falling back to all relayers
+    SetRelayerExclusiveWindow {
+        seconds: u64,
+    },
+    /// Require a proof on every source confirmation, or accept relayer attestations (owner only)
+    SetProofRequired {
+        required: bool,
+    },
+    /// Refund price improvement paid above the live auction price to the payer (owner only)
+    SetRefundOverpayment {
+        enabled: bool,
+    },
+    /// Refuse source confirmations until the destination escrow is fully funded (owner only)
+    SetRequireFundedDestination {
+        required: bool,
+    },
+    /// Set the secret hash algorithm used by deploys that do not name one (owner only)
+    SetDefaultHashAlgo {
+        hash_algo: HashAlgo,
+    },
+    /// Flag an order as disputed, freezing everything but cancellation (maker, taker or relayer)
+    RaiseDispute {
+        order_id: String,
+        evidence_hash: String,
+    },
+    /// Record the outcome of an open dispute and lift the freeze (owner only)
+    ResolveDispute {
+        order_id: String,
+        outcome: DisputeOutcome,
+    },
+}
+
+/// Canonical source order signed by the maker
+#[cw_serde]
+pub struct SignedSrcOrder {
+    /// Resolver the order is valid for, so it cannot be replayed elsewhere
+    pub resolver: String,
+    pub maker: String,
+    pub taker: Option<String>,
+    pub secret_hash: String,
+    pub timelock: u64,
+    pub dst_chain_id: String,
+    pub dst_asset: String,
+    pub dst_amount: Uint128,
+    pub src_amount: Option<Uint128>,
+    pub initial_price: Option<Uint128>,
+    pub price_decay_rate: Option<Uint128>,
+    pub minimum_price: Option<Uint128>,
+    pub allow_partial_fill: bool,
+    pub minimum_fill_amount: Option<Uint128>,
+    pub nonce: u64,
+    pub label: String,
+}
+
+/// Withdrawal authorization signed by the maker
+#[cw_serde]
+pub struct SignedWithdrawal {
+    /// Resolver the authorization is valid for, so it cannot be replayed elsewhere
+    pub resolver: String,
+    pub order_id: String,
+    /// Swap secret revealed to the escrow by the withdrawal
+    pub secret: String,
 }
 
 #[cw_serde]
@@ -91,6 +346,11 @@ pub enum OrderAction {
     ExecuteSwap {
         secret: String,
     },
+    /// Fill part of a source order and release the proportional amount on its destination
+    PartialExecuteSwap {
+        secret: String,
+        amount: Uint128,
+    },
     /// Cancel order
     CancelOrder,
 }
@@ -104,18 +364,123 @@ pub enum QueryMsg {
     /// Get order information
     #[returns(OrderResponse)]
     Order { order_id: String },
-    /// List active orders
+    /// List orders still in `Active` status
     #[returns(OrderListResponse)]
     ActiveOrders {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// List orders in the given status, through the status index
+    #[returns(OrderListResponse)]
+    OrdersByStatus {
+        status: OrderStatus,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// List a maker's orders in any status, through the maker index
+    #[returns(OrderListResponse)]
+    OrdersByMaker {
+        maker: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Export live orders as lightweight summaries, for indexers bootstrapping their state
+    #[returns(ExportOrdersResponse)]
+    ExportActiveOrders {
+        cursor: Option<String>,
+        page_size: Option<u32>,
+    },
+    /// Order status transitions recorded at `height` or later; pass the last seen `seq` to continue within a height
+    #[returns(EventsSinceResponse)]
+    EventsSince {
+        height: u64,
+        start_after_seq: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Preview the order ID and factory salt a DeploySrc with the same params would get in this block
+    #[returns(PreviewDeployResponse)]
+    PreviewDeploySrc {
+        maker: String,
+        taker: Option<String>,
+        secret_hash: String,
+        timelock: u64,
+        dst_chain_id: String,
+        dst_asset: String,
+        dst_amount: Uint128,
+        src_amount: Option<Uint128>,
+        initial_price: Option<Uint128>,
+        price_decay_rate: Option<Uint128>,
+        minimum_price: Option<Uint128>,
+        allow_partial_fill: bool,
+        minimum_fill_amount: Option<Uint128>,
+        dust_threshold: Option<Uint128>,
+        lop_order_data: Option<String>,
+        on_expiry: Option<ExpiryAction>,
+        src_refund_addr: Option<String>,
+        safety_deposit: Option<Coin>,
+        safety_deposit_swap: Option<SafetyDepositSwap>,
+        ibc_forward: Option<IbcForwardParams>,
+        allowed_cw20: Option<Vec<String>>,
+        label: String,
+    },
     /// Get Dutch auction current price
     #[returns(PriceResponse)]
     CurrentPrice { escrow_address: String },
+    /// Active auction orders ranked by live price; prices every active order, so keep `limit` small
+    #[returns(OrdersByPriceResponse)]
+    OrdersByPrice {
+        ascending: bool,
+        limit: Option<u32>,
+    },
+    /// Compare an offered price against the live auction price
+    #[returns(EvaluateFillResponse)]
+    EvaluateFill {
+        escrow_address: String,
+        offered_price: Uint128,
+    },
     /// Check if relayer is authorized
     #[returns(RelayerResponse)]
     IsAuthorizedRelayer { relayer: String },
+    /// List the orders a relayer can currently act on
+    #[returns(RelayerScopeResponse)]
+    RelayerScope {
+        relayer: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Check if taker is registered
+    #[returns(TakerResponse)]
+    IsRegisteredTaker { taker: String },
+    /// Get the tracked head of a source chain
+    #[returns(SourceHeadResponse)]
+    SourceHead { chain_id: String },
+    /// Get an archived order
+    #[returns(OrderResponse)]
+    ArchivedOrder { order_id: String },
+    /// List orders matching every provided filter; `chain` matches either leg's chain
+    #[returns(OrderListResponse)]
+    SearchOrders {
+        maker: Option<String>,
+        status: Option<OrderStatus>,
+        chain: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Get time-to-completion statistics across completed orders
+    #[returns(PerformanceStatsResponse)]
+    PerformanceStats {},
+    /// Get protocol fees accrued and not yet withdrawn
+    #[returns(AccruedFeesResponse)]
+    AccruedFees {},
+    /// Get the schema version and the features this deployment supports
+    #[returns(FeaturesResponse)]
+    Features {},
+    /// Check that the configured factory responds and report its code IDs
+    #[returns(HealthResponse)]
+    Health {},
+    /// Absolute timestamps of each timelock stage of an order's escrow
+    #[returns(TimelockScheduleResponse)]
+    TimelockSchedule { order_id: String },
 }
 
 #[cw_serde]
@@ -123,19 +488,58 @@ pub struct ConfigResponse {
     pub owner: Addr,
     pub escrow_factory: Addr,
     pub authorized_relayers: Vec<Addr>,
+    pub require_registered_taker: bool,
+    pub min_confirmations: u64,
+    pub fast_fill_window: u64,
+    pub fast_fill_rebate_bps: u16,
+    pub cancel_grace_period: u64,
+    pub price_improvement_maker_bps: u16,
+    pub price_improvement_collector: Option<Addr>,
+    pub relayer_exclusive_window: u64,
+    pub proof_required: bool,
+    pub refund_overpayment: bool,
+    pub require_funded_destination: bool,
+    pub default_hash_algo: HashAlgo,
+    pub fee_bps: u16,
+    pub fee_collector: Addr,
+    pub paused: bool,
 }
 
 #[cw_serde]
 pub struct OrderResponse {
     pub order_id: String,
     pub escrow_address: Addr,
+    pub escrow_type: EscrowType,
     pub maker: Addr,
     pub taker: Option<Addr>,
+    pub secret_hash: String,
+    pub src_amount: Option<Uint128>,
+    pub dst_amount: Uint128,
     pub status: OrderStatus,
     pub created_at: u64,
     pub updated_at: u64,
     pub dutch_auction: Option<DutchAuctionInfo>,
     pub partial_fill: Option<PartialFillInfo>,
+    pub on_expiry: ExpiryAction,
+    pub src_refund_addr: Option<Addr>,
+    pub dst_claim_addr: Option<Addr>,
+    pub counterparty_order_id: Option<String>,
+    pub src_chain_id: Option<String>,
+    pub dst_chain_id: Option<String>,
+    pub filled_at: Option<u64>,
+    pub frozen: bool,
+    pub accepting_fills: bool,
+    pub cancel_effective_at: Option<u64>,
+    pub assigned_relayer: Option<Addr>,
+    pub exclusive_until: Option<u64>,
+    pub relayer_reward: Option<Coin>,
+    pub dispute: Option<DisputeInfo>,
+    pub relist: Option<RelistParams>,
+    pub relisted_as: Option<String>,
+    pub hash_algo: HashAlgo,
+    /// Whether the maker is currently exempt from protocol fees
+    pub fee_exempt: bool,
+    pub fee_paid: Option<Coin>,
 }
 
 #[cw_serde]
@@ -143,17 +547,152 @@ pub struct OrderListResponse {
     pub orders: Vec<OrderResponse>,
 }
 
+#[cw_serde]
+pub struct OrderSummary {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub maker: Addr,
+    pub escrow_address: Addr,
+    pub updated_at: u64,
+}
+
+#[cw_serde]
+pub struct ExportOrdersResponse {
+    pub orders: Vec<OrderSummary>,
+    /// Cursor for the next page; `None` once the export is complete
+    pub next_cursor: Option<String>,
+}
+
+/// Reference to an order status transition, as kept in the event index
+#[cw_serde]
+pub struct OrderEventRef {
+    pub order_id: String,
+    pub status: OrderStatus,
+}
+
+#[cw_serde]
+pub struct OrderEvent {
+    pub height: u64,
+    pub seq: u64,
+    pub order_id: String,
+    pub status: OrderStatus,
+}
+
+#[cw_serde]
+pub struct EventsSinceResponse {
+    pub events: Vec<OrderEvent>,
+}
+
+#[cw_serde]
+pub struct PricedOrder {
+    pub order: OrderResponse,
+    pub current_price: Uint128,
+}
+
+#[cw_serde]
+pub struct OrdersByPriceResponse {
+    pub orders: Vec<PricedOrder>,
+}
+
 #[cw_serde]
 pub struct PriceResponse {
     pub current_price: Uint128,
     pub initial_price: Option<Uint128>,
     pub minimum_price: Option<Uint128>,
     pub time_elapsed: u64,
+    pub price_decimals: u8,
+}
+
+#[cw_serde]
+pub struct PreviewDeployResponse {
+    pub order_id: String,
+    pub salt: String,
+}
+
+#[cw_serde]
+pub struct EvaluateFillResponse {
+    pub current_price: Uint128,
+    pub accepted: bool,
+    /// How far the offer falls below the current price; zero when accepted
+    pub shortfall: Uint128,
+}
+
+#[cw_serde]
+pub struct TimelockScheduleResponse {
+    pub order_id: String,
+    /// Withdrawals open at this time
+    pub finality_at: u64,
+    /// End of the taker-only withdrawal window
+    pub exclusive_until: u64,
+    /// End of the public withdrawal window
+    pub public_until: u64,
+    /// Cancellation opens at this time
+    pub cancel_at: u64,
+}
+
+#[cw_serde]
+pub struct HealthResponse {
+    pub factory_reachable: bool,
+    /// Code IDs reported by the factory; unset when it did not respond
+    pub source_escrow_code_id: Option<u64>,
+    pub destination_escrow_code_id: Option<u64>,
+    pub active_orders: u64,
+}
+
+#[cw_serde]
+pub struct FeaturesResponse {
+    /// Contract name and version recorded at instantiate or migrate
+    pub contract: String,
+    pub schema_version: String,
+    pub supports_partial_fill: bool,
+    pub supports_dutch_auction: bool,
+    pub supports_merkle_secrets: bool,
+    pub supports_signed_orders: bool,
+    /// Whether price improvement capture is configured
+    pub supports_price_improvement: bool,
+    pub hash_algos: Vec<String>,
 }
 
 #[cw_serde]
 pub struct RelayerResponse {
     pub is_authorized: bool,
+    pub expires_at: Option<u64>,
+}
+
+#[cw_serde]
+pub struct RelayerScopeResponse {
+    pub is_authorized: bool,
+    /// Total actionable orders, across all pages
+    pub actionable_count: u64,
+    pub order_ids: Vec<String>,
+}
+
+#[cw_serde]
+pub struct TakerResponse {
+    pub is_registered: bool,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    /// Swap fees owed to the fee collector
+    pub native: Vec<Coin>,
+    pub cw20: Vec<Cw20Coin>,
+    /// Price improvement owed to the price improvement collector
+    pub price_improvement: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct PerformanceStatsResponse {
+    pub avg_completion_seconds: u64,
+    /// Upper bound in seconds of the histogram bucket holding the median; u64::MAX past the last bucket
+    pub median_bucket: u64,
+    pub completed_count: u64,
+}
+
+#[cw_serde]
+pub struct SourceHeadResponse {
+    pub chain_id: String,
+    pub height: Option<u64>,
 }
 
 #[cw_serde]
@@ -162,15 +701,97 @@ pub struct DutchAuctionInfo {
     pub minimum_price: Uint128,
     pub price_decay_rate: Uint128,
     pub start_time: u64,
+    pub start_height: u64,
+    pub decay_basis: DecayBasis,
     pub current_price: Uint128,
+    /// Swaps are rejected while the live price sits below this floor
+    pub min_settle_price: Option<Uint128>,
+    /// Decimals of the prices above, so clients can render them
+    pub price_decimals: u8,
+}
+
+#[cw_serde]
+pub enum DecayBasis {
+    /// `price_decay_rate` is applied per second since `start_time`
+    Time,
+    /// `price_decay_rate` is applied per block since `start_height`
+    Height,
 }
 
 #[cw_serde]
 pub struct PartialFillInfo {
     pub allow_partial_fill: bool,
     pub minimum_fill_amount: Option<Uint128>,
+    pub dust_threshold: Option<Uint128>,
+    /// This leg's own progress, in the asset its escrow holds
     pub filled_amount: Uint128,
     pub remaining_amount: Uint128,
+    /// Swap progress in source-asset units, shared by both legs of a linked pair
+    pub src_filled: Uint128,
+    pub src_remaining: Uint128,
+    /// Swap progress in destination-asset units, converted at the settlement price
+    pub dst_filled: Uint128,
+    pub dst_remaining: Uint128,
+}
+
+/// Terminal order carried over from another resolver for analytics
+#[cw_serde]
+pub struct ImportedOrder {
+    pub order_id: String,
+    pub escrow_address: String,
+    pub escrow_type: EscrowType,
+    pub maker: String,
+    pub taker: Option<String>,
+    pub secret_hash: String,
+    pub src_amount: Option<Uint128>,
+    pub dst_amount: Uint128,
+    /// Must be completed, cancelled or expired
+    pub status: OrderStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub src_chain_id: Option<String>,
+    pub dst_chain_id: Option<String>,
+    pub filled_at: Option<u64>,
+}
+
+/// Terms for re-listing the unfilled remainder of a partial-fill order
+#[cw_serde]
+pub struct RelistParams {
+    /// Fresh hashlock; the original secret is public once a fill has settled
+    pub secret_hash: String,
+    pub timelock: u64,
+    pub dst_asset: String,
+    pub initial_price: Option<Uint128>,
+    pub price_decay_rate: Option<Uint128>,
+    pub minimum_price: Option<Uint128>,
+    pub label: String,
+}
+
+#[cw_serde]
+pub struct DisputeInfo {
+    pub raised_by: Addr,
+    /// Hash of the off-chain evidence bundle
+    pub evidence_hash: String,
+    pub raised_at: u64,
+    /// Set by the owner on resolution; the order stays frozen until then
+    pub outcome: Option<DisputeOutcome>,
+    pub resolved_at: Option<u64>,
+}
+
+#[cw_serde]
+pub enum DisputeOutcome {
+    /// The dispute was valid
+    Upheld,
+    /// The dispute was rejected and the order resumes
+    Dismissed,
+}
+
+#[cw_serde]
+pub enum ExpiryAction {
+    /// Cancel the source escrow and refund the maker
+    CancelRefund,
+    /// Keep the order open as a fixed-price order at the minimum price
+    RestAtMinimum,
 }
 
 #[cw_serde]
@@ -182,3 +803,15 @@ pub enum OrderStatus {
     Expired,
 }
 
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Active => "active",
+            OrderStatus::Matched => "matched",
+            OrderStatus::Completed => "completed",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Expired => "expired",
+        }
+    }
+}
+
@@ -1,32 +1,152 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use escrow_common::hash::HashAlgo;
+use escrow_factory::msg::EscrowType;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::{OrderStatus, DutchAuctionInfo, PartialFillInfo};
+use crate::msg::{OrderStatus, OrderEventRef, DutchAuctionInfo, DisputeInfo, PartialFillInfo, ExpiryAction, RelistParams};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: Addr,
     pub escrow_factory: Addr,
     pub authorized_relayers: Vec<Addr>,
+    pub require_registered_taker: bool,
+    pub min_confirmations: u64,
+    /// Seconds after creation within which a fill earns the maker a fee rebate; 0 disables
+    pub fast_fill_window: u64,
+    pub fast_fill_rebate_bps: u16,
+    /// Seconds a relayer-initiated cancel waits for a maker veto; 0 cancels immediately
+    pub cancel_grace_period: u64,
+    /// Share of partial-fill price improvement paid to the maker; the rest goes to the collector
+    pub price_improvement_maker_bps: u16,
+    /// Collector of the protocol's share of price improvement; capture is off when unset
+    pub price_improvement_collector: Option<Addr>,
+    /// Seconds an assigned relayer has exclusive rights to process an order before any relayer may
+    pub relayer_exclusive_window: u64,
+    /// Reject source confirmations that carry no proof; trusted relayer attestations otherwise
+    pub proof_required: bool,
+    /// Return surplus attached above the live price instead of rejecting it
+    pub refund_overpayment: bool,
+    /// Confirm a source only once its destination escrow holds the full expected amount
+    pub require_funded_destination: bool,
+    /// Secret hash algorithm for deploys that do not specify one
+    pub default_hash_algo: HashAlgo,
+    /// Protocol fee on executed swaps, in basis points of the order amount
+    pub fee_bps: u16,
+    pub fee_collector: Addr,
+    /// Emergency stop for deploys, withdrawals and order processing; cancellation stays open
+    pub paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Order {
     pub order_id: String,
     pub escrow_address: Addr,
+    pub escrow_type: EscrowType,
     pub maker: Addr,
     pub taker: Option<Addr>,
+    pub secret_hash: String,
+    pub src_amount: Option<Uint128>,
+    pub dst_amount: Uint128,
     pub status: OrderStatus,
     pub created_at: u64,
     pub updated_at: u64,
     pub dutch_auction: Option<DutchAuctionInfo>,
     pub partial_fill: Option<PartialFillInfo>,
     pub lop_order_data: Option<String>,
+    pub on_expiry: ExpiryAction,
+    pub src_refund_addr: Option<Addr>,
+    pub dst_claim_addr: Option<Addr>,
+    pub counterparty_order_id: Option<String>,
+    pub src_chain_id: Option<String>,
+    pub dst_chain_id: Option<String>,
+    pub filled_at: Option<u64>,
+    pub frozen: bool,
+    /// When false, only the final remainder of an already started fill may be executed
+    pub accepting_fills: bool,
+    pub cancel_effective_at: Option<u64>,
+    pub assigned_relayer: Option<Addr>,
+    pub exclusive_until: Option<u64>,
+    /// Reward held by the resolver for the relayer that completes the order
+    pub relayer_reward: Option<Coin>,
+    /// Account that attached `relayer_reward`, refunded when no relayer earns it
+    pub reward_payer: Option<Addr>,
+    pub dispute: Option<DisputeInfo>,
+    pub relist: Option<RelistParams>,
+    /// Order created from this order's unfilled remainder
+    pub relisted_as: Option<String>,
+    pub hash_algo: HashAlgo,
+    /// Protocol fee the executing relayer paid on the swap
+    pub fee_paid: Option<Coin>,
+}
+
+pub struct OrderIndexes<'a> {
+    pub maker: MultiIndex<'a, String, Order, String>,
+    pub status: MultiIndex<'a, String, Order, String>,
+}
+
+impl<'a> IndexList<Order> for OrderIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Order>> + '_> {
+        let v: Vec<&dyn Index<Order>> = vec![&self.maker, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Orders keyed by order ID, indexed by maker and status.
+pub fn orders<'a>() -> IndexedMap<'a, String, Order, OrderIndexes<'a>> {
+    let indexes = OrderIndexes {
+        maker: MultiIndex::new(|_pk, order: &Order| order.maker.to_string(), "orders", "orders__maker"),
+        status: MultiIndex::new(
+            |_pk, order: &Order| order.status.as_str().to_string(),
+            "orders",
+            "orders__status",
+        ),
+    };
+    IndexedMap::new("orders", indexes)
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const ORDERS: Map<String, Order> = Map::new("orders");
+/// Order status transitions keyed by (block height, global sequence number)
+pub const ORDER_EVENTS: Map<(u64, u64), OrderEventRef> = Map::new("order_events");
+pub const EVENT_COUNT: Item<u64> = Item::new("event_count");
+pub const ARCHIVED_ORDERS: Map<String, Order> = Map::new("archived_orders");
 pub const ORDER_COUNT: Item<u64> = Item::new("order_count");
+pub const REGISTERED_TAKERS: Map<&Addr, bool> = Map::new("registered_takers");
+/// Makers whose orders pay no protocol fees
+pub const FEE_EXEMPT: Map<&Addr, bool> = Map::new("fee_exempt");
+pub const SOURCE_HEADS: Map<&str, u64> = Map::new("source_heads");
+pub const PERFORMANCE: Item<PerformanceStats> = Item::new("performance");
+/// Expiry time of relayers added with a TTL; relayers without an entry never expire
+pub const RELAYER_EXPIRIES: Map<&Addr, u64> = Map::new("relayer_expiries");
+/// Relayers serving a single chain, on top of the global set in `Config`
+pub const CHAIN_RELAYERS: Map<&str, Vec<Addr>> = Map::new("chain_relayers");
+pub const MAKER_PUBKEYS: Map<&Addr, Binary> = Map::new("maker_pubkeys");
+/// Hashes of signed orders already deployed, mapped to their order ID
+pub const SIGNED_ORDERS: Map<&[u8], String> = Map::new("signed_orders");
+/// Swap fees held for the fee collector, by native denom
+pub const ACCRUED_FEES: Map<&str, Uint128> = Map::new("accrued_fees");
+/// Swap fees held for the fee collector, by CW20 contract
+pub const ACCRUED_CW20_FEES: Map<&Addr, Uint128> = Map::new("accrued_cw20_fees");
+/// Price improvement held for the price improvement collector, by native denom
+pub const ACCRUED_SURPLUS: Map<&str, Uint128> = Map::new("accrued_surplus");
+/// Order whose escrow the factory is creating, by reply id
+pub const PENDING_ESCROWS: Map<u64, String> = Map::new("pending_escrows");
+pub const ESCROW_REPLY_COUNT: Item<u64> = Item::new("escrow_reply_count");
+/// Principal to deposit into an order's escrow once the factory has created it, by order ID
+pub const PENDING_FUNDING: Map<&str, Coin> = Map::new("pending_funding");
+/// Order and principal of the escrow deposit awaiting its reply
+pub const FUNDING_CHECK: Item<(String, Coin)> = Item::new("funding_check");
+
+/// Upper bounds, in seconds, of the completion-time histogram buckets; the last bucket is open-ended
+pub const COMPLETION_BUCKETS: [u64; 5] = [60, 300, 900, 3600, 86400];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PerformanceStats {
+    pub completed_count: u64,
+    pub total_completion_seconds: u64,
+    /// Completions per `COMPLETION_BUCKETS` entry, plus one overflow bucket
+    pub bucket_counts: Vec<u64>,
+}
 
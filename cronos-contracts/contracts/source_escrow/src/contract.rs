@@ -2,19 +2,40 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    CosmosMsg, BankMsg, WasmMsg, from_binary, Addr
+    CosmosMsg, BankMsg, WasmMsg, from_binary, Storage, Addr, Coin, Event, IbcMsg, IbcTimeout, Reply,
+    StdError, SubMsg, SubMsgResult
 };
 use cw2::set_contract_version;
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use escrow_common::denom::denoms_match;
+use escrow_common::history::{query_history, record_transition};
+use escrow_common::validation::{
+    validate_amounts, validate_auction_params, validate_identifier, validate_minimum_fill, validate_secret_hash,
+    validate_timelock,
+};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, EscrowResponse, PriceResponse, FillStatusResponse};
-use crate::state::{EscrowInfo, EscrowStatus, ESCROW_INFO};
+use crate::msg::{
+    AssetFillStatus, AssetInfo, CheckSecretResponse, ClaimableResponse, CounterpartyParamsResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg, EscrowResponse,
+    RouterExecuteMsg, Timelocks,
+    HistoryResponse, PriceResponse, FillStatusResponse, PreviewCancelResponse, RefundAsset, RefundQuoteResponse,
+};
+use crate::state::{
+    BasketAsset, EscrowInfo, EscrowInfoV1, EscrowStatus, CLAIMABLE, ESCROW_INFO, FACTORY, LEGACY_ESCROW_INFO,
+    PENDING_DEPOSIT, PENDING_PAYOUT, RESOLVER,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:source_escrow";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Reply ID for withdrawal payouts that are parked on failure
+const PAYOUT_REPLY_ID: u64 = 1;
+// Reply ID for CW20 deposits pulled with TransferFrom
+const DEPOSIT_REPLY_ID: u64 = 2;
+// Price precision assumed when the deployer does not give one, matching micro-denominated assets
+const DEFAULT_PRICE_DECIMALS: u8 = 6;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -24,25 +45,102 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     let maker = deps.api.addr_validate(&msg.maker)?;
     let taker = msg.taker.map(|t| deps.api.addr_validate(&t)).transpose()?;
+    let refund_addr = msg.refund_addr.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let resolver = msg.resolver.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let allowed_cw20 = msg
+        .allowed_cw20
+        .map(|list| list.iter().map(|a| deps.api.addr_validate(a)).collect::<StdResult<Vec<_>>>())
+        .transpose()?;
+    let withdraw_whitelist = msg
+        .withdraw_whitelist
+        .map(|list| list.iter().map(|a| deps.api.addr_validate(a)).collect::<StdResult<Vec<_>>>())
+        .transpose()?;
+    let on_complete_msg = msg
+        .on_complete_msg
+        .map(|(contract, callback)| deps.api.addr_validate(&contract).map(|addr| (addr, callback)))
+        .transpose()?;
+
+    // The callback runs as the escrow, so it must not be able to move the principal
+    if let Some((contract, _)) = &on_complete_msg {
+        if *contract == env.contract.address
+            || allowed_cw20.as_ref().map_or(false, |list| list.contains(contract))
+        {
+            return Err(ContractError::InvalidCompletionCallback {});
+        }
+    }
+
+    validate_identifier(&msg.dst_chain_id)?;
+    validate_identifier(&msg.dst_asset)?;
+    let now = env.block.time.seconds();
+    let timelocks = match msg.timelocks {
+        Some(timelocks) => {
+            validate_stages(&timelocks)?;
+            validate_timelock(now.saturating_add(timelocks.cancellation), now)?;
+            timelocks
+        }
+        // A single timelock keeps withdrawals open to anyone until it, then allows cancellation
+        None => {
+            validate_timelock(msg.timelock, now)?;
+            Timelocks::from_timelock(now, msg.timelock)
+        }
+    };
+    validate_amounts(msg.dst_amount, msg.src_amount)?;
+    validate_auction_params(msg.initial_price, msg.price_decay_rate, msg.minimum_price)?;
+
+    if let Some(safety_deposit) = &msg.safety_deposit {
+        if safety_deposit.amount.is_zero() {
+            return Err(ContractError::MissingSafetyDeposit {});
+        }
+    }
+
+    if let Some(swap) = &msg.safety_deposit_swap {
+        deps.api.addr_validate(&swap.router)?;
+        if swap.output_denom.is_empty() {
+            return Err(ContractError::InvalidSafetyDepositSwap {});
+        }
+    }
 
-    // Validate dutch auction parameters
-    if let (Some(initial_price), Some(minimum_price)) = (&msg.initial_price, &msg.minimum_price) {
-        if initial_price <= minimum_price {
-            return Err(ContractError::InvalidDutchAuctionParams {});
+    validate_minimum_fill(msg.allow_partial_fill, msg.minimum_fill_amount, msg.dst_amount)?;
+
+    if let Some(ibc_forward) = &msg.ibc_forward {
+        if ibc_forward.channel.is_empty() || ibc_forward.receiver.is_empty() || ibc_forward.timeout == 0 {
+            return Err(ContractError::InvalidIbcForward {});
+        }
+    }
+
+    if let Some(basket) = &msg.basket {
+        let mut denoms: Vec<&str> = basket.iter().map(|asset| asset.denom.as_str()).collect();
+        denoms.sort_unstable();
+        denoms.dedup();
+        if basket.is_empty()
+            || denoms.len() != basket.len()
+            || basket.iter().any(|asset| asset.amount.is_zero())
+        {
+            return Err(ContractError::InvalidBasket {});
+        }
+    }
+
+    // Tranches split a single-asset deposit along the Merkle secrets
+    if let Some(count) = msg.tranche_count {
+        if count == 0 || msg.merkle_root.is_none() || msg.basket.is_some() {
+            return Err(ContractError::InvalidTranche {});
         }
     }
 
     let escrow_info = EscrowInfo {
         maker: maker.clone(),
         taker,
-        secret_hash: msg.secret_hash,
-        timelock: msg.timelock,
+        refund_addr,
+        secret_hash: decode_secret_hash(&msg.secret_hash)?,
+        timelocks,
         dst_chain_id: msg.dst_chain_id,
         dst_asset: msg.dst_asset,
         dst_amount: msg.dst_amount,
+        src_amount: msg.src_amount,
         deposited_amount: Uint128::zero(),
         deposited_denom: None,
         cw20_contract: None,
+        allowed_cw20,
         status: EscrowStatus::Active,
         created_at: env.block.time.seconds(),
         initial_price: msg.initial_price,
@@ -50,17 +148,48 @@ pub fn instantiate(
         minimum_price: msg.minimum_price,
         allow_partial_fill: msg.allow_partial_fill,
         minimum_fill_amount: msg.minimum_fill_amount,
+        dust_threshold: msg.dust_threshold,
         filled_amount: Uint128::zero(),
         remaining_amount: Uint128::zero(), // Will be set when deposit is made
+        safety_deposit: msg.safety_deposit,
+        safety_deposit_swap: msg.safety_deposit_swap,
+        safety_deposit_funded: false,
+        ibc_forward: msg.ibc_forward,
+        basket: msg.basket.map(|basket| {
+            basket
+                .into_iter()
+                .map(|asset| BasketAsset {
+                    denom: asset.denom,
+                    amount: asset.amount,
+                    filled_amount: Uint128::zero(),
+                })
+                .collect()
+        }),
+        withdraw_whitelist,
+        on_complete_msg,
+        max_total_extension: msg.max_total_extension.unwrap_or(0),
+        total_extension: 0,
+        hash_algorithm: msg.hash_algorithm.unwrap_or_default(),
+        case_insensitive_denoms: msg.case_insensitive_denoms,
+        merkle_root: msg.merkle_root.as_deref().map(decode_secret_hash).transpose()?,
+        last_used_index: None,
+        tranche_count: msg.tranche_count,
+        cancelled_tranches: vec![],
+        price_decimals: msg.price_decimals.unwrap_or(DEFAULT_PRICE_DECIMALS),
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    FACTORY.save(deps.storage, &info.sender)?;
+    if let Some(resolver) = &resolver {
+        RESOLVER.save(deps.storage, resolver)?;
+    }
+    record_transition(deps.storage, &env, None, EscrowStatus::Active, &info.sender)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("maker", maker)
-        .add_attribute("timelock", msg.timelock.to_string()))
+        .add_attribute("timelock", cancel_at(&escrow_info).to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -73,12 +202,26 @@ pub fn execute(
     match msg {
         ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::DepositFrom { cw20_contract, amount } => {
+            execute_deposit_from(deps, env, info, cw20_contract, amount)
+        }
         ExecuteMsg::Withdraw { secret } => execute_withdraw(deps, env, info, secret),
         ExecuteMsg::Cancel {} => execute_cancel(deps, env, info),
         ExecuteMsg::PartialWithdraw { secret, amount } => {
             execute_partial_withdraw(deps, env, info, secret, amount)
         }
+        ExecuteMsg::PartialWithdrawMerkle { secret, merkle_proof, index, amount } => {
+            execute_partial_withdraw_merkle(deps, env, info, secret, merkle_proof, index, amount)
+        }
+        ExecuteMsg::CancelTranches { indices } => execute_cancel_tranches(deps, env, info, indices),
         ExecuteMsg::UpdatePrice {} => execute_update_price(deps, env, info),
+        ExecuteMsg::RestAtMinimum {} => execute_rest_at_minimum(deps, info),
+        ExecuteMsg::UpdateSecretHash { new_secret_hash } => {
+            execute_update_secret_hash(deps, info, new_secret_hash)
+        }
+        ExecuteMsg::ClaimWithdrawn {} => execute_claim_withdrawn(deps, info),
+        ExecuteMsg::ExtendTimelock { extension } => execute_extend_timelock(deps, env, info, extension),
+        ExecuteMsg::Rescue { denom, cw20_contract } => execute_rescue(deps, env, info, denom, cw20_contract),
     }
 }
 
@@ -90,21 +233,89 @@ pub fn execute_deposit(
     let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
     if escrow_info.status != EscrowStatus::Active {
-        return Err(ContractError::AlreadyWithdrawn {});
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
     }
 
-    if info.sender != escrow_info.maker {
+    if info.sender != escrow_info.maker && !is_resolver(deps.storage, &info.sender)? {
         return Err(ContractError::Unauthorized {});
     }
 
-    if info.funds.len() != 1 {
+    let case_insensitive = escrow_info.case_insensitive_denoms;
+
+    // Split the safety deposit out of the funds; whatever remains is the principal
+    let mut principal = vec![];
+    let mut safety_deposit_received = None;
+    for coin in &info.funds {
+        match &escrow_info.safety_deposit {
+            Some(safety_deposit)
+                if !escrow_info.safety_deposit_funded
+                    && safety_deposit_received.is_none()
+                    && coin.amount == safety_deposit.amount
+                    && denoms_match(&safety_deposit.denom, &coin.denom, case_insensitive) =>
+            {
+                safety_deposit_received = Some(coin.denom.clone());
+            }
+            _ => principal.push(coin),
+        }
+    }
+
+    if escrow_info.safety_deposit.is_some()
+        && !escrow_info.safety_deposit_funded
+        && safety_deposit_received.is_none()
+    {
+        return Err(ContractError::MissingSafetyDeposit {});
+    }
+
+    // A basket must be deposited in full, one coin per asset
+    if let Some(basket) = escrow_info.basket.as_mut() {
+        if principal.len() != basket.len() {
+            return Err(ContractError::InvalidBasket {});
+        }
+        for asset in basket.iter_mut() {
+            let coin = principal
+                .iter()
+                .find(|c| denoms_match(&asset.denom, &c.denom, case_insensitive))
+                .ok_or(ContractError::DenomMismatch {})?;
+            if coin.amount != asset.amount {
+                return Err(ContractError::InvalidBasket {});
+            }
+            // Payouts go out in the denom the bank actually holds
+            asset.denom = coin.denom.clone();
+        }
+        principal.retain(|c| c.denom == basket[0].denom);
+    }
+
+    if principal.len() != 1 {
         return Err(ContractError::InsufficientFunds {});
     }
 
-    let coin = &info.funds[0];
-    escrow_info.deposited_amount = coin.amount;
+    let coin = principal[0];
+
+    // Repeat deposits top up the escrow in the asset it already holds
+    if !escrow_info.deposited_amount.is_zero() {
+        if escrow_info.basket.is_some() {
+            return Err(ContractError::AlreadyFunded {});
+        }
+        if escrow_info.deposited_denom.as_deref() != Some(coin.denom.as_str()) {
+            return Err(ContractError::DepositDenomMismatch {});
+        }
+    }
+
+    let deposited = escrow_info.deposited_amount.checked_add(coin.amount).map_err(StdError::from)?;
+    if deposited > escrow_info.src_amount.unwrap_or(escrow_info.dst_amount) {
+        return Err(ContractError::DepositMismatch {});
+    }
+    validate_minimum_fill(escrow_info.allow_partial_fill, escrow_info.minimum_fill_amount, deposited)?;
+
+    escrow_info.deposited_amount = deposited;
     escrow_info.deposited_denom = Some(coin.denom.clone());
-    escrow_info.remaining_amount = coin.amount;
+    escrow_info.remaining_amount += coin.amount;
+    if let (Some(denom), Some(safety_deposit)) = (safety_deposit_received, escrow_info.safety_deposit.as_mut()) {
+        safety_deposit.denom = denom;
+        escrow_info.safety_deposit_funded = true;
+    }
 
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
 
@@ -129,16 +340,41 @@ pub fn execute_receive(
             let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
             if escrow_info.status != EscrowStatus::Active {
-                return Err(ContractError::AlreadyWithdrawn {});
+                return Err(ContractError::InvalidState {
+                    current: format!("{:?}", escrow_info.status),
+                });
             }
 
             if sender != escrow_info.maker {
                 return Err(ContractError::Unauthorized {});
             }
 
-            escrow_info.deposited_amount = amount;
+            if escrow_info.basket.is_some() {
+                return Err(ContractError::InvalidBasket {});
+            }
+
+            // Only whitelisted CW20 contracts may fund the escrow, when a whitelist is set
+            if let Some(allowed_cw20) = &escrow_info.allowed_cw20 {
+                if !allowed_cw20.contains(&info.sender) {
+                    return Err(ContractError::Cw20NotAllowed {});
+                }
+            }
+            ensure_callback_isolated(&escrow_info, &info.sender)?;
+
+            // Repeat deposits top up the escrow in the token it already holds, as native ones do
+            if !escrow_info.deposited_amount.is_zero() && escrow_info.cw20_contract.as_ref() != Some(&info.sender) {
+                return Err(ContractError::DepositDenomMismatch {});
+            }
+
+            let deposited = escrow_info.deposited_amount.checked_add(amount).map_err(StdError::from)?;
+            if deposited > escrow_info.src_amount.unwrap_or(escrow_info.dst_amount) {
+                return Err(ContractError::DepositMismatch {});
+            }
+            validate_minimum_fill(escrow_info.allow_partial_fill, escrow_info.minimum_fill_amount, deposited)?;
+
+            escrow_info.deposited_amount = deposited;
             escrow_info.cw20_contract = Some(info.sender);
-            escrow_info.remaining_amount = amount;
+            escrow_info.remaining_amount += amount;
 
             ESCROW_INFO.save(deps.storage, &escrow_info)?;
 
@@ -150,9 +386,73 @@ pub fn execute_receive(
     }
 }
 
+pub fn execute_deposit_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_contract: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if escrow_info.status != EscrowStatus::Active {
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
+    }
+
+    if info.sender != escrow_info.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if PENDING_DEPOSIT.exists(deps.storage) {
+        return Err(ContractError::AlreadyFunded {});
+    }
+
+    if escrow_info.basket.is_some() {
+        return Err(ContractError::InvalidBasket {});
+    }
+
+    let cw20_contract = deps.api.addr_validate(&cw20_contract)?;
+    if let Some(allowed_cw20) = &escrow_info.allowed_cw20 {
+        if !allowed_cw20.contains(&cw20_contract) {
+            return Err(ContractError::Cw20NotAllowed {});
+        }
+    }
+    ensure_callback_isolated(&escrow_info, &cw20_contract)?;
+
+    // Pulls top up the escrow like direct deposits, in the token it already holds
+    if !escrow_info.deposited_amount.is_zero() && escrow_info.cw20_contract.as_ref() != Some(&cw20_contract) {
+        return Err(ContractError::DepositDenomMismatch {});
+    }
+    let deposited = escrow_info.deposited_amount.checked_add(amount).map_err(StdError::from)?;
+    if deposited > escrow_info.src_amount.unwrap_or(escrow_info.dst_amount) {
+        return Err(ContractError::DepositMismatch {});
+    }
+    validate_minimum_fill(escrow_info.allow_partial_fill, escrow_info.minimum_fill_amount, deposited)?;
+
+    // The deposit is only recorded once the transfer has gone through
+    PENDING_DEPOSIT.save(deps.storage, &(cw20_contract.clone(), amount))?;
+    let transfer = WasmMsg::Execute {
+        contract_addr: cw20_contract.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: escrow_info.maker.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(transfer, DEPOSIT_REPLY_ID))
+        .add_attribute("method", "deposit_from")
+        .add_attribute("cw20_contract", cw20_contract)
+        .add_attribute("amount", amount))
+}
+
 pub fn execute_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     secret: String,
 ) -> Result<Response, ContractError> {
@@ -166,9 +466,11 @@ pub fn execute_withdraw(
         return Err(ContractError::AlreadyCancelled {});
     }
 
+    ensure_fully_funded(&escrow_info)?;
+    ensure_withdrawal_window(&escrow_info, &info.sender, env.block.time.seconds())?;
+
     // Verify secret hash
-    let secret_hash = format!("{:x}", sha2::Sha256::digest(secret.as_bytes()));
-    if secret_hash != escrow_info.secret_hash {
+    if hash_secret(&escrow_info, &secret) != escrow_info.secret_hash.as_slice() {
         return Err(ContractError::InvalidSecret {});
     }
 
@@ -181,43 +483,66 @@ pub fn execute_withdraw(
     let mut messages = vec![];
 
     // Transfer tokens to taker or sender
-    let recipient = escrow_info.taker.as_ref().unwrap_or(&info.sender);
-    
-    if let Some(cw20_contract) = &escrow_info.cw20_contract {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: cw20_contract.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: recipient.to_string(),
-                amount: withdraw_amount,
-            })?,
-            funds: vec![],
-        }));
-    } else if let Some(denom) = &escrow_info.deposited_denom {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: recipient.to_string(),
-            amount: vec![cosmwasm_std::Coin {
-                denom: denom.clone(),
-                amount: withdraw_amount,
-            }],
-        }));
+    let recipient = escrow_info.taker.clone().unwrap_or_else(|| info.sender.clone());
+    ensure_whitelisted(&escrow_info, &recipient)?;
+    let payout = match release_basket(&mut escrow_info, withdraw_amount, true, &recipient) {
+        Some(msg) => vec![SubMsg::new(msg)],
+        None => parked_on_failure(deps.storage, &escrow_info, &env, &recipient, withdraw_amount)?,
+    };
+
+    // Safety deposit goes to whoever executed the withdrawal
+    if let Some(msg) = safety_deposit_payout(&mut escrow_info, &info.sender, true)? {
+        messages.push(msg);
     }
+    messages.extend(completion_callback(&escrow_info));
 
+    let from_status = escrow_info.status.clone();
     escrow_info.status = EscrowStatus::Withdrawn;
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    record_transition(deps.storage, &env, Some(from_status), EscrowStatus::Withdrawn, &info.sender)?;
 
+    let secret_hash = encode_secret_hash(&escrow_info.secret_hash);
     Ok(Response::new()
+        .add_submessages(payout)
         .add_messages(messages)
+        .add_event(secret_revealed_event(&secret, &secret_hash))
         .add_attribute("method", "withdraw")
         .add_attribute("recipient", recipient)
-        .add_attribute("amount", withdraw_amount))
+        .add_attribute("amount", withdraw_amount)
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", secret_hash))
 }
 
 pub fn execute_partial_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
+    secret: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    partial_withdraw(deps, env, info, secret, amount, None)
+}
+
+pub fn execute_partial_withdraw_merkle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    secret: String,
+    merkle_proof: Vec<String>,
+    index: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    partial_withdraw(deps, env, info, secret, amount, Some((merkle_proof, index)))
+}
+
+/// Shared partial fill; `merkle` carries the proof and index when the secret is one of the Merkle leaves.
+fn partial_withdraw(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     secret: String,
     amount: Uint128,
+    merkle: Option<(Vec<String>, u64)>,
 ) -> Result<Response, ContractError> {
     let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
 
@@ -233,64 +558,110 @@ pub fn execute_partial_withdraw(
         return Err(ContractError::AlreadyCancelled {});
     }
 
+    ensure_fully_funded(&escrow_info)?;
+    ensure_withdrawal_window(&escrow_info, &info.sender, env.block.time.seconds())?;
+
     if amount > escrow_info.remaining_amount {
         return Err(ContractError::InsufficientFunds {});
     }
 
-    if let Some(min_fill) = escrow_info.minimum_fill_amount {
+    // A dust remainder must be cleared in one fill, regardless of the minimum fill size
+    let clearing_dust = escrow_info
+        .dust_threshold
+        .map_or(false, |dust| escrow_info.remaining_amount <= dust);
+    if clearing_dust {
+        if amount != escrow_info.remaining_amount {
+            return Err(ContractError::InvalidPartialFillAmount {});
+        }
+    } else if let Some(min_fill) = escrow_info.minimum_fill_amount {
         if amount < min_fill {
             return Err(ContractError::InvalidPartialFillAmount {});
         }
     }
 
-    // Verify secret hash
-    let secret_hash = format!("{:x}", sha2::Sha256::digest(secret.as_bytes()));
-    if secret_hash != escrow_info.secret_hash {
-        return Err(ContractError::InvalidSecret {});
+    // Fills stop once the live decay passes the floor, rather than executing at a clamped price
+    if let (Some(min_price), Some(price)) =
+        (escrow_info.minimum_price, implied_price(&escrow_info, env.block.time.seconds()))
+    {
+        if price < min_price {
+            return Err(ContractError::MinimumPriceReached {});
+        }
+    }
+
+    // Verify the secret against the single hashlock or its leaf in the Merkle tree
+    let index = match (&escrow_info.merkle_root, merkle) {
+        (None, None) => {
+            if hash_secret(&escrow_info, &secret) != escrow_info.secret_hash.as_slice() {
+                return Err(ContractError::InvalidSecret {});
+            }
+            None
+        }
+        (Some(_), None) => return Err(ContractError::MerkleProofRequired {}),
+        (None, Some(_)) => return Err(ContractError::MerkleRootNotSet {}),
+        (Some(root), Some((proof, index))) => {
+            if escrow_info.cancelled_tranches.contains(&index) {
+                return Err(ContractError::TrancheCancelled {});
+            }
+            if escrow_info.last_used_index.map_or(false, |last| index <= last) {
+                return Err(ContractError::SecretIndexReused {});
+            }
+            let leaf = merkle_leaf(&escrow_info, index, &secret);
+            if merkle_root_from_proof(&escrow_info, leaf, &proof)? != root.as_slice() {
+                return Err(ContractError::InvalidMerkleProof {});
+            }
+            Some(index)
+        }
+    };
+    if index.is_some() {
+        escrow_info.last_used_index = index;
     }
 
     let mut messages = vec![];
 
     // Transfer tokens to taker or sender
-    let recipient = escrow_info.taker.as_ref().unwrap_or(&info.sender);
-    
-    if let Some(cw20_contract) = &escrow_info.cw20_contract {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: cw20_contract.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: recipient.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        }));
-    } else if let Some(denom) = &escrow_info.deposited_denom {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: recipient.to_string(),
-            amount: vec![cosmwasm_std::Coin {
-                denom: denom.clone(),
-                amount,
-            }],
-        }));
-    }
+    let recipient = escrow_info.taker.clone().unwrap_or_else(|| info.sender.clone());
+    ensure_whitelisted(&escrow_info, &recipient)?;
+    let closing = amount == escrow_info.remaining_amount;
+    let payout = match release_basket(&mut escrow_info, amount, closing, &recipient) {
+        Some(msg) => vec![SubMsg::new(msg)],
+        None => parked_on_failure(deps.storage, &escrow_info, &env, &recipient, amount)?,
+    };
 
     // Update escrow state
     escrow_info.filled_amount += amount;
     escrow_info.remaining_amount -= amount;
 
+    let from_status = escrow_info.status.clone();
     if escrow_info.remaining_amount.is_zero() {
         escrow_info.status = EscrowStatus::Withdrawn;
+        if let Some(msg) = safety_deposit_payout(&mut escrow_info, &info.sender, true)? {
+            messages.push(msg);
+        }
+        messages.extend(completion_callback(&escrow_info));
     } else {
         escrow_info.status = EscrowStatus::PartiallyFilled;
     }
 
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    if escrow_info.status != from_status {
+        record_transition(deps.storage, &env, Some(from_status), escrow_info.status.clone(), &info.sender)?;
+    }
 
-    Ok(Response::new()
+    let secret_hash = encode_secret_hash(&Binary::from(hash_secret(&escrow_info, &secret)));
+    let mut response = Response::new()
+        .add_submessages(payout)
         .add_messages(messages)
-        .add_attribute("method", "partial_withdraw")
+        .add_event(secret_revealed_event(&secret, &secret_hash))
+        .add_attribute("method", if index.is_some() { "partial_withdraw_merkle" } else { "partial_withdraw" })
         .add_attribute("recipient", recipient)
         .add_attribute("amount", amount)
-        .add_attribute("remaining", escrow_info.remaining_amount))
+        .add_attribute("remaining", escrow_info.remaining_amount)
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", secret_hash);
+    if let Some(index) = index {
+        response = response.add_attribute("index", index.to_string());
+    }
+    Ok(response)
 }
 
 pub fn execute_cancel(
@@ -308,46 +679,178 @@ pub fn execute_cancel(
         return Err(ContractError::AlreadyCancelled {});
     }
 
-    if info.sender != escrow_info.maker {
+    // The factory can unwind an escrow early during an emergency
+    let emergency = FACTORY.may_load(deps.storage)?.map_or(false, |factory| info.sender == factory);
+    let resolver = is_resolver(deps.storage, &info.sender)?;
+
+    if info.sender != escrow_info.maker && !resolver && !emergency {
         return Err(ContractError::Unauthorized {});
     }
 
-    if env.block.time.seconds() < escrow_info.timelock {
+    // An unfunded escrow holds nothing to refund, so its resolver may retire it before the timelock
+    let retiring = resolver && escrow_info.deposited_amount.is_zero() && !escrow_info.safety_deposit_funded;
+    if env.block.time.seconds() < cancel_at(&escrow_info) && !emergency && !retiring {
         return Err(ContractError::TimelockNotExpired {});
     }
 
-    let mut messages = vec![];
+    // Return remaining tokens to the refund address (or maker)
+    let return_amount = escrow_info.remaining_amount;
+    let refund_recipient = escrow_info.refund_addr.clone().unwrap_or_else(|| escrow_info.maker.clone());
+    let messages = cancel_messages(&mut escrow_info)?;
+
+    let from_status = escrow_info.status.clone();
+    escrow_info.status = EscrowStatus::Cancelled;
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+    record_transition(deps.storage, &env, Some(from_status), EscrowStatus::Cancelled, &info.sender)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "cancel")
+        .add_attribute("maker", escrow_info.maker.clone())
+        .add_attribute("refund_recipient", refund_recipient)
+        .add_attribute("returned_amount", return_amount))
+}
 
-    // Return remaining tokens to maker
+/// Messages a cancel emits: the unfilled principal to the refund recipient, then the safety deposit
+/// back to the maker. Marks the safety deposit as paid out on `escrow_info`.
+fn cancel_messages(escrow_info: &mut EscrowInfo) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
     let return_amount = escrow_info.remaining_amount;
-    
+    let refund_recipient = escrow_info.refund_addr.clone().unwrap_or_else(|| escrow_info.maker.clone());
+
+    if let Some(basket) = &escrow_info.basket {
+        let refund: Vec<Coin> = basket
+            .iter()
+            .filter(|asset| asset.amount > asset.filled_amount)
+            .map(|asset| Coin {
+                denom: asset.denom.clone(),
+                amount: asset.amount - asset.filled_amount,
+            })
+            .collect();
+        if !escrow_info.deposited_amount.is_zero() && !refund.is_empty() {
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: refund_recipient.to_string(),
+                amount: refund,
+            }));
+        }
+    } else if let Some(msg) = principal_refund(escrow_info, &refund_recipient, return_amount)? {
+        messages.push(msg);
+    }
+
+    // Safety deposit returns to the maker on cancellation
+    let maker = escrow_info.maker.clone();
+    if let Some(msg) = safety_deposit_payout(escrow_info, &maker, false)? {
+        messages.push(msg);
+    }
+    Ok(messages)
+}
+
+/// Transfer of `amount` of the single deposited asset, CW20 or native, to `recipient`.
+fn principal_refund(escrow_info: &EscrowInfo, recipient: &Addr, amount: Uint128) -> StdResult<Option<CosmosMsg>> {
     if let Some(cw20_contract) = &escrow_info.cw20_contract {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        return Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: cw20_contract.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: escrow_info.maker.to_string(),
-                amount: return_amount,
+                recipient: recipient.to_string(),
+                amount,
             })?,
             funds: vec![],
-        }));
-    } else if let Some(denom) = &escrow_info.deposited_denom {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: escrow_info.maker.to_string(),
+        })));
+    }
+    Ok(escrow_info.deposited_denom.as_ref().map(|denom| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
             amount: vec![cosmwasm_std::Coin {
                 denom: denom.clone(),
-                amount: return_amount,
+                amount,
             }],
-        }));
+        })
+    }))
+}
+
+pub fn execute_cancel_tranches(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mut indices: Vec<u64>,
+) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if info.sender != escrow_info.maker {
+        return Err(ContractError::Unauthorized {});
     }
 
-    escrow_info.status = EscrowStatus::Cancelled;
+    if escrow_info.status == EscrowStatus::Withdrawn {
+        return Err(ContractError::AlreadyWithdrawn {});
+    }
+
+    if escrow_info.status == EscrowStatus::Cancelled {
+        return Err(ContractError::AlreadyCancelled {});
+    }
+
+    if env.block.time.seconds() < cancel_at(&escrow_info) {
+        return Err(ContractError::TimelockNotExpired {});
+    }
+
+    let count = escrow_info.tranche_count.ok_or(ContractError::MerkleRootNotSet {})?;
+    if escrow_info.deposited_amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.is_empty() || indices.iter().any(|index| *index >= count) {
+        return Err(ContractError::InvalidTranche {});
+    }
+    for index in &indices {
+        if escrow_info.last_used_index.map_or(false, |last| *index <= last) {
+            return Err(ContractError::TrancheAlreadyUsed {});
+        }
+        if escrow_info.cancelled_tranches.contains(index) {
+            return Err(ContractError::TrancheCancelled {});
+        }
+    }
+
+    // Earlier fills may have taken more than their tranche, so never refund past what is left
+    let refund = indices
+        .iter()
+        .map(|index| tranche_amount(escrow_info.deposited_amount, *index, count))
+        .fold(Uint128::zero(), |total, amount| total + amount)
+        .min(escrow_info.remaining_amount);
+    escrow_info.remaining_amount -= refund;
+    escrow_info.cancelled_tranches.extend(indices.iter().copied());
+    escrow_info.cancelled_tranches.sort_unstable();
+
+    let mut messages = vec![];
+    let refund_recipient = escrow_info.refund_addr.clone().unwrap_or_else(|| escrow_info.maker.clone());
+    if !refund.is_zero() {
+        messages.extend(principal_refund(&escrow_info, &refund_recipient, refund)?);
+    }
+
+    // Cancelling the last open tranches settles the escrow like a full cancel
+    if escrow_info.remaining_amount.is_zero() {
+        let maker = escrow_info.maker.clone();
+        if let Some(msg) = safety_deposit_payout(&mut escrow_info, &maker, false)? {
+            messages.push(msg);
+        }
+        let from_status = escrow_info.status.clone();
+        escrow_info.status = EscrowStatus::Cancelled;
+        record_transition(deps.storage, &env, Some(from_status), EscrowStatus::Cancelled, &info.sender)?;
+    }
     ESCROW_INFO.save(deps.storage, &escrow_info)?;
 
+    let indices: Vec<String> = indices.iter().map(|index| index.to_string()).collect();
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "cancel")
-        .add_attribute("maker", escrow_info.maker)
-        .add_attribute("returned_amount", return_amount))
+        .add_attribute("method", "cancel_tranches")
+        .add_attribute("indices", indices.join(","))
+        .add_attribute("refund_recipient", refund_recipient)
+        .add_attribute("refunded_amount", refund))
+}
+
+/// Share of the deposit behind tranche `index` of `count`; rounding leftovers fall to the later tranches.
+fn tranche_amount(deposited: Uint128, index: u64, count: u64) -> Uint128 {
+    deposited.multiply_ratio(index + 1, count) - deposited.multiply_ratio(index, count)
 }
 
 pub fn execute_update_price(
@@ -364,39 +867,378 @@ pub fn execute_update_price(
         .add_attribute("current_price", current_price))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Escrow {} => to_binary(&query_escrow(deps)?),
-        QueryMsg::CurrentPrice {} => to_binary(&query_current_price(deps, env)?),
-        QueryMsg::FillStatus {} => to_binary(&query_fill_status(deps)?),
+pub fn execute_rest_at_minimum(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if info.sender != escrow_info.maker && !is_resolver(deps.storage, &info.sender)? {
+        return Err(ContractError::Unauthorized {});
     }
-}
+    if !matches!(escrow_info.status, EscrowStatus::Active | EscrowStatus::PartiallyFilled) {
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
+    }
+    let minimum_price = escrow_info.minimum_price.ok_or(ContractError::InvalidDutchAuctionParams {})?;
 
-fn query_escrow(deps: Deps) -> StdResult<EscrowResponse> {
-    let escrow_info = ESCROW_INFO.load(deps.storage)?;
-    Ok(EscrowResponse {
-        maker: escrow_info.maker,
-        taker: escrow_info.taker,
-        secret_hash: escrow_info.secret_hash,
-        timelock: escrow_info.timelock,
-        dst_chain_id: escrow_info.dst_chain_id,
-        dst_asset: escrow_info.dst_asset,
-        dst_amount: escrow_info.dst_amount,
-        deposited_amount: escrow_info.deposited_amount,
-        deposited_denom: escrow_info.deposited_denom,
-        cw20_contract: escrow_info.cw20_contract,
-        status: escrow_info.status,
-        created_at: escrow_info.created_at,
-        allow_partial_fill: escrow_info.allow_partial_fill,
-        filled_amount: escrow_info.filled_amount,
-        remaining_amount: escrow_info.remaining_amount,
-    })
+    // Starting at the floor with no decay keeps fills open at the minimum instead of tripping the floor check
+    escrow_info.initial_price = Some(minimum_price);
+    escrow_info.price_decay_rate = Some(Uint128::zero());
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "rest_at_minimum")
+        .add_attribute("price", minimum_price))
 }
 
-fn query_current_price(deps: Deps, env: Env) -> StdResult<PriceResponse> {
-    let escrow_info = ESCROW_INFO.load(deps.storage)?;
-    let current_time = env.block.time.seconds();
+pub fn execute_update_secret_hash(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_secret_hash: String,
+) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    // The resolver forwards rotations the maker requests through it
+    if info.sender != escrow_info.maker && !is_resolver(deps.storage, &info.sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // The hashlock can only be rotated before any funds are locked against it
+    if escrow_info.status != EscrowStatus::Active || !escrow_info.deposited_amount.is_zero() {
+        return Err(ContractError::AlreadyFunded {});
+    }
+
+    escrow_info.secret_hash = decode_secret_hash(&new_secret_hash)?;
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_secret_hash")
+        .add_attribute("secret_hash", new_secret_hash))
+}
+
+pub fn execute_extend_timelock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    extension: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if info.sender != escrow_info.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !matches!(escrow_info.status, EscrowStatus::Active | EscrowStatus::PartiallyFilled) {
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
+    }
+    if extension == 0 {
+        return Err(ContractError::InvalidTimelock {});
+    }
+
+    // The cap bounds the sum of all extensions, so repeated small extensions cannot outrun it
+    let total_extension = escrow_info.total_extension.saturating_add(extension);
+    if total_extension > escrow_info.max_total_extension {
+        return Err(ContractError::ExtensionCapExceeded {});
+    }
+    // The public window moves with the cancellation stage so no gap opens between them
+    escrow_info.timelocks.public_withdrawal = escrow_info.timelocks.public_withdrawal.saturating_add(extension);
+    escrow_info.timelocks.cancellation = escrow_info.timelocks.cancellation.saturating_add(extension);
+    let timelock = cancel_at(&escrow_info);
+    validate_timelock(timelock, env.block.time.seconds())?;
+
+    escrow_info.total_extension = total_extension;
+    ESCROW_INFO.save(deps.storage, &escrow_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "extend_timelock")
+        .add_attribute("timelock", timelock.to_string())
+        .add_attribute("total_extension", total_extension.to_string()))
+}
+
+pub fn execute_rescue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: Option<String>,
+    cw20_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+
+    if info.sender != escrow_info.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !matches!(escrow_info.status, EscrowStatus::Withdrawn | EscrowStatus::Cancelled) {
+        return Err(ContractError::InvalidState {
+            current: format!("{:?}", escrow_info.status),
+        });
+    }
+
+    // Tracked assets may still back a parked claim, so only untracked balances are swept
+    let (msg, asset, amount) = match (denom, cw20_contract) {
+        (Some(denom), None) => {
+            let tracked = escrow_info.deposited_denom.as_deref() == Some(denom.as_str())
+                || escrow_info.basket.iter().flatten().any(|asset| asset.denom == denom)
+                || escrow_info.safety_deposit.as_ref().map_or(false, |coin| coin.denom == denom);
+            if tracked {
+                return Err(ContractError::TrackedAssetNotRescuable {});
+            }
+            let balance = deps.querier.query_balance(&env.contract.address, &denom)?;
+            let amount = balance.amount;
+            let msg = CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![balance],
+            });
+            (msg, denom, amount)
+        }
+        (None, Some(cw20_contract)) => {
+            let cw20_addr = deps.api.addr_validate(&cw20_contract)?;
+            if escrow_info.cw20_contract.as_ref() == Some(&cw20_addr) {
+                return Err(ContractError::TrackedAssetNotRescuable {});
+            }
+            let balance: BalanceResponse = deps.querier.query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+            )?;
+            let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: cw20_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: balance.balance,
+                })?,
+                funds: vec![],
+            });
+            (msg, cw20_addr.to_string(), balance.balance)
+        }
+        _ => return Err(ContractError::InvalidRescueTarget {}),
+    };
+
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "rescue")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("asset", asset)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_claim_withdrawn(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let amount = CLAIMABLE.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    CLAIMABLE.remove(deps.storage, &info.sender);
+
+    // Claims are pulled by the recipient directly, without IBC forwarding
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let msg = if let Some(cw20_contract) = &escrow_info.cw20_contract {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })
+    } else {
+        let denom = escrow_info.deposited_denom.ok_or(ContractError::NothingToClaim {})?;
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin { denom, amount }],
+        })
+    };
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("method", "claim_withdrawn")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", amount))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Earlier versions stored the hashlock as a hex string; re-encode it as raw bytes
+    let legacy = LEGACY_ESCROW_INFO.load(deps.storage)?;
+    let (secret_hash, converted) = match decode_secret_hash(&legacy.secret_hash) {
+        Ok(secret_hash) => (secret_hash, true),
+        // Already raw bytes, which serialize as base64
+        Err(_) => (Binary::from_base64(&legacy.secret_hash)?, false),
+    };
+    ESCROW_INFO.save(deps.storage, &upgrade_escrow_info(legacy, secret_hash)?)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("secret_hash_converted", converted.to_string()))
+}
+
+/// Fills in what a legacy record predates with the values instantiate would have chosen for it
+fn upgrade_escrow_info(legacy: EscrowInfoV1, secret_hash: Binary) -> Result<EscrowInfo, ContractError> {
+    let timelocks = match (legacy.timelocks, legacy.timelock) {
+        (Some(timelocks), _) => timelocks,
+        (None, Some(timelock)) => Timelocks::from_timelock(legacy.created_at, timelock),
+        (None, None) => return Err(StdError::not_found("timelocks").into()),
+    };
+    Ok(EscrowInfo {
+        maker: legacy.maker,
+        taker: legacy.taker,
+        refund_addr: legacy.refund_addr,
+        secret_hash,
+        timelocks,
+        dst_chain_id: legacy.dst_chain_id,
+        dst_asset: legacy.dst_asset,
+        dst_amount: legacy.dst_amount,
+        src_amount: legacy.src_amount,
+        deposited_amount: legacy.deposited_amount,
+        deposited_denom: legacy.deposited_denom,
+        cw20_contract: legacy.cw20_contract,
+        allowed_cw20: legacy.allowed_cw20,
+        status: legacy.status,
+        created_at: legacy.created_at,
+        initial_price: legacy.initial_price,
+        price_decay_rate: legacy.price_decay_rate,
+        minimum_price: legacy.minimum_price,
+        allow_partial_fill: legacy.allow_partial_fill,
+        minimum_fill_amount: legacy.minimum_fill_amount,
+        filled_amount: legacy.filled_amount,
+        remaining_amount: legacy.remaining_amount,
+        dust_threshold: legacy.dust_threshold,
+        safety_deposit: legacy.safety_deposit,
+        safety_deposit_swap: legacy.safety_deposit_swap,
+        safety_deposit_funded: legacy.safety_deposit_funded,
+        ibc_forward: legacy.ibc_forward,
+        basket: legacy.basket,
+        withdraw_whitelist: legacy.withdraw_whitelist,
+        on_complete_msg: legacy.on_complete_msg,
+        max_total_extension: legacy.max_total_extension,
+        total_extension: legacy.total_extension,
+        hash_algorithm: legacy.hash_algorithm,
+        case_insensitive_denoms: legacy.case_insensitive_denoms,
+        merkle_root: legacy.merkle_root,
+        last_used_index: legacy.last_used_index,
+        tranche_count: legacy.tranche_count,
+        cancelled_tranches: legacy.cancelled_tranches,
+        price_decimals: legacy.price_decimals.unwrap_or(DEFAULT_PRICE_DECIMALS),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        PAYOUT_REPLY_ID => {
+            let (recipient, amount) = PENDING_PAYOUT.load(deps.storage)?;
+            PENDING_PAYOUT.remove(deps.storage);
+
+            let error = match msg.result {
+                SubMsgResult::Err(error) => error,
+                SubMsgResult::Ok(_) => return Ok(Response::new()),
+            };
+            // The secret is already public, so keep the withdrawal and let the recipient pull later
+            CLAIMABLE.update(deps.storage, &recipient, |claimable| -> StdResult<_> {
+                Ok(claimable.unwrap_or_default() + amount)
+            })?;
+
+            Ok(Response::new()
+                .add_attribute("method", "park_payout")
+                .add_attribute("recipient", recipient)
+                .add_attribute("amount", amount)
+                .add_attribute("error", error))
+        }
+        DEPOSIT_REPLY_ID => {
+            let (cw20_contract, amount) = PENDING_DEPOSIT.load(deps.storage)?;
+            PENDING_DEPOSIT.remove(deps.storage);
+
+            let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+            escrow_info.deposited_amount += amount;
+            escrow_info.cw20_contract = Some(cw20_contract.clone());
+            escrow_info.remaining_amount += amount;
+            ESCROW_INFO.save(deps.storage, &escrow_info)?;
+
+            Ok(Response::new()
+                .add_attribute("method", "confirm_deposit_from")
+                .add_attribute("cw20_contract", cw20_contract)
+                .add_attribute("amount", amount))
+        }
+        id => Err(StdError::generic_err(format!("unknown reply id: {}", id)).into()),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Escrow {} => to_binary(&query_escrow(deps)?),
+        QueryMsg::History { start_after, limit } => {
+            let history: HistoryResponse = query_history(deps.storage, start_after, limit)?;
+            to_binary(&history)
+        }
+        QueryMsg::CurrentPrice {} => to_binary(&query_current_price(deps, env)?),
+        QueryMsg::FillStatus {} => to_binary(&query_fill_status(deps)?),
+        QueryMsg::CheckSecret { secret } => to_binary(&query_check_secret(deps, secret)?),
+        QueryMsg::CounterpartyParams {} => to_binary(&query_counterparty_params(deps)?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(deps, address)?),
+        QueryMsg::RefundQuote {} => to_binary(&query_refund_quote(deps, env)?),
+        QueryMsg::PreviewCancel {} => to_binary(&query_preview_cancel(deps, env)?),
+    }
+}
+
+fn query_escrow(deps: Deps) -> StdResult<EscrowResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    Ok(EscrowResponse {
+        maker: escrow_info.maker,
+        taker: escrow_info.taker,
+        refund_addr: escrow_info.refund_addr,
+        secret_hash: encode_secret_hash(&escrow_info.secret_hash),
+        timelock: cancel_at(&escrow_info),
+        timelocks: escrow_info.timelocks,
+        dst_chain_id: escrow_info.dst_chain_id,
+        dst_asset: escrow_info.dst_asset,
+        dst_amount: escrow_info.dst_amount,
+        src_amount: escrow_info.src_amount,
+        deposited_amount: escrow_info.deposited_amount,
+        deposited_denom: escrow_info.deposited_denom,
+        cw20_contract: escrow_info.cw20_contract,
+        allowed_cw20: escrow_info.allowed_cw20,
+        status: escrow_info.status,
+        created_at: escrow_info.created_at,
+        allow_partial_fill: escrow_info.allow_partial_fill,
+        filled_amount: escrow_info.filled_amount,
+        remaining_amount: escrow_info.remaining_amount,
+        dust_threshold: escrow_info.dust_threshold,
+        safety_deposit: escrow_info.safety_deposit,
+        safety_deposit_swap: escrow_info.safety_deposit_swap,
+        safety_deposit_funded: escrow_info.safety_deposit_funded,
+        ibc_forward: escrow_info.ibc_forward,
+        basket: escrow_info.basket.map(|basket| {
+            basket
+                .into_iter()
+                .map(|asset| AssetInfo {
+                    denom: asset.denom,
+                    amount: asset.amount,
+                })
+                .collect()
+        }),
+        withdraw_whitelist: escrow_info.withdraw_whitelist,
+        on_complete_msg: escrow_info.on_complete_msg,
+        max_total_extension: escrow_info.max_total_extension,
+        total_extension: escrow_info.total_extension,
+        hash_algorithm: escrow_info.hash_algorithm,
+        case_insensitive_denoms: escrow_info.case_insensitive_denoms,
+        merkle_root: escrow_info.merkle_root.as_ref().map(encode_secret_hash),
+        last_used_index: escrow_info.last_used_index,
+        tranche_count: escrow_info.tranche_count,
+        cancelled_tranches: escrow_info.cancelled_tranches,
+    })
+}
+
+fn query_current_price(deps: Deps, env: Env) -> StdResult<PriceResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
     
     let current_price = calculate_current_price(&escrow_info, current_time)
         .unwrap_or(escrow_info.initial_price.unwrap_or(Uint128::zero()));
@@ -406,7 +1248,8 @@ fn query_current_price(deps: Deps, env: Env) -> StdResult<PriceResponse> {
         initial_price: escrow_info.initial_price,
         minimum_price: escrow_info.minimum_price,
         price_decay_rate: escrow_info.price_decay_rate,
-        time_elapsed: current_time - escrow_info.created_at,
+        time_elapsed: current_time.saturating_sub(escrow_info.created_at),
+        price_decimals: escrow_info.price_decimals,
     })
 }
 
@@ -418,16 +1261,353 @@ fn query_fill_status(deps: Deps) -> StdResult<FillStatusResponse> {
         remaining_amount: escrow_info.remaining_amount,
         is_fully_filled: escrow_info.remaining_amount.is_zero(),
         allow_partial_fill: escrow_info.allow_partial_fill,
+        basket: escrow_info
+            .basket
+            .unwrap_or_default()
+            .into_iter()
+            .map(|asset| AssetFillStatus {
+                denom: asset.denom,
+                total_amount: asset.amount,
+                filled_amount: asset.filled_amount,
+                remaining_amount: asset.amount - asset.filled_amount,
+            })
+            .collect(),
+    })
+}
+
+fn query_check_secret(deps: Deps, secret: String) -> StdResult<CheckSecretResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    Ok(CheckSecretResponse {
+        valid: hash_secret(&escrow_info, &secret) == escrow_info.secret_hash.as_slice(),
+    })
+}
+
+fn query_claimable(deps: Deps, address: String) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let amount = CLAIMABLE.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(ClaimableResponse { amount })
+}
+
+fn query_refund_quote(deps: Deps, env: Env) -> StdResult<RefundQuoteResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let settled = matches!(escrow_info.status, EscrowStatus::Withdrawn | EscrowStatus::Cancelled);
+
+    let basket_refund = escrow_info
+        .basket
+        .iter()
+        .flatten()
+        .filter(|asset| !escrow_info.deposited_amount.is_zero() && asset.amount > asset.filled_amount)
+        .map(|asset| Coin {
+            denom: asset.denom.clone(),
+            amount: asset.amount - asset.filled_amount,
+        })
+        .collect();
+    let safety_deposit = escrow_info
+        .safety_deposit
+        .clone()
+        .filter(|_| escrow_info.safety_deposit_funded);
+
+    Ok(RefundQuoteResponse {
+        cancellable: !settled && now >= cancel_at(&escrow_info),
+        seconds_until_cancellable: cancel_at(&escrow_info).saturating_sub(now),
+        recipient: escrow_info.refund_addr.unwrap_or(escrow_info.maker),
+        refund_amount: if settled { Uint128::zero() } else { escrow_info.remaining_amount },
+        basket_refund: if settled { vec![] } else { basket_refund },
+        safety_deposit: if settled { None } else { safety_deposit },
+    })
+}
+
+fn query_preview_cancel(deps: Deps, env: Env) -> StdResult<PreviewCancelResponse> {
+    let mut escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let recipient = escrow_info.refund_addr.clone().unwrap_or_else(|| escrow_info.maker.clone());
+    let settled = matches!(escrow_info.status, EscrowStatus::Withdrawn | EscrowStatus::Cancelled);
+    if settled || env.block.time.seconds() < cancel_at(&escrow_info) {
+        return Ok(PreviewCancelResponse {
+            cancellable: false,
+            recipient,
+            amount: Uint128::zero(),
+            asset: None,
+            messages: vec![],
+        });
+    }
+
+    let asset = if escrow_info.deposited_amount.is_zero() {
+        None
+    } else if escrow_info.basket.is_some() {
+        Some(RefundAsset::Basket {})
+    } else if let Some(contract) = &escrow_info.cw20_contract {
+        Some(RefundAsset::Cw20 { contract: contract.clone() })
+    } else {
+        escrow_info.deposited_denom.clone().map(|denom| RefundAsset::Native { denom })
+    };
+    Ok(PreviewCancelResponse {
+        cancellable: true,
+        recipient,
+        amount: escrow_info.remaining_amount,
+        asset,
+        messages: cancel_messages(&mut escrow_info)?,
+    })
+}
+
+fn query_counterparty_params(deps: Deps) -> StdResult<CounterpartyParamsResponse> {
+    let escrow_info = ESCROW_INFO.load(deps.storage)?;
+    let recipient = deps.api.addr_canonicalize(escrow_info.maker.as_str())?;
+    Ok(CounterpartyParamsResponse {
+        chain_id: escrow_info.dst_chain_id,
+        asset: escrow_info.dst_asset,
+        secret_hash: format!("0x{}", encode_secret_hash(&escrow_info.secret_hash)),
+        timelock: format!("0x{:064x}", cancel_at(&escrow_info)),
+        amount: format!("0x{:064x}", escrow_info.dst_amount.u128()),
+        recipient: format!(
+            "0x{}",
+            recipient.as_slice().iter().map(|b| format!("{:02x}", b)).collect::<String>()
+        ),
+    })
+}
+
+/// Raw hash of a secret under the escrow's algorithm, comparable to the stored `secret_hash`.
+fn hash_secret(escrow_info: &EscrowInfo, secret: &str) -> Vec<u8> {
+    escrow_info.hash_algorithm.digest(secret.as_bytes())
+}
+
+/// Merkle leaf committing the `index`-th secret: `hash(index as big-endian u64 || hash(secret))`.
+fn merkle_leaf(escrow_info: &EscrowInfo, index: u64, secret: &str) -> Vec<u8> {
+    let mut preimage = index.to_be_bytes().to_vec();
+    preimage.extend(hash_secret(escrow_info, secret));
+    escrow_info.hash_algorithm.digest(&preimage)
+}
+
+/// Folds hex sibling hashes into a root, hashing each pair in sorted order so proofs need no direction bits.
+fn merkle_root_from_proof(escrow_info: &EscrowInfo, leaf: Vec<u8>, proof: &[String]) -> Result<Vec<u8>, ContractError> {
+    proof.iter().try_fold(leaf, |node, sibling| {
+        let sibling = decode_secret_hash(sibling).map_err(|_| ContractError::InvalidMerkleProof {})?;
+        let (left, right) = if node.as_slice() <= sibling.as_slice() {
+            (node.as_slice(), sibling.as_slice())
+        } else {
+            (sibling.as_slice(), node.as_slice())
+        };
+        Ok(escrow_info.hash_algorithm.digest(&[left, right].concat()))
     })
 }
 
+/// Decodes a 64-character hex hashlock, with or without a `0x` prefix, into its raw bytes.
+/// Case and prefix are normalised first, since the bytes are what gets compared.
+fn decode_secret_hash(secret_hash: &str) -> Result<Binary, ContractError> {
+    let digits = secret_hash.trim_start_matches("0x").to_ascii_lowercase();
+    validate_secret_hash(&digits)?;
+    let bytes = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| ContractError::InvalidSecretHash {})?;
+    Ok(Binary::from(bytes))
+}
+
+/// Dedicated event relayers filter on to pick up a secret revealed by a withdrawal.
+fn secret_revealed_event(secret: &str, secret_hash: &str) -> Event {
+    Event::new("secret_revealed")
+        .add_attribute("secret", secret)
+        .add_attribute("secret_hash", secret_hash)
+}
+
+/// Lowercase hex form of the stored hashlock.
+fn encode_secret_hash(secret_hash: &Binary) -> String {
+    secret_hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Withdrawal payout as a submessage whose failure parks the funds as claimable instead of reverting.
+fn parked_on_failure(
+    storage: &mut dyn Storage,
+    escrow_info: &EscrowInfo,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let msg = if let Some(cw20_contract) = &escrow_info.cw20_contract {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })
+    } else if let Some(denom) = &escrow_info.deposited_denom {
+        native_payout(escrow_info, env, recipient, Coin {
+            denom: denom.clone(),
+            amount,
+        })
+    } else {
+        return Ok(vec![]);
+    };
+
+    PENDING_PAYOUT.save(storage, &(recipient.clone(), amount))?;
+    Ok(vec![SubMsg::reply_on_error(msg, PAYOUT_REPLY_ID)])
+}
+
+/// Builds the configured completion callback; it never carries funds.
+fn completion_callback(escrow_info: &EscrowInfo) -> Option<CosmosMsg> {
+    escrow_info.on_complete_msg.as_ref().map(|(contract, msg)| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: msg.clone(),
+            funds: vec![],
+        })
+    })
+}
+
+/// Rejects a deposit token the completion callback could use to move the principal.
+fn ensure_callback_isolated(escrow_info: &EscrowInfo, token: &Addr) -> Result<(), ContractError> {
+    match &escrow_info.on_complete_msg {
+        Some((contract, _)) if contract == token => Err(ContractError::InvalidCompletionCallback {}),
+        _ => Ok(()),
+    }
+}
+
+/// Requires stages to run in order: finality, exclusive window, public window, cancellation.
+fn validate_stages(timelocks: &Timelocks) -> Result<(), ContractError> {
+    if timelocks.finality > timelocks.exclusive_withdrawal
+        || timelocks.exclusive_withdrawal > timelocks.public_withdrawal
+        || timelocks.public_withdrawal > timelocks.cancellation
+    {
+        return Err(ContractError::InvalidTimelock {});
+    }
+    Ok(())
+}
+
+fn is_resolver(storage: &dyn Storage, sender: &Addr) -> StdResult<bool> {
+    Ok(RESOLVER.may_load(storage)?.map_or(false, |resolver| *sender == resolver))
+}
+
+/// Absolute time from which the escrow can be cancelled.
+fn cancel_at(escrow_info: &EscrowInfo) -> u64 {
+    escrow_info.created_at.saturating_add(escrow_info.timelocks.cancellation)
+}
+
+/// Rejects withdrawals before finality, by anyone but the taker during the exclusive window, and after
+/// the public window. Escrows without a designated taker have no exclusive window.
+fn ensure_withdrawal_window(escrow_info: &EscrowInfo, sender: &Addr, now: u64) -> Result<(), ContractError> {
+    let elapsed = now.saturating_sub(escrow_info.created_at);
+    let timelocks = &escrow_info.timelocks;
+    if elapsed < timelocks.finality {
+        return Err(ContractError::FinalityNotReached {});
+    }
+    if elapsed < timelocks.exclusive_withdrawal && escrow_info.taker.as_ref().map_or(false, |taker| taker != sender) {
+        return Err(ContractError::ExclusiveWithdrawal {});
+    }
+    if elapsed >= timelocks.public_withdrawal {
+        return Err(ContractError::WithdrawalWindowClosed {});
+    }
+    Ok(())
+}
+
+/// Top-ups may arrive over several deposits, so nothing is released until the expected principal is in.
+fn ensure_fully_funded(escrow_info: &EscrowInfo) -> Result<(), ContractError> {
+    match escrow_info.src_amount {
+        Some(src_amount) if escrow_info.deposited_amount != src_amount => Err(ContractError::Underfunded {}),
+        _ => Ok(()),
+    }
+}
+
+/// Rejects a withdrawal recipient outside the escrow's whitelist, when one is set.
+fn ensure_whitelisted(escrow_info: &EscrowInfo, recipient: &Addr) -> Result<(), ContractError> {
+    match &escrow_info.withdraw_whitelist {
+        Some(whitelist) if !whitelist.contains(recipient) => Err(ContractError::RecipientNotWhitelisted {}),
+        _ => Ok(()),
+    }
+}
+
+/// Releases a basket fill, pro rata to `fill` in the reference asset, or everything left when closing.
+/// Returns `None` for single-asset escrows.
+fn release_basket(
+    escrow_info: &mut EscrowInfo,
+    fill: Uint128,
+    closing: bool,
+    recipient: &Addr,
+) -> Option<CosmosMsg> {
+    let reference = escrow_info.deposited_amount;
+    let basket = escrow_info.basket.as_mut()?;
+
+    let mut amount = vec![];
+    for asset in basket.iter_mut() {
+        let release = if closing {
+            asset.amount - asset.filled_amount
+        } else {
+            asset.amount.multiply_ratio(fill, reference)
+        };
+        asset.filled_amount += release;
+        if !release.is_zero() {
+            amount.push(Coin {
+                denom: asset.denom.clone(),
+                amount: release,
+            });
+        }
+    }
+
+    Some(CosmosMsg::Bank(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount,
+    }))
+}
+
+/// Pays native proceeds to the recipient, or over IBC when forwarding is configured.
+fn native_payout(escrow_info: &EscrowInfo, env: &Env, recipient: &Addr, coin: Coin) -> CosmosMsg {
+    match &escrow_info.ibc_forward {
+        Some(ibc_forward) => CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: ibc_forward.channel.clone(),
+            to_address: ibc_forward.receiver.clone(),
+            amount: coin,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(ibc_forward.timeout)),
+        }),
+        None => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![coin],
+        }),
+    }
+}
+
+/// Builds the transfer of a funded safety deposit and marks it as paid out.
+fn safety_deposit_payout(
+    escrow_info: &mut EscrowInfo,
+    recipient: &Addr,
+    convert: bool,
+) -> StdResult<Option<CosmosMsg>> {
+    if !escrow_info.safety_deposit_funded {
+        return Ok(None);
+    }
+    let safety_deposit: Coin = match escrow_info.safety_deposit.clone() {
+        Some(safety_deposit) => safety_deposit,
+        None => return Ok(None),
+    };
+    escrow_info.safety_deposit_funded = false;
+
+    // Executor payouts go through the router when one is configured; refunds stay in the raw denom
+    match &escrow_info.safety_deposit_swap {
+        Some(swap) if convert && swap.output_denom != safety_deposit.denom => {
+            Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: swap.router.clone(),
+                msg: to_binary(&RouterExecuteMsg::Swap {
+                    output_denom: swap.output_denom.clone(),
+                    recipient: recipient.to_string(),
+                })?,
+                funds: vec![safety_deposit],
+            })))
+        }
+        _ => Ok(Some(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![safety_deposit],
+        }))),
+    }
+}
+
 fn calculate_current_price(escrow_info: &EscrowInfo, current_time: u64) -> Result<Uint128, ContractError> {
     if let (Some(initial_price), Some(decay_rate), Some(min_price)) = (
         &escrow_info.initial_price,
         &escrow_info.price_decay_rate,
         &escrow_info.minimum_price,
     ) {
-        let time_elapsed = current_time - escrow_info.created_at;
+        let time_elapsed = current_time.saturating_sub(escrow_info.created_at);
         let price_decrease = decay_rate.checked_mul(Uint128::from(time_elapsed))
             .map_err(|_| ContractError::InvalidDutchAuctionParams {})?;
         
@@ -445,34 +1625,1291 @@ fn calculate_current_price(escrow_info: &EscrowInfo, current_time: u64) -> Resul
     }
 }
 
+/// Price the decay schedule implies at `current_time`, without the floor clamp applied for display
+fn implied_price(escrow_info: &EscrowInfo, current_time: u64) -> Option<Uint128> {
+    let (initial_price, decay_rate) = (escrow_info.initial_price?, escrow_info.price_decay_rate?);
+    let time_elapsed = current_time.saturating_sub(escrow_info.created_at);
+    Some(initial_price.saturating_sub(decay_rate.saturating_mul(Uint128::from(time_elapsed))))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coin, coins, from_binary};
+    use escrow_common::hash::HashAlgo;
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
+    // sha256("secret")
+    const SECRET_HASH: &str = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
 
-        let msg = InstantiateMsg {
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
             maker: "maker".to_string(),
             taker: Some("taker".to_string()),
-            secret_hash: "hash123".to_string(),
-            timelock: 1000,
+            // sha256("hash123")
+            secret_hash: "673d190b758967621da243f06c350ce68be4276174dc886560239fea923d4a5a".to_string(),
+            timelock: mock_env().block.time.seconds() + 1000,
             dst_chain_id: "ethereum-1".to_string(),
             dst_asset: "ETH".to_string(),
             dst_amount: Uint128::from(100u128),
+            src_amount: None,
             initial_price: Some(Uint128::from(200u128)),
             price_decay_rate: Some(Uint128::from(1u128)),
             minimum_price: Some(Uint128::from(100u128)),
             allow_partial_fill: true,
             minimum_fill_amount: Some(Uint128::from(10u128)),
-        };
+            dust_threshold: None,
+            refund_addr: None,
+            safety_deposit: None,
+            safety_deposit_swap: None,
+            ibc_forward: None,
+            allowed_cw20: None,
+            basket: None,
+            withdraw_whitelist: None,
+            on_complete_msg: None,
+            max_total_extension: None,
+            hash_algorithm: None,
+            timelocks: None,
+            case_insensitive_denoms: false,
+            merkle_root: None,
+            tranche_count: None,
+            price_decimals: None,
+            resolver: None,
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+
+        let msg = instantiate_msg();
         let info = mock_info("creator", &coins(1000, "earth"));
 
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
     }
-}
 
+    #[test]
+    fn cancel_refunds_to_refund_addr() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            refund_addr: Some("refund".to_string()),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        let res = execute_cancel(deps.as_mut(), expired, mock_info("maker", &[])).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "refund".to_string(),
+                amount: coins(100, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn check_secret_reports_validity() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let check = |secret: &str| -> bool {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::CheckSecret { secret: secret.to_string() },
+            )
+            .unwrap();
+            from_binary::<CheckSecretResponse>(&res).unwrap().valid
+        };
+        assert!(check("secret"));
+        assert!(!check("wrong"));
+    }
+
+    #[test]
+    fn counterparty_params_are_evm_encoded() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CounterpartyParams {}).unwrap();
+        let params: CounterpartyParamsResponse = from_binary(&res).unwrap();
+        assert_eq!(format!("0x{}", SECRET_HASH), params.secret_hash);
+        assert_eq!(format!("0x{:064x}", mock_env().block.time.seconds() + 1000), params.timelock);
+        assert_eq!(
+            "0x0000000000000000000000000000000000000000000000000000000000000064",
+            params.amount
+        );
+        let maker = deps.api.addr_canonicalize("maker").unwrap();
+        assert_eq!(2 + 2 * maker.len(), params.recipient.len());
+        assert_eq!("ethereum-1", params.chain_id);
+    }
+
+    #[test]
+    fn safety_deposit_routed_separately() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            safety_deposit: Some(cosmwasm_std::coin(5, "ufee")),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Principal alone is rejected while the safety deposit is outstanding
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::MissingSafetyDeposit {}));
+
+        let funds = vec![cosmwasm_std::coin(100, "uatom"), cosmwasm_std::coin(5, "ufee")];
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+        let escrow_info = ESCROW_INFO.load(&deps.storage).unwrap();
+        assert_eq!(Uint128::from(100u128), escrow_info.deposited_amount);
+        assert!(escrow_info.safety_deposit_funded);
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        let res = execute_cancel(deps.as_mut(), expired, mock_info("maker", &[])).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: coins(5, "ufee"),
+            }),
+            res.messages[1].msg
+        );
+    }
+
+    #[test]
+    fn safety_deposit_paid_to_whoever_closes_the_fill() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            safety_deposit: Some(cosmwasm_std::coin(5, "ufee")),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let funds = vec![cosmwasm_std::coin(100, "uatom"), cosmwasm_std::coin(5, "ufee")];
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+
+        // Intermediate fills leave the safety deposit in place
+        let fill = |deps: DepsMut, sender: &str, amount: u128| {
+            execute_partial_withdraw(deps, mock_env(), mock_info(sender, &[]), "secret".to_string(), Uint128::from(amount))
+        };
+        let res = fill(deps.as_mut(), "executor", 40).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        let res = fill(deps.as_mut(), "closer", 60).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "closer".to_string(),
+                amount: coins(5, "ufee"),
+            }),
+            res.messages[1].msg
+        );
+        assert!(!ESCROW_INFO.load(&deps.storage).unwrap().safety_deposit_funded);
+    }
+
+    #[test]
+    fn safety_deposit_swapped_through_router_on_withdraw() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            safety_deposit: Some(cosmwasm_std::coin(5, "ufee")),
+            safety_deposit_swap: Some(crate::msg::SafetyDepositSwap {
+                router: "router".to_string(),
+                output_denom: "uatom".to_string(),
+            }),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let funds = vec![cosmwasm_std::coin(100, "uatom"), cosmwasm_std::coin(5, "ufee")];
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("executor", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router".to_string(),
+                msg: to_binary(&RouterExecuteMsg::Swap {
+                    output_denom: "uatom".to_string(),
+                    recipient: "executor".to_string(),
+                })
+                .unwrap(),
+                funds: coins(5, "ufee"),
+            }),
+            res.messages[1].msg
+        );
+    }
+
+    #[test]
+    fn history_records_deposit_withdraw_lifecycle() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            allow_partial_fill: false,
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+        execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap();
+
+        let history: HistoryResponse = query_history(deps.as_ref().storage, None, None).unwrap();
+        assert_eq!(2, history.transitions.len());
+        assert_eq!(None, history.transitions[0].from_status);
+        assert_eq!(EscrowStatus::Active, history.transitions[0].to_status);
+        assert_eq!(Some(EscrowStatus::Active), history.transitions[1].from_status);
+        assert_eq!(EscrowStatus::Withdrawn, history.transitions[1].to_status);
+        assert_eq!(Addr::unchecked("taker"), history.transitions[1].actor);
+
+        let page: HistoryResponse = query_history(deps.as_ref().storage, Some(0), None).unwrap();
+        assert_eq!(1, page.transitions.len());
+    }
+
+    #[test]
+    fn minimum_fill_cannot_exceed_order_amount() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            minimum_fill_amount: Some(Uint128::from(101u128)),
+            ..instantiate_msg()
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MinimumFillTooLarge {}));
+
+        let msg = InstantiateMsg {
+            minimum_fill_amount: Some(Uint128::from(50u128)),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // The deposit itself must also cover the minimum fill
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(40, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::MinimumFillTooLarge {}));
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+    }
+
+    #[test]
+    fn dust_remainder_must_be_cleared_in_one_fill() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            dust_threshold: Some(Uint128::from(10u128)),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let fill = |deps: DepsMut, amount: u128| {
+            execute_partial_withdraw(
+                deps,
+                mock_env(),
+                mock_info("taker", &[]),
+                "secret".to_string(),
+                Uint128::from(amount),
+            )
+        };
+        fill(deps.as_mut(), 94).unwrap();
+
+        // 6 left is dust: partial clears are rejected, the full remainder is accepted below min fill
+        let err = fill(deps.as_mut(), 3).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPartialFillAmount {}));
+        fill(deps.as_mut(), 6).unwrap();
+
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert!(escrow_info.remaining_amount.is_zero());
+        assert_eq!(EscrowStatus::Withdrawn, escrow_info.status);
+    }
+
+    #[test]
+    fn current_price_tolerates_block_time_before_creation() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.minus_seconds(100);
+        let price = query_current_price(deps.as_ref(), env).unwrap();
+        assert_eq!(0, price.time_elapsed);
+        assert_eq!(Uint128::from(200u128), price.current_price);
+    }
+
+    #[test]
+    fn price_decimals_default_and_round_trip() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        assert_eq!(6, query_current_price(deps.as_ref(), mock_env()).unwrap().price_decimals);
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { price_decimals: Some(18), ..instantiate_msg() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(18, query_current_price(deps.as_ref(), mock_env()).unwrap().price_decimals);
+    }
+
+    #[test]
+    fn rotated_secret_hash_required_to_withdraw() {
+        // sha256("rotated")
+        let rotated = "f42546d5ecdd452509808b2d6d0413b5a738c70a793b99ccf8ed6f423aac83d3";
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            allow_partial_fill: false,
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let err = execute_update_secret_hash(deps.as_mut(), mock_info("taker", &[]), rotated.to_string())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute_update_secret_hash(deps.as_mut(), mock_info("maker", &[]), rotated.to_string()).unwrap();
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+        let err = execute_update_secret_hash(deps.as_mut(), mock_info("maker", &[]), SECRET_HASH.to_string())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyFunded {}));
+
+        let err = execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSecret {}));
+        execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "rotated".to_string())
+            .unwrap();
+    }
+
+    #[test]
+    fn withdraw_forwards_over_ibc_when_configured() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            allow_partial_fill: false,
+            ibc_forward: Some(crate::msg::IbcForwardParams {
+                channel: "channel-0".to_string(),
+                receiver: "osmo1receiver".to_string(),
+                timeout: 600,
+            }),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let env = mock_env();
+        let res = execute_withdraw(deps.as_mut(), env.clone(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: "channel-0".to_string(),
+                to_address: "osmo1receiver".to_string(),
+                amount: cosmwasm_std::coin(100, "uatom"),
+                timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(600)),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn deposit_must_match_expected_src_amount() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            src_amount: Some(Uint128::from(100u128)),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(110, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DepositMismatch {}));
+
+        // Cannot withdraw while underfunded
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(10, "uatom"))).unwrap();
+        let taker = mock_info("taker", &[]);
+        let err = execute_withdraw(deps.as_mut(), mock_env(), taker.clone(), "secret".to_string()).unwrap_err();
+        assert!(matches!(err, ContractError::Underfunded {}));
+        let partial = ExecuteMsg::PartialWithdraw { secret: "secret".to_string(), amount: Uint128::from(10u128) };
+        let err = execute(deps.as_mut(), mock_env(), taker.clone(), partial).unwrap_err();
+        assert!(matches!(err, ContractError::Underfunded {}));
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(90, "uatom"))).unwrap();
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(Uint128::from(100u128), escrow_info.deposited_amount);
+        execute_withdraw(deps.as_mut(), mock_env(), taker, "secret".to_string()).unwrap();
+    }
+
+    #[test]
+    fn cw20_deposit_requires_whitelisted_contract() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            allowed_cw20: Some(vec!["token".to_string()]),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let deposit = Cw20ReceiveMsg {
+            sender: "maker".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&ReceiveMsg::Deposit {}).unwrap(),
+        };
+        let err = execute_receive(deps.as_mut(), mock_env(), mock_info("junk", &[]), deposit.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Cw20NotAllowed {}));
+
+        execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), deposit).unwrap();
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(Some(Addr::unchecked("token")), escrow_info.cw20_contract);
+    }
+
+    #[test]
+    fn deposit_from_pulls_approved_cw20() {
+        use cw20::{Cw20Coin, Cw20ExecuteMsg as TokenMsg};
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let token_code = app.store_code(Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        let escrow_code = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+        ));
+
+        let token = app
+            .instantiate_contract(
+                token_code,
+                Addr::unchecked("creator"),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Token".to_string(),
+                    symbol: "TKN".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: "maker".to_string(),
+                        amount: Uint128::from(500u128),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+        let escrow = app
+            .instantiate_contract(escrow_code, Addr::unchecked("creator"), &instantiate_msg(), &[], "escrow", None)
+            .unwrap();
+
+        app.execute_contract(
+            Addr::unchecked("maker"),
+            token.clone(),
+            &TokenMsg::IncreaseAllowance {
+                spender: escrow.to_string(),
+                amount: Uint128::from(200u128),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let deposit = |amount: u128| ExecuteMsg::DepositFrom {
+            cw20_contract: token.to_string(),
+            amount: Uint128::from(amount),
+        };
+        for amount in [60, 40] {
+            app.execute_contract(Addr::unchecked("maker"), escrow.clone(), &deposit(amount), &[]).unwrap();
+        }
+
+        let res: EscrowResponse = app.wrap().query_wasm_smart(&escrow, &QueryMsg::Escrow {}).unwrap();
+        assert_eq!(Uint128::from(100u128), res.deposited_amount);
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&token, &cw20::Cw20QueryMsg::Balance { address: escrow.to_string() })
+            .unwrap();
+        assert_eq!(Uint128::from(100u128), balance.balance);
+
+        // Pulls past the order amount are rejected even though the allowance would cover them
+        let err = app.execute_contract(Addr::unchecked("maker"), escrow, &deposit(1), &[]).unwrap_err();
+        assert!(matches!(err.downcast().unwrap(), ContractError::DepositMismatch {}));
+    }
+
+    #[test]
+    fn deposit_reports_current_state_when_not_active() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+
+        for (status, expected) in [
+            (EscrowStatus::Cancelled, "Cancelled"),
+            (EscrowStatus::PartiallyFilled, "PartiallyFilled"),
+        ] {
+            ESCROW_INFO
+                .update(deps.as_mut().storage, |mut escrow_info| -> StdResult<_> {
+                    escrow_info.status = status;
+                    Ok(escrow_info)
+                })
+                .unwrap();
+            let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom")))
+                .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidState { current } if current == expected));
+        }
+    }
+
+    #[test]
+    fn basket_deposited_and_withdrawn_atomically() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            basket: Some(vec![
+                AssetInfo { denom: "uatom".to_string(), amount: Uint128::from(100u128) },
+                AssetInfo { denom: "uosmo".to_string(), amount: Uint128::from(300u128) },
+            ]),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBasket {}));
+        let basket = vec![Coin::new(100, "uatom"), Coin::new(300, "uosmo")];
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &basket)).unwrap();
+
+        // A partial fill releases the same share of every asset
+        execute_partial_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("taker", &[]),
+            "secret".to_string(),
+            Uint128::from(40u128),
+        )
+        .unwrap();
+        let status = query_fill_status(deps.as_ref()).unwrap();
+        assert_eq!(Uint128::from(120u128), status.basket[1].filled_amount);
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: vec![Coin::new(60, "uatom"), Coin::new(180, "uosmo")],
+            }),
+            res.messages[0].msg
+        );
+        let status = query_fill_status(deps.as_ref()).unwrap();
+        assert!(status.basket.iter().all(|asset| asset.remaining_amount.is_zero()));
+    }
+
+    #[test]
+    fn case_insensitive_denoms_adopt_the_received_denom() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            basket: Some(vec![
+                AssetInfo { denom: "uATOM".to_string(), amount: Uint128::from(100u128) },
+                AssetInfo { denom: "uosmo".to_string(), amount: Uint128::from(300u128) },
+            ]),
+            safety_deposit: Some(Coin::new(5, "uFee")),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()).unwrap();
+
+        // Exact matching is the default
+        let funds = vec![Coin::new(100, "uatom"), Coin::new(300, "uosmo"), Coin::new(5, "ufee")];
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap_err();
+        assert!(matches!(err, ContractError::MissingSafetyDeposit {}));
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { case_insensitive_denoms: true, ..msg };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let wrong = vec![Coin::new(100, "ujuno"), Coin::new(300, "uosmo"), Coin::new(5, "ufee")];
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &wrong)).unwrap_err();
+        assert!(matches!(err, ContractError::DenomMismatch {}));
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+        let escrow_info = ESCROW_INFO.load(&deps.storage).unwrap();
+        assert_eq!("uatom", escrow_info.basket.unwrap()[0].denom);
+        assert_eq!(Some(Coin::new(5, "ufee")), escrow_info.safety_deposit);
+        assert_eq!(Some("uatom".to_string()), escrow_info.deposited_denom);
+    }
+
+    #[test]
+    fn refund_quote_tracks_fills_and_timelock() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            timelock: mock_env().block.time.seconds() + 100,
+            safety_deposit: Some(Coin::new(10, "uatom")),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let funds = vec![Coin::new(100, "uosmo"), Coin::new(10, "uatom")];
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+        execute_partial_withdraw(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("taker", &[]),
+            "secret".to_string(),
+            Uint128::from(30u128),
+        )
+        .unwrap();
+
+        let quote = query_refund_quote(deps.as_ref(), mock_env()).unwrap();
+        assert!(!quote.cancellable);
+        assert_eq!(100, quote.seconds_until_cancellable);
+        assert_eq!(Uint128::from(70u128), quote.refund_amount);
+        assert_eq!(Some(Coin::new(10, "uatom")), quote.safety_deposit);
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(100);
+        let quote = query_refund_quote(deps.as_ref(), expired.clone()).unwrap();
+        assert!(quote.cancellable);
+        assert_eq!(0, quote.seconds_until_cancellable);
+
+        execute_cancel(deps.as_mut(), expired.clone(), mock_info("maker", &[])).unwrap();
+        let quote = query_refund_quote(deps.as_ref(), expired).unwrap();
+        assert!(!quote.cancellable);
+        assert!(quote.refund_amount.is_zero());
+    }
+
+    #[test]
+    fn withdraw_whitelist_restricts_recipient() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            taker: None,
+            secret_hash: SECRET_HASH.to_string(),
+            withdraw_whitelist: Some(vec!["approved".to_string()]),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let err = execute_withdraw(deps.as_mut(), mock_env(), mock_info("stranger", &[]), "secret".to_string())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::RecipientNotWhitelisted {}));
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("approved", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "approved".to_string(),
+                amount: coins(100, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn completion_callback_fires_after_withdrawal() {
+        use cosmwasm_schema::cw_serde;
+        use cosmwasm_std::Empty;
+        use cw_multi_test::{App, ContractWrapper, Executor};
+        use cw_storage_plus::Item;
+
+        #[cw_serde]
+        enum VaultMsg {
+            Deposit { memo: String },
+        }
+        const LAST_MEMO: Item<String> = Item::new("last_memo");
+
+        let mut app = App::new(|router, _, storage| {
+            router.bank.init_balance(storage, &Addr::unchecked("maker"), coins(100, "uatom")).unwrap();
+        });
+        let vault_code = app.store_code(Box::new(ContractWrapper::new(
+            |deps: DepsMut, _env: Env, _info: MessageInfo, msg: VaultMsg| -> StdResult<Response> {
+                let VaultMsg::Deposit { memo } = msg;
+                LAST_MEMO.save(deps.storage, &memo)?;
+                Ok(Response::new())
+            },
+            |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> { to_binary(&LAST_MEMO.may_load(deps.storage)?) },
+        )));
+        let escrow_code = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+        ));
+
+        let vault = app
+            .instantiate_contract(vault_code, Addr::unchecked("creator"), &Empty {}, &[], "vault", None)
+            .unwrap();
+        let callback = to_binary(&VaultMsg::Deposit { memo: "order-1".to_string() }).unwrap();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            on_complete_msg: Some((vault.to_string(), callback)),
+            ..instantiate_msg()
+        };
+        let escrow = app
+            .instantiate_contract(escrow_code, Addr::unchecked("creator"), &msg, &[], "escrow", None)
+            .unwrap();
+
+        app.execute_contract(Addr::unchecked("maker"), escrow.clone(), &ExecuteMsg::Deposit {}, &coins(100, "uatom"))
+            .unwrap();
+        let memo: Option<String> = app.wrap().query_wasm_smart(&vault, &Empty {}).unwrap();
+        assert_eq!(None, memo);
+
+        app.execute_contract(
+            Addr::unchecked("taker"),
+            escrow,
+            &ExecuteMsg::Withdraw { secret: "secret".to_string() },
+            &[],
+        )
+        .unwrap();
+        let memo: Option<String> = app.wrap().query_wasm_smart(&vault, &Empty {}).unwrap();
+        assert_eq!(Some("order-1".to_string()), memo);
+        assert_eq!(Uint128::from(100u128), app.wrap().query_balance("taker", "uatom").unwrap().amount);
+    }
+
+    #[test]
+    fn migrate_converts_hex_secret_hash_to_bytes() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: format!("0x{}", SECRET_HASH),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(32, escrow_info.secret_hash.len());
+
+        // Rewrite the stored hashlock into the legacy hex form
+        let raw = deps.storage.get(ESCROW_INFO.as_slice()).unwrap();
+        let legacy = String::from_utf8(raw)
+            .unwrap()
+            .replace(&escrow_info.secret_hash.to_base64(), SECRET_HASH);
+        deps.storage.set(ESCROW_INFO.as_slice(), legacy.as_bytes());
+        assert!(ESCROW_INFO.load(deps.as_ref().storage).is_err());
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!("true", res.attributes[1].value);
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!("false", res.attributes[1].value);
+
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(32, escrow_info.secret_hash.len());
+        assert_eq!(SECRET_HASH, query_escrow(deps.as_ref()).unwrap().secret_hash);
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+        let err = execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "wrong".to_string())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSecret {}));
+        execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string()).unwrap();
+    }
+
+    #[test]
+    fn migrate_upgrades_first_release_record() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+
+        // The record as the first release stored it: one absolute timelock and a hex hashlock
+        let created_at = mock_env().block.time.seconds();
+        let baseline = format!(
+            r#"{{"maker":"maker","taker":"taker","secret_hash":"{}","timelock":{},"dst_chain_id":"ethereum-1","dst_asset":"ETH","dst_amount":"100","deposited_amount":"0","deposited_denom":null,"cw20_contract":null,"status":"Active","created_at":{},"initial_price":null,"price_decay_rate":null,"minimum_price":null,"allow_partial_fill":false,"minimum_fill_amount":null,"filled_amount":"0","remaining_amount":"0"}}"#,
+            SECRET_HASH,
+            created_at + 1000,
+            created_at
+        );
+        deps.storage.set(ESCROW_INFO.as_slice(), baseline.as_bytes());
+        assert!(ESCROW_INFO.load(deps.as_ref().storage).is_err());
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!("true", res.attributes[1].value);
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(Timelocks::from_timelock(created_at, created_at + 1000), escrow_info.timelocks);
+        assert_eq!(HashAlgo::Sha256, escrow_info.hash_algorithm);
+        assert_eq!(DEFAULT_PRICE_DECIMALS, escrow_info.price_decimals);
+        assert!(!escrow_info.safety_deposit_funded && escrow_info.cancelled_tranches.is_empty());
+        assert_eq!((0, 0), (escrow_info.max_total_extension, escrow_info.total_extension));
+        assert_eq!(SECRET_HASH, query_escrow(deps.as_ref()).unwrap().secret_hash);
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+        execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string()).unwrap();
+    }
+
+    #[test]
+    fn malformed_secret_hash_rejected() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: "hash123".to_string(),
+            ..instantiate_msg()
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSecretHash {}));
+    }
+
+    #[test]
+    fn fixed_price_order_allowed_without_decay() {
+        let mut deps = mock_dependencies();
+        let fixed = InstantiateMsg {
+            initial_price: Some(Uint128::from(150u128)),
+            price_decay_rate: Some(Uint128::zero()),
+            minimum_price: Some(Uint128::from(150u128)),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), fixed.clone()).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(500);
+        let price = query_current_price(deps.as_ref(), env).unwrap();
+        assert_eq!(Uint128::from(150u128), price.current_price);
+
+        let decaying = InstantiateMsg {
+            price_decay_rate: Some(Uint128::from(1u128)),
+            ..fixed
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), decaying).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidDutchAuctionParams {}));
+
+        let inverted = InstantiateMsg {
+            initial_price: Some(Uint128::from(100u128)),
+            price_decay_rate: Some(Uint128::zero()),
+            minimum_price: Some(Uint128::from(150u128)),
+            ..instantiate_msg()
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), inverted).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidDutchAuctionParams {}));
+    }
+
+    #[test]
+    fn timelock_extensions_stop_at_cap() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_total_extension: Some(100),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let extend = |extension: u64| ExecuteMsg::ExtendTimelock { extension };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), extend(60)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), extend(60)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), extend(40)).unwrap();
+        let escrow = ESCROW_INFO.load(&deps.storage).unwrap();
+        assert_eq!(1100, escrow.timelocks.cancellation);
+        assert_eq!(100, escrow.total_extension);
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), extend(1)).unwrap_err();
+        assert!(matches!(err, ContractError::ExtensionCapExceeded {}));
+        assert_eq!(1100, ESCROW_INFO.load(&deps.storage).unwrap().timelocks.cancellation);
+    }
+
+    #[test]
+    fn keccak_hashlock_requires_keccak_mode() {
+        // keccak256("secret"), as locked by the EVM-side HTLC
+        let keccak_hash = "65462b0520ef7d3df61b9992ed3bea0c56ead753be7c8b3614e0ce01e4cac41b";
+        for (hash_algorithm, unlocks) in [(None, false), (Some(HashAlgo::Keccak256), true)] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                secret_hash: keccak_hash.to_string(),
+                hash_algorithm,
+                ..instantiate_msg()
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+            let withdraw = ExecuteMsg::Withdraw { secret: "secret".to_string() };
+            let res = execute(deps.as_mut(), mock_env(), mock_info("taker", &[]), withdraw);
+            if unlocks {
+                res.unwrap();
+            } else {
+                assert!(matches!(res.unwrap_err(), ContractError::InvalidSecret {}));
+            }
+        }
+    }
+
+    #[test]
+    fn staged_timelocks_gate_withdrawal_and_cancel() {
+        let at = |offset: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(offset);
+            env
+        };
+        let staged = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                secret_hash: SECRET_HASH.to_string(),
+                allow_partial_fill: false,
+                timelocks: Some(Timelocks {
+                    finality: 10,
+                    exclusive_withdrawal: 20,
+                    public_withdrawal: 30,
+                    cancellation: 40,
+                }),
+                ..instantiate_msg()
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+            deps
+        };
+        let withdraw = |deps: DepsMut, offset: u64, sender: &str| {
+            execute_withdraw(deps, at(offset), mock_info(sender, &[]), "secret".to_string())
+        };
+
+        let mut deps = staged();
+        let err = withdraw(deps.as_mut(), 9, "taker").unwrap_err();
+        assert!(matches!(err, ContractError::FinalityNotReached {}));
+        let err = withdraw(deps.as_mut(), 10, "resolver").unwrap_err();
+        assert!(matches!(err, ContractError::ExclusiveWithdrawal {}));
+        let err = withdraw(deps.as_mut(), 19, "resolver").unwrap_err();
+        assert!(matches!(err, ContractError::ExclusiveWithdrawal {}));
+        withdraw(deps.as_mut(), 10, "taker").unwrap();
+
+        let mut deps = staged();
+        withdraw(deps.as_mut(), 29, "resolver").unwrap();
+
+        let mut deps = staged();
+        let err = withdraw(deps.as_mut(), 30, "taker").unwrap_err();
+        assert!(matches!(err, ContractError::WithdrawalWindowClosed {}));
+        let err = execute_cancel(deps.as_mut(), at(39), mock_info("maker", &[])).unwrap_err();
+        assert!(matches!(err, ContractError::TimelockNotExpired {}));
+        execute_cancel(deps.as_mut(), at(40), mock_info("maker", &[])).unwrap();
+
+        // Stages out of order are rejected
+        let msg = InstantiateMsg {
+            timelocks: Some(Timelocks {
+                finality: 10,
+                exclusive_withdrawal: 20,
+                public_withdrawal: 50,
+                cancellation: 40,
+            }),
+            ..instantiate_msg()
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTimelock {}));
+    }
+
+    #[test]
+    fn preview_cancel_matches_actual_cancel() {
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        for partial_fill in [None, Some(30u128)] {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                secret_hash: SECRET_HASH.to_string(),
+                refund_addr: Some("refund".to_string()),
+                safety_deposit: Some(Coin::new(5, "ufee")),
+                ..instantiate_msg()
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let funds = vec![Coin::new(100, "uatom"), Coin::new(5, "ufee")];
+            execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &funds)).unwrap();
+            if let Some(amount) = partial_fill {
+                execute_partial_withdraw(
+                    deps.as_mut(),
+                    mock_env(),
+                    mock_info("taker", &[]),
+                    "secret".to_string(),
+                    Uint128::from(amount),
+                )
+                .unwrap();
+            }
+
+            let early: PreviewCancelResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PreviewCancel {}).unwrap()).unwrap();
+            assert!(!early.cancellable);
+            assert!(early.messages.is_empty());
+
+            let preview: PreviewCancelResponse =
+                from_binary(&query(deps.as_ref(), expired.clone(), QueryMsg::PreviewCancel {}).unwrap()).unwrap();
+            assert!(preview.cancellable);
+            assert_eq!("refund", preview.recipient);
+            assert_eq!(Uint128::from(100 - partial_fill.unwrap_or(0)), preview.amount);
+            assert_eq!(Some(RefundAsset::Native { denom: "uatom".to_string() }), preview.asset);
+
+            let res = execute_cancel(deps.as_mut(), expired.clone(), mock_info("maker", &[])).unwrap();
+            let sent: Vec<CosmosMsg> = res.messages.into_iter().map(|sub_msg| sub_msg.msg).collect();
+            assert_eq!(preview.messages, sent);
+            assert!(res.attributes.contains(&cosmwasm_std::attr("returned_amount", preview.amount)));
+        }
+    }
+
+    #[test]
+    fn partial_fills_respect_the_price_floor() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        // Decays from 200 at 1 per second onto the 100 floor
+        let fill_after = |deps: DepsMut, seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            execute_partial_withdraw(deps, env, mock_info("taker", &[]), "secret".to_string(), Uint128::from(10u128))
+        };
+        fill_after(deps.as_mut(), 100).unwrap();
+
+        let err = fill_after(deps.as_mut(), 101).unwrap_err();
+        assert!(matches!(err, ContractError::MinimumPriceReached {}));
+        assert_eq!(Uint128::from(90u128), ESCROW_INFO.load(&deps.storage).unwrap().remaining_amount);
+    }
+
+    #[test]
+    fn repeat_deposits_accumulate_in_one_denom() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(60, "uatom"))).unwrap();
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(40, "uosmo")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DepositDenomMismatch {}));
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(40, "uatom"))).unwrap();
+
+        let escrow_info = ESCROW_INFO.load(&deps.storage).unwrap();
+        assert_eq!(Uint128::from(100u128), escrow_info.deposited_amount);
+        assert_eq!(Uint128::from(100u128), escrow_info.remaining_amount);
+
+        // Topping up past dst_amount is rejected
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(1, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DepositMismatch {}));
+    }
+
+    #[test]
+    fn repeat_cw20_deposits_accumulate_in_one_token() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+
+        let deposit = |amount: u128| Cw20ReceiveMsg {
+            sender: "maker".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&ReceiveMsg::Deposit {}).unwrap(),
+        };
+        execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), deposit(60)).unwrap();
+        let err = execute_receive(deps.as_mut(), mock_env(), mock_info("other_token", &[]), deposit(40))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DepositDenomMismatch {}));
+        let err = execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(40, "uatom")))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::DepositDenomMismatch {}));
+        execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), deposit(40)).unwrap();
+
+        let escrow_info = ESCROW_INFO.load(&deps.storage).unwrap();
+        assert_eq!(Uint128::from(100u128), escrow_info.deposited_amount);
+        assert_eq!(Uint128::from(100u128), escrow_info.remaining_amount);
+        assert_eq!(Some(Addr::unchecked("token")), escrow_info.cw20_contract);
+
+        // Topping up past dst_amount is rejected
+        let err = execute_receive(deps.as_mut(), mock_env(), mock_info("token", &[]), deposit(1)).unwrap_err();
+        assert!(matches!(err, ContractError::DepositMismatch {}));
+    }
+
+    #[test]
+    fn withdraw_emits_secret_revealed_event() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(
+            vec![Event::new("secret_revealed")
+                .add_attribute("secret", "secret")
+                .add_attribute("secret_hash", SECRET_HASH)],
+            res.events
+        );
+        assert!(res.attributes.contains(&cosmwasm_std::attr("secret", "secret")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("secret_hash", SECRET_HASH)));
+    }
+
+    #[test]
+    fn rescue_sweeps_stray_denom_after_settlement() {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg()).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![coin(100, "uatom"), coin(7, "uosmo")]);
+
+        let rescue = |denom: &str| ExecuteMsg::Rescue { denom: Some(denom.to_string()), cw20_contract: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("maker", &[]), rescue("uosmo")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidState { .. }));
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        execute_cancel(deps.as_mut(), expired.clone(), mock_info("maker", &[])).unwrap();
+
+        let err = execute(deps.as_mut(), expired.clone(), mock_info("taker", &[]), rescue("uosmo")).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), expired.clone(), mock_info("maker", &[]), rescue("uatom")).unwrap_err();
+        assert!(matches!(err, ContractError::TrackedAssetNotRescuable {}));
+
+        let res = execute(deps.as_mut(), expired, mock_info("maker", &[]), rescue("uosmo")).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: coins(7, "uosmo"),
+            }),
+            res.messages[0].msg
+        );
+    }
+
+    #[test]
+    fn merkle_partial_fills_verify_proofs_and_advance_the_index() {
+        let digest = |data: &[u8]| HashAlgo::Sha256.digest(data);
+        let pair = |a: &[u8], b: &[u8]| if a <= b { digest(&[a, b].concat()) } else { digest(&[b, a].concat()) };
+        let hex = |bytes: &[u8]| encode_secret_hash(&Binary::from(bytes));
+        let secrets = ["s0", "s1", "s2", "s3"];
+        let leaves: Vec<Vec<u8>> = secrets
+            .iter()
+            .enumerate()
+            .map(|(i, s)| digest(&[(i as u64).to_be_bytes().to_vec(), digest(s.as_bytes())].concat()))
+            .collect();
+        let (left, right) = (pair(&leaves[0], &leaves[1]), pair(&leaves[2], &leaves[3]));
+        let proofs = [
+            vec![hex(&leaves[1]), hex(&right)],
+            vec![hex(&leaves[0]), hex(&right)],
+            vec![hex(&leaves[3]), hex(&left)],
+        ];
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { merkle_root: Some(hex(&pair(&left, &right))), ..instantiate_msg() };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let fill = |index: u64, proof: &Vec<String>| ExecuteMsg::PartialWithdrawMerkle {
+            secret: secrets[index as usize].to_string(),
+            merkle_proof: proof.clone(),
+            index,
+            amount: Uint128::from(25u128),
+        };
+        let taker = mock_info("taker", &[]);
+
+        let plain = ExecuteMsg::PartialWithdraw { secret: "s1".to_string(), amount: Uint128::from(25u128) };
+        let err = execute(deps.as_mut(), mock_env(), taker.clone(), plain).unwrap_err();
+        assert!(matches!(err, ContractError::MerkleProofRequired {}));
+
+        let err = execute(deps.as_mut(), mock_env(), taker.clone(), fill(1, &proofs[2])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMerkleProof {}));
+
+        execute(deps.as_mut(), mock_env(), taker.clone(), fill(1, &proofs[1])).unwrap();
+        let escrow = query_escrow(deps.as_ref()).unwrap();
+        assert_eq!(Some(1), escrow.last_used_index);
+        assert_eq!(Uint128::from(75u128), escrow.remaining_amount);
+
+        // Revealed or skipped indices cannot be used again
+        let err = execute(deps.as_mut(), mock_env(), taker.clone(), fill(0, &proofs[0])).unwrap_err();
+        assert!(matches!(err, ContractError::SecretIndexReused {}));
+        execute(deps.as_mut(), mock_env(), taker, fill(2, &proofs[2])).unwrap();
+        assert_eq!(Some(2), query_escrow(deps.as_ref()).unwrap().last_used_index);
+    }
+
+    #[test]
+    fn cancelled_tranches_refund_and_retire_their_secrets() {
+        let digest = |data: &[u8]| HashAlgo::Sha256.digest(data);
+        let pair = |a: &[u8], b: &[u8]| if a <= b { digest(&[a, b].concat()) } else { digest(&[b, a].concat()) };
+        let hex = |bytes: &[u8]| encode_secret_hash(&Binary::from(bytes));
+        let secrets = ["s0", "s1", "s2", "s3"];
+        let leaves: Vec<Vec<u8>> = secrets
+            .iter()
+            .enumerate()
+            .map(|(i, s)| digest(&[(i as u64).to_be_bytes().to_vec(), digest(s.as_bytes())].concat()))
+            .collect();
+        let (left, right) = (pair(&leaves[0], &leaves[1]), pair(&leaves[2], &leaves[3]));
+        let proofs = [
+            vec![hex(&leaves[1]), hex(&right)],
+            vec![hex(&leaves[0]), hex(&right)],
+            vec![hex(&leaves[3]), hex(&left)],
+            vec![hex(&leaves[2]), hex(&left)],
+        ];
+
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            merkle_root: Some(hex(&pair(&left, &right))),
+            tranche_count: Some(4),
+            max_total_extension: Some(1000),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let fill = |index: usize| ExecuteMsg::PartialWithdrawMerkle {
+            secret: secrets[index].to_string(),
+            merkle_proof: proofs[index].clone(),
+            index: index as u64,
+            amount: Uint128::from(25u128),
+        };
+        let cancel = |indices: Vec<u64>| ExecuteMsg::CancelTranches { indices };
+        let (maker, taker) = (mock_info("maker", &[]), mock_info("taker", &[]));
+        execute(deps.as_mut(), mock_env(), taker.clone(), fill(0)).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), maker.clone(), cancel(vec![1, 2])).unwrap_err();
+        assert!(matches!(err, ContractError::TimelockNotExpired {}));
+
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(1000);
+        let err = execute(deps.as_mut(), expired.clone(), taker.clone(), cancel(vec![1, 2])).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), expired.clone(), maker.clone(), cancel(vec![0])).unwrap_err();
+        assert!(matches!(err, ContractError::TrancheAlreadyUsed {}));
+        let err = execute(deps.as_mut(), expired.clone(), maker.clone(), cancel(vec![4])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTranche {}));
+
+        let res = execute(deps.as_mut(), expired.clone(), maker.clone(), cancel(vec![2, 1])).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "maker".to_string(),
+                amount: coins(50, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+        let escrow = query_escrow(deps.as_ref()).unwrap();
+        assert_eq!(vec![1, 2], escrow.cancelled_tranches);
+        assert_eq!(Uint128::from(25u128), escrow.remaining_amount);
+        assert_eq!(EscrowStatus::PartiallyFilled, ESCROW_INFO.load(deps.as_ref().storage).unwrap().status);
+
+        // Reopen the window: the cancelled tranches stay closed while the last one still fills
+        let extend = ExecuteMsg::ExtendTimelock { extension: 500 };
+        execute(deps.as_mut(), expired.clone(), maker.clone(), extend).unwrap();
+        execute(deps.as_mut(), expired.clone(), maker, ExecuteMsg::RestAtMinimum {}).unwrap();
+        for index in [1, 2] {
+            let err = execute(deps.as_mut(), expired.clone(), taker.clone(), fill(index)).unwrap_err();
+            assert!(matches!(err, ContractError::TrancheCancelled {}));
+        }
+        execute(deps.as_mut(), expired, taker, fill(3)).unwrap();
+        assert_eq!(EscrowStatus::Withdrawn, ESCROW_INFO.load(deps.as_ref().storage).unwrap().status);
+    }
+
+    #[test]
+    fn rejected_payout_is_parked_for_claim() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            secret_hash: SECRET_HASH.to_string(),
+            ..instantiate_msg()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute_deposit(deps.as_mut(), mock_env(), mock_info("maker", &coins(100, "uatom"))).unwrap();
+
+        let res = execute_withdraw(deps.as_mut(), mock_env(), mock_info("taker", &[]), "secret".to_string())
+            .unwrap();
+        assert_eq!(PAYOUT_REPLY_ID, res.messages[0].id);
+
+        // The recipient rejects the transfer
+        let failed = Reply {
+            id: PAYOUT_REPLY_ID,
+            result: SubMsgResult::Err("recipient rejected funds".to_string()),
+        };
+        reply(deps.as_mut(), mock_env(), failed).unwrap();
+        assert_eq!(
+            Uint128::from(100u128),
+            query_claimable(deps.as_ref(), "taker".to_string()).unwrap().amount
+        );
+        let escrow_info = ESCROW_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(EscrowStatus::Withdrawn, escrow_info.status);
+
+        let res = execute_claim_withdrawn(deps.as_mut(), mock_info("taker", &[])).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "taker".to_string(),
+                amount: coins(100, "uatom"),
+            }),
+            res.messages[0].msg
+        );
+        let err = execute_claim_withdrawn(deps.as_mut(), mock_info("taker", &[])).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToClaim {}));
+    }
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use escrow_common::validation::ValidationError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,9 +10,36 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Escrow already funded")]
+    AlreadyFunded {},
+
+    #[error("CW20 contract not allowed")]
+    Cw20NotAllowed {},
+
     #[error("Invalid secret")]
     InvalidSecret {},
 
+    #[error("Invalid Merkle proof")]
+    InvalidMerkleProof {},
+
+    #[error("Secret index must be greater than the last one used")]
+    SecretIndexReused {},
+
+    #[error("Partial fills on this escrow need a Merkle proof")]
+    MerkleProofRequired {},
+
+    #[error("Escrow has no Merkle root")]
+    MerkleRootNotSet {},
+
+    #[error("Invalid Merkle tranche")]
+    InvalidTranche {},
+
+    #[error("Merkle tranche already used")]
+    TrancheAlreadyUsed {},
+
+    #[error("Merkle tranche cancelled")]
+    TrancheCancelled {},
+
     #[error("Escrow already withdrawn")]
     AlreadyWithdrawn {},
 
@@ -21,9 +49,30 @@ pub enum ContractError {
     #[error("Cannot cancel before timelock expires")]
     TimelockNotExpired {},
 
+    #[error("Withdrawals are locked until finality")]
+    FinalityNotReached {},
+
+    #[error("Only the taker may withdraw during the exclusive window")]
+    ExclusiveWithdrawal {},
+
+    #[error("Withdrawal window has closed")]
+    WithdrawalWindowClosed {},
+
     #[error("Insufficient funds")]
     InsufficientFunds {},
 
+    #[error("Deposit does not match the expected source amount")]
+    DepositMismatch {},
+
+    #[error("Escrow holds less than the expected source amount")]
+    Underfunded {},
+
+    #[error("Top-up denom does not match the deposited denom")]
+    DepositDenomMismatch {},
+
+    #[error("Received denom does not match the configured denom")]
+    DenomMismatch {},
+
     #[error("Invalid partial fill amount")]
     InvalidPartialFillAmount {},
 
@@ -35,5 +84,65 @@ pub enum ContractError {
 
     #[error("Invalid dutch auction parameters")]
     InvalidDutchAuctionParams {},
+
+    #[error("Missing safety deposit")]
+    MissingSafetyDeposit {},
+
+    #[error("Invalid IBC forward parameters")]
+    InvalidIbcForward {},
+
+    #[error("Minimum fill amount exceeds the order amount")]
+    MinimumFillTooLarge {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Invalid safety deposit swap")]
+    InvalidSafetyDepositSwap {},
+
+    #[error("Invalid asset basket")]
+    InvalidBasket {},
+
+    #[error("Escrow is {current} and cannot take this action")]
+    InvalidState { current: String },
+
+    #[error("Invalid chain or asset identifier")]
+    InvalidIdentifier {},
+
+    #[error("Recipient is not on the withdrawal whitelist")]
+    RecipientNotWhitelisted {},
+
+    #[error("Completion callback may not target the escrow or its deposit token")]
+    InvalidCompletionCallback {},
+
+    #[error("Invalid secret hash")]
+    InvalidSecretHash {},
+
+    #[error("Invalid timelock")]
+    InvalidTimelock {},
+
+    #[error("Invalid amount")]
+    InvalidAmount {},
+
+    #[error("Timelock extension exceeds the escrow's cap")]
+    ExtensionCapExceeded {},
+
+    #[error("Set exactly one of denom or cw20_contract to rescue")]
+    InvalidRescueTarget {},
+
+    #[error("The escrow's tracked asset cannot be rescued")]
+    TrackedAssetNotRescuable {},
 }
 
+impl From<ValidationError> for ContractError {
+    fn from(err: ValidationError) -> Self {
+        match err {
+            ValidationError::InvalidTimelock {} => ContractError::InvalidTimelock {},
+            ValidationError::InvalidAuctionParams {} => ContractError::InvalidDutchAuctionParams {},
+            ValidationError::InvalidAmount {} => ContractError::InvalidAmount {},
+            ValidationError::MinimumFillTooLarge {} => ContractError::MinimumFillTooLarge {},
+            ValidationError::InvalidIdentifier {} => ContractError::InvalidIdentifier {},
+            ValidationError::InvalidSecretHash {} => ContractError::InvalidSecretHash {},
+        }
+    }
+}
@@ -1,16 +1,21 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Uint128};
 use cw20::Cw20ReceiveMsg;
+use escrow_common::hash::HashAlgo;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub maker: String,
     pub taker: Option<String>,
     pub secret_hash: String,
+    /// Absolute cancellation time; ignored when `timelocks` is set
     pub timelock: u64,
     pub dst_chain_id: String,
     pub dst_asset: String,
     pub dst_amount: Uint128,
+    /// Principal the maker must deposit in full, across top-ups, before withdrawals open; deposits are
+    /// only capped at `dst_amount` when unset
+    pub src_amount: Option<Uint128>,
     // Dutch auction parameters
     pub initial_price: Option<Uint128>,
     pub price_decay_rate: Option<Uint128>, // per second
@@ -18,25 +23,148 @@ pub struct InstantiateMsg {
     // Partial fill parameters
     pub allow_partial_fill: bool,
     pub minimum_fill_amount: Option<Uint128>,
+    /// Once remaining is at or below this, the next fill must clear it
+    pub dust_threshold: Option<Uint128>,
+    /// Address refunded on cancel; defaults to the maker
+    pub refund_addr: Option<String>,
+    /// Safety deposit funded alongside the principal, in its own denom
+    pub safety_deposit: Option<Coin>,
+    /// Swap the safety deposit into another denom when paying it to the executor
+    pub safety_deposit_swap: Option<SafetyDepositSwap>,
+    /// Forward native withdrawals over IBC instead of sending locally
+    pub ibc_forward: Option<IbcForwardParams>,
+    /// CW20 contracts accepted for deposits; any when unset
+    pub allowed_cw20: Option<Vec<String>>,
+    /// Native assets locked and released together; the first asset is the reference for fill amounts
+    pub basket: Option<Vec<AssetInfo>>,
+    /// Addresses withdrawals may be paid to; any recipient when unset
+    pub withdraw_whitelist: Option<Vec<String>>,
+    /// Contract and message executed, without funds, once the escrow is fully withdrawn
+    pub on_complete_msg: Option<(String, Binary)>,
+    /// Combined seconds the maker may push the timelock back; extensions are disabled when unset
+    pub max_total_extension: Option<u64>,
+    /// Hash applied to the secret; sha256 when unset
+    pub hash_algorithm: Option<HashAlgo>,
+    /// Staged withdrawal and cancellation windows; derived from `timelock` when unset
+    pub timelocks: Option<Timelocks>,
+    /// Match basket and safety deposit denoms ignoring case; IBC denoms always match exactly
+    pub case_insensitive_denoms: bool,
+    /// Root of a Merkle tree of per-fill secret hashes; partial fills then need `PartialWithdrawMerkle`
+    pub merkle_root: Option<String>,
+    /// Equal tranches the Merkle secrets split the deposit into, so unclaimed ones can be cancelled
+    pub tranche_count: Option<u64>,
+    /// Decimals of the quoted prices, so clients can render them; 6 when unset
+    pub price_decimals: Option<u8>,
+    /// Resolver that ordered the escrow; it may cancel the escrow alongside the maker
+    pub resolver: Option<String>,
+}
+
+#[cw_serde]
+pub struct AssetInfo {
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct IbcForwardParams {
+    pub channel: String,
+    pub receiver: String,
+    /// Seconds after the withdrawal before the transfer times out
+    pub timeout: u64,
+}
+
+#[cw_serde]
+pub struct SafetyDepositSwap {
+    /// Swap router contract receiving the deposit
+    pub router: String,
+    pub output_denom: String,
+}
+
+/// Stage boundaries of an escrow, as second offsets from `created_at`. Each stage ends at its offset:
+/// no withdrawals before `finality`, taker-only until `exclusive_withdrawal`, anyone until
+/// `public_withdrawal`, and cancellation from `cancellation`.
+#[cw_serde]
+pub struct Timelocks {
+    pub finality: u64,
+    pub exclusive_withdrawal: u64,
+    pub public_withdrawal: u64,
+    pub cancellation: u64,
+}
+
+impl Timelocks {
+    /// Stages equivalent to a single absolute timelock: open withdrawal until it, then cancellation.
+    pub fn from_timelock(created_at: u64, timelock: u64) -> Self {
+        let offset = timelock.saturating_sub(created_at);
+        Timelocks {
+            finality: 0,
+            exclusive_withdrawal: 0,
+            public_withdrawal: offset,
+            cancellation: offset,
+        }
+    }
+
+    /// The same absolute stages, measured from a start `elapsed` seconds later.
+    pub fn rebased(&self, elapsed: u64) -> Self {
+        Timelocks {
+            finality: self.finality.saturating_sub(elapsed),
+            exclusive_withdrawal: self.exclusive_withdrawal.saturating_sub(elapsed),
+            public_withdrawal: self.public_withdrawal.saturating_sub(elapsed),
+            cancellation: self.cancellation.saturating_sub(elapsed),
+        }
+    }
+}
+
+/// Execute interface expected from the safety deposit swap router
+#[cw_serde]
+pub enum RouterExecuteMsg {
+    /// Swap the attached funds into `output_denom` and send them to `recipient`
+    Swap {
+        output_denom: String,
+        recipient: String,
+    },
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Deposit native tokens to the escrow
+    /// Deposit native tokens to the escrow, by the maker or by its resolver on the maker's behalf
     Deposit {},
     /// Deposit CW20 tokens to the escrow
     Receive(Cw20ReceiveMsg),
+    /// Pull CW20 tokens the maker approved for this escrow via TransferFrom
+    DepositFrom { cw20_contract: String, amount: Uint128 },
     /// Withdraw tokens using the secret
     Withdraw { secret: String },
-    /// Cancel the escrow after timelock expires
+    /// Cancel the escrow after timelock expires; the factory may cancel at any time, the resolver while unfunded
     Cancel {},
     /// Partial withdraw for partial fills
     PartialWithdraw { 
         secret: String, 
         amount: Uint128 
     },
+    /// Partial withdraw revealing the `index`-th secret of the escrow's Merkle tree; indices must increase
+    PartialWithdrawMerkle {
+        secret: String,
+        merkle_proof: Vec<String>,
+        index: u64,
+        amount: Uint128,
+    },
+    /// Refund the listed unused Merkle tranches once the timelock expires, retiring their secrets (maker only)
+    CancelTranches { indices: Vec<u64> },
     /// Update the current price (Dutch auction)
     UpdatePrice {},
+    /// Stop the price decay and hold the auction at its minimum price (maker or resolver)
+    RestAtMinimum {},
+    /// Rotate the hashlock before the escrow is funded (maker, or the resolver on the maker's behalf)
+    UpdateSecretHash { new_secret_hash: String },
+    /// Pull a withdrawal payout that was parked after the recipient rejected it
+    ClaimWithdrawn {},
+    /// Push the timelock back by `extension` seconds, within the escrow's extension cap (maker only)
+    ExtendTimelock { extension: u64 },
+    /// Sweep a stray native denom or CW20 balance to the maker once the escrow is settled (maker only)
+    Rescue {
+        denom: Option<String>,
+        cw20_contract: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -45,37 +173,83 @@ pub enum ReceiveMsg {
     Deposit {},
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Get escrow details
     #[returns(EscrowResponse)]
     Escrow {},
+    /// Get the status transition history of this escrow
+    #[returns(HistoryResponse)]
+    History {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Get current price (Dutch auction)
     #[returns(PriceResponse)]
     CurrentPrice {},
     /// Get fill status
     #[returns(FillStatusResponse)]
     FillStatus {},
+    /// Check a candidate secret against the stored hash without withdrawing.
+    /// The secret is sent in the query, so it is visible to the queried node.
+    #[returns(CheckSecretResponse)]
+    CheckSecret { secret: String },
+    /// Parameters for building the counterparty HTLC on an EVM chain
+    #[returns(CounterpartyParamsResponse)]
+    CounterpartyParams {},
+    /// Get the parked withdrawal balance of an address
+    #[returns(ClaimableResponse)]
+    Claimable { address: String },
+    /// What a cancel would refund at the current block
+    #[returns(RefundQuoteResponse)]
+    RefundQuote {},
+    /// The messages a cancel would emit at the current block, without cancelling
+    #[returns(PreviewCancelResponse)]
+    PreviewCancel {},
 }
 
 #[cw_serde]
 pub struct EscrowResponse {
     pub maker: Addr,
     pub taker: Option<Addr>,
+    pub refund_addr: Option<Addr>,
     pub secret_hash: String,
+    /// Absolute time from which the escrow can be cancelled
     pub timelock: u64,
+    pub timelocks: Timelocks,
     pub dst_chain_id: String,
     pub dst_asset: String,
     pub dst_amount: Uint128,
+    pub src_amount: Option<Uint128>,
     pub deposited_amount: Uint128,
     pub deposited_denom: Option<String>,
     pub cw20_contract: Option<Addr>,
+    pub allowed_cw20: Option<Vec<Addr>>,
     pub status: EscrowStatus,
     pub created_at: u64,
     pub allow_partial_fill: bool,
     pub filled_amount: Uint128,
     pub remaining_amount: Uint128,
+    pub dust_threshold: Option<Uint128>,
+    pub safety_deposit: Option<Coin>,
+    pub safety_deposit_swap: Option<SafetyDepositSwap>,
+    pub safety_deposit_funded: bool,
+    pub ibc_forward: Option<IbcForwardParams>,
+    pub basket: Option<Vec<AssetInfo>>,
+    pub withdraw_whitelist: Option<Vec<Addr>>,
+    pub on_complete_msg: Option<(Addr, Binary)>,
+    pub max_total_extension: u64,
+    pub total_extension: u64,
+    pub hash_algorithm: HashAlgo,
+    pub case_insensitive_denoms: bool,
+    pub merkle_root: Option<String>,
+    pub last_used_index: Option<u64>,
+    pub tranche_count: Option<u64>,
+    pub cancelled_tranches: Vec<u64>,
 }
 
 #[cw_serde]
@@ -85,6 +259,7 @@ pub struct PriceResponse {
     pub minimum_price: Option<Uint128>,
     pub price_decay_rate: Option<Uint128>,
     pub time_elapsed: u64,
+    pub price_decimals: u8,
 }
 
 #[cw_serde]
@@ -94,8 +269,79 @@ pub struct FillStatusResponse {
     pub remaining_amount: Uint128,
     pub is_fully_filled: bool,
     pub allow_partial_fill: bool,
+    /// Per-asset fill of a basket escrow; empty for single-asset escrows
+    pub basket: Vec<AssetFillStatus>,
+}
+
+#[cw_serde]
+pub struct AssetFillStatus {
+    pub denom: String,
+    pub total_amount: Uint128,
+    pub filled_amount: Uint128,
+    pub remaining_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct CheckSecretResponse {
+    pub valid: bool,
+}
+
+/// Counterparty HTLC parameters as 0x-prefixed hex, with numbers as 32-byte uint256 words.
+#[cw_serde]
+pub struct CounterpartyParamsResponse {
+    pub chain_id: String,
+    pub asset: String,
+    pub secret_hash: String,
+    pub timelock: String,
+    pub amount: String,
+    /// Maker's canonical address bytes; matches the EVM address on Ethermint chains
+    pub recipient: String,
+}
+
+#[cw_serde]
+pub struct ClaimableResponse {
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RefundQuoteResponse {
+    /// False while the timelock is running or once the escrow is settled
+    pub cancellable: bool,
+    /// Seconds until the timelock expires; 0 once cancellable
+    pub seconds_until_cancellable: u64,
+    pub recipient: Addr,
+    /// Unfilled principal, in the deposited denom or CW20
+    pub refund_amount: Uint128,
+    /// Unfilled remainder of each basket asset
+    pub basket_refund: Vec<Coin>,
+    /// Safety deposit returned to the maker alongside the refund
+    pub safety_deposit: Option<Coin>,
+}
+
+#[cw_serde]
+pub struct PreviewCancelResponse {
+    /// False while the cancellation stage has not begun or once the escrow is settled
+    pub cancellable: bool,
+    pub recipient: Addr,
+    /// Unfilled principal returned to `recipient`; zero when not cancellable
+    pub amount: Uint128,
+    /// Asset the principal is refunded in; `None` when nothing would be refunded
+    pub asset: Option<RefundAsset>,
+    /// Messages the cancel would emit, in order, including the safety deposit refund
+    pub messages: Vec<CosmosMsg>,
+}
+
+#[cw_serde]
+pub enum RefundAsset {
+    Native { denom: String },
+    Cw20 { contract: Addr },
+    /// Unfilled remainder of each basket asset, sent together
+    Basket {},
+}
+
+/// Status history of the escrow, shared with the other escrow contracts
+pub type HistoryResponse = escrow_common::history::HistoryResponse<crate::state::EscrowStatus>;
+
 #[cw_serde]
 pub enum EscrowStatus {
     Active,
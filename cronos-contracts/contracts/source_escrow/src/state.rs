@@ -1,20 +1,28 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw_storage_plus::{Item, Map};
+use escrow_common::hash::HashAlgo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{IbcForwardParams, SafetyDepositSwap, Timelocks};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct EscrowInfo {
     pub maker: Addr,
     pub taker: Option<Addr>,
-    pub secret_hash: String,
-    pub timelock: u64,
+    pub refund_addr: Option<Addr>,
+    /// Raw 32-byte hashlock; hex only at the API boundary
+    pub secret_hash: Binary,
+    /// Withdrawal and cancellation stages, as offsets from `created_at`
+    pub timelocks: Timelocks,
     pub dst_chain_id: String,
     pub dst_asset: String,
     pub dst_amount: Uint128,
+    pub src_amount: Option<Uint128>,
     pub deposited_amount: Uint128,
     pub deposited_denom: Option<String>,
     pub cw20_contract: Option<Addr>,
+    pub allowed_cw20: Option<Vec<Addr>>,
     pub status: EscrowStatus,
     pub created_at: u64,
     // Dutch auction fields
@@ -26,6 +34,41 @@ pub struct EscrowInfo {
     pub minimum_fill_amount: Option<Uint128>,
     pub filled_amount: Uint128,
     pub remaining_amount: Uint128,
+    pub dust_threshold: Option<Uint128>,
+    // Safety deposit fields
+    pub safety_deposit: Option<Coin>,
+    pub safety_deposit_swap: Option<SafetyDepositSwap>,
+    pub safety_deposit_funded: bool,
+    // IBC forwarding of withdrawn funds
+    pub ibc_forward: Option<IbcForwardParams>,
+    // Multi-asset basket; fill amounts are expressed in the first asset
+    pub basket: Option<Vec<BasketAsset>>,
+    // Compliance restriction on withdrawal recipients
+    pub withdraw_whitelist: Option<Vec<Addr>>,
+    // Downstream call made after the final payout
+    pub on_complete_msg: Option<(Addr, Binary)>,
+    // Timelock extensions granted so far, bounded by max_total_extension
+    pub max_total_extension: u64,
+    pub total_extension: u64,
+    pub hash_algorithm: HashAlgo,
+    /// Match configured denoms against received ones ignoring case, except for IBC denoms
+    pub case_insensitive_denoms: bool,
+    /// Raw Merkle root over `hash(index || hash(secret))` leaves, for multi-resolver fills
+    pub merkle_root: Option<Binary>,
+    /// Highest secret index revealed so far in Merkle mode
+    pub last_used_index: Option<u64>,
+    /// Number of equal Merkle tranches, and those the maker has cancelled
+    pub tranche_count: Option<u64>,
+    pub cancelled_tranches: Vec<u64>,
+    /// Decimals of the auction prices, reported for display only
+    pub price_decimals: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BasketAsset {
+    pub denom: String,
+    pub amount: Uint128,
+    pub filled_amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -36,5 +79,70 @@ pub enum EscrowStatus {
     PartiallyFilled,
 }
 
+/// `EscrowInfo` as any earlier version may have stored it, read once by `migrate`. Fields added since
+/// the first release are optional or defaulted, and the hashlock may still be a hex string.
+#[derive(Deserialize)]
+pub struct EscrowInfoV1 {
+    pub maker: Addr,
+    pub taker: Option<Addr>,
+    pub refund_addr: Option<Addr>,
+    pub secret_hash: String,
+    /// Single absolute timelock of the first release, replaced by `timelocks`
+    pub timelock: Option<u64>,
+    pub timelocks: Option<Timelocks>,
+    pub dst_chain_id: String,
+    pub dst_asset: String,
+    pub dst_amount: Uint128,
+    pub src_amount: Option<Uint128>,
+    pub deposited_amount: Uint128,
+    pub deposited_denom: Option<String>,
+    pub cw20_contract: Option<Addr>,
+    pub allowed_cw20: Option<Vec<Addr>>,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+    pub initial_price: Option<Uint128>,
+    pub price_decay_rate: Option<Uint128>,
+    pub minimum_price: Option<Uint128>,
+    pub allow_partial_fill: bool,
+    pub minimum_fill_amount: Option<Uint128>,
+    pub filled_amount: Uint128,
+    pub remaining_amount: Uint128,
+    pub dust_threshold: Option<Uint128>,
+    pub safety_deposit: Option<Coin>,
+    pub safety_deposit_swap: Option<SafetyDepositSwap>,
+    #[serde(default)]
+    pub safety_deposit_funded: bool,
+    pub ibc_forward: Option<IbcForwardParams>,
+    pub basket: Option<Vec<BasketAsset>>,
+    pub withdraw_whitelist: Option<Vec<Addr>>,
+    pub on_complete_msg: Option<(Addr, Binary)>,
+    #[serde(default)]
+    pub max_total_extension: u64,
+    #[serde(default)]
+    pub total_extension: u64,
+    #[serde(default)]
+    pub hash_algorithm: HashAlgo,
+    #[serde(default)]
+    pub case_insensitive_denoms: bool,
+    pub merkle_root: Option<Binary>,
+    pub last_used_index: Option<u64>,
+    pub tranche_count: Option<u64>,
+    #[serde(default)]
+    pub cancelled_tranches: Vec<u64>,
+    pub price_decimals: Option<u8>,
+}
+
 pub const ESCROW_INFO: Item<EscrowInfo> = Item::new("escrow_info");
+/// Read-only view of `ESCROW_INFO` in its legacy shapes; only `migrate` reads through it
+pub const LEGACY_ESCROW_INFO: Item<EscrowInfoV1> = Item::new("escrow_info");
+/// Contract that instantiated the escrow; it may cancel before the timelock in an emergency
+pub const FACTORY: Item<Addr> = Item::new("factory");
+/// Resolver that ordered the escrow through the factory, when it was given one
+pub const RESOLVER: Item<Addr> = Item::new("resolver");
+/// Recipient and amount of the withdrawal payout awaiting its reply
+pub const PENDING_PAYOUT: Item<(Addr, Uint128)> = Item::new("pending_payout");
+/// CW20 contract and amount of the TransferFrom deposit awaiting its reply
+pub const PENDING_DEPOSIT: Item<(Addr, Uint128)> = Item::new("pending_deposit");
+/// Withdrawal payouts parked after the recipient rejected them
+pub const CLAIMABLE: Map<&Addr, Uint128> = Map::new("claimable");
 
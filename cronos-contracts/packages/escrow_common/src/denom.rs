@@ -0,0 +1,46 @@
+//! Comparison of configured native denoms against the denoms funds actually arrive in.
+
+const IBC_PREFIX: &str = "ibc/";
+
+/// Trims surrounding whitespace and lowercases an `ibc/` prefix; the IBC hash itself is kept as is
+pub fn normalize_denom(denom: &str) -> String {
+    let denom = denom.trim();
+    match denom.get(..IBC_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(IBC_PREFIX) => {
+            format!("{}{}", IBC_PREFIX, &denom[IBC_PREFIX.len()..])
+        }
+        _ => denom.to_string(),
+    }
+}
+
+/// Whether a received denom matches a configured one after normalization. Case is only ignored for
+/// non-IBC denoms, and only when `case_insensitive` is set.
+pub fn denoms_match(expected: &str, received: &str, case_insensitive: bool) -> bool {
+    let (expected, received) = (normalize_denom(expected), normalize_denom(received));
+    if case_insensitive && !expected.starts_with(IBC_PREFIX) && !received.starts_with(IBC_PREFIX) {
+        expected.eq_ignore_ascii_case(&received)
+    } else {
+        expected == received
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ibc_hash_case_is_preserved() {
+        let hash = "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        assert_eq!(format!("ibc/{}", hash), normalize_denom(&format!(" IBC/{} ", hash)));
+        assert!(denoms_match(&format!("IBC/{}", hash), &format!("ibc/{}", hash), false));
+        assert!(!denoms_match(&format!("ibc/{}", hash), &format!("ibc/{}", hash.to_lowercase()), true));
+    }
+
+    #[test]
+    fn native_case_only_ignored_when_requested() {
+        assert!(!denoms_match("uATOM", "uatom", false));
+        assert!(denoms_match("uATOM", "uatom", true));
+        assert!(denoms_match(" uatom", "uatom", false));
+        assert!(!denoms_match("uatom", "uosmo", true));
+    }
+}
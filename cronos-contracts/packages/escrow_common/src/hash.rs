@@ -0,0 +1,51 @@
+use cosmwasm_schema::cw_serde;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Hash function an escrow applies to the revealed secret before comparing it with the stored hash
+#[cw_serde]
+#[derive(Default)]
+pub enum HashAlgo {
+    #[default]
+    Sha256,
+    /// Matches the `keccak256(secret)` locks used by EVM-side HTLCs
+    Keccak256,
+}
+
+impl HashAlgo {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Keccak256 => "keccak256",
+        }
+    }
+
+    /// Raw 32-byte digest of `data`
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgo::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgo::Keccak256 => Keccak256::digest(data).to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn digests_match_reference_vectors() {
+        assert_eq!(
+            "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b",
+            hex(&HashAlgo::Sha256.digest(b"secret"))
+        );
+        assert_eq!(
+            "65462b0520ef7d3df61b9992ed3bea0c56ead753be7c8b3614e0ce01e4cac41b",
+            hex(&HashAlgo::Keccak256.digest(b"secret"))
+        );
+    }
+}
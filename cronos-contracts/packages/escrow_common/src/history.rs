@@ -0,0 +1,95 @@
+//! Status history the escrow contracts record for every transition, generic over each contract's status enum.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Env, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const TRANSITION_COUNT: Item<u64> = Item::new("transition_count");
+
+#[cw_serde]
+pub struct TransitionRecord<S> {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub from_status: Option<S>,
+    pub to_status: S,
+    pub actor: Addr,
+}
+
+#[cw_serde]
+pub struct HistoryResponse<S> {
+    pub transitions: Vec<TransitionRecord<S>>,
+}
+
+fn transitions<S: Serialize + DeserializeOwned>() -> Map<'static, u64, TransitionRecord<S>> {
+    Map::new("transitions")
+}
+
+/// Appends a transition to the history, numbered after the ones already recorded
+pub fn record_transition<S: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    from_status: Option<S>,
+    to_status: S,
+    actor: &Addr,
+) -> StdResult<()> {
+    let seq = TRANSITION_COUNT.may_load(storage)?.unwrap_or_default();
+    transitions().save(
+        storage,
+        seq,
+        &TransitionRecord {
+            seq,
+            timestamp: env.block.time.seconds(),
+            from_status,
+            to_status,
+            actor: actor.clone(),
+        },
+    )?;
+    TRANSITION_COUNT.save(storage, &(seq + 1))
+}
+
+/// Transitions after `start_after` in order, 10 per page by default and at most 30
+pub fn query_history<S: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<HistoryResponse<S>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let transitions: StdResult<Vec<_>> = transitions()
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect();
+
+    Ok(HistoryResponse {
+        transitions: transitions?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+
+    #[test]
+    fn transitions_are_numbered_and_paged() {
+        let mut storage = MockStorage::new();
+        let actor = Addr::unchecked("actor");
+        for (from, to) in [(None, "a"), (Some("a"), "b"), (Some("b"), "c")] {
+            let from = from.map(str::to_string);
+            record_transition(&mut storage, &mock_env(), from, to.to_string(), &actor).unwrap();
+        }
+
+        let history: HistoryResponse<String> = query_history(&storage, None, None).unwrap();
+        let seqs: Vec<u64> = history.transitions.iter().map(|record| record.seq).collect();
+        assert_eq!(vec![0, 1, 2], seqs);
+        assert_eq!(Some("b".to_string()), history.transitions[2].from_status);
+
+        let page: HistoryResponse<String> = query_history(&storage, Some(0), Some(1)).unwrap();
+        assert_eq!(1, page.transitions.len());
+        assert_eq!("b", page.transitions[0].to_status);
+    }
+}
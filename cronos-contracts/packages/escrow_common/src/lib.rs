@@ -0,0 +1,4 @@
+pub mod denom;
+pub mod hash;
+pub mod history;
+pub mod validation;
@@ -0,0 +1,182 @@
+use cosmwasm_std::Uint128;
+use thiserror::Error;
+
+/// Maximum byte length of chain IDs and asset identifiers
+pub const MAX_IDENTIFIER_LEN: usize = 128;
+/// Furthest a timelock may sit past the current block time, in seconds
+pub const MAX_TIMELOCK_HORIZON: u64 = 365 * 24 * 60 * 60;
+
+/// Parameter errors shared by the escrow contracts; each contract maps them onto its own error type.
+#[derive(Error, Debug, PartialEq)]
+pub enum ValidationError {
+    #[error("Invalid timelock")]
+    InvalidTimelock {},
+
+    #[error("Invalid dutch auction parameters")]
+    InvalidAuctionParams {},
+
+    #[error("Invalid amount")]
+    InvalidAmount {},
+
+    #[error("Minimum fill amount exceeds the order amount")]
+    MinimumFillTooLarge {},
+
+    #[error("Invalid chain or asset identifier")]
+    InvalidIdentifier {},
+
+    #[error("Invalid secret hash")]
+    InvalidSecretHash {},
+}
+
+/// Rejects an unset timelock, or one so far ahead that funds would be locked indefinitely.
+pub fn validate_timelock(timelock: u64, now: u64) -> Result<(), ValidationError> {
+    if timelock == 0 || timelock > now.saturating_add(MAX_TIMELOCK_HORIZON) {
+        return Err(ValidationError::InvalidTimelock {});
+    }
+    Ok(())
+}
+
+/// Requires the auction to start above its floor, or at it for a zero-decay, fixed-price order.
+pub fn validate_auction_params(
+    initial_price: Option<Uint128>,
+    price_decay_rate: Option<Uint128>,
+    minimum_price: Option<Uint128>,
+) -> Result<(), ValidationError> {
+    if let (Some(initial_price), Some(minimum_price)) = (initial_price, minimum_price) {
+        let fixed_price = price_decay_rate.map_or(true, |rate| rate.is_zero());
+        if initial_price < minimum_price || (initial_price == minimum_price && !fixed_price) {
+            return Err(ValidationError::InvalidAuctionParams {});
+        }
+    }
+    Ok(())
+}
+
+/// Requires the order amount, and the exact principal when one is pinned, to be non-zero.
+pub fn validate_amounts(amount: Uint128, exact_amount: Option<Uint128>) -> Result<(), ValidationError> {
+    if amount.is_zero() || exact_amount.map_or(false, |exact| exact.is_zero()) {
+        return Err(ValidationError::InvalidAmount {});
+    }
+    Ok(())
+}
+
+/// Rejects a minimum fill larger than the order, which would make partial fills impossible.
+pub fn validate_minimum_fill(
+    allow_partial_fill: bool,
+    minimum_fill_amount: Option<Uint128>,
+    total: Uint128,
+) -> Result<(), ValidationError> {
+    match minimum_fill_amount {
+        Some(minimum_fill) if allow_partial_fill && minimum_fill > total => {
+            Err(ValidationError::MinimumFillTooLarge {})
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Bounds chain IDs and asset identifiers to a short, printable charset so they cannot bloat state.
+/// The limit leaves room for `ibc/<64 hex>` denoms.
+pub fn validate_identifier(value: &str) -> Result<(), ValidationError> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ':');
+    if value.is_empty() || value.len() > MAX_IDENTIFIER_LEN || !value.chars().all(valid_char) {
+        return Err(ValidationError::InvalidIdentifier {});
+    }
+    Ok(())
+}
+
+/// Requires a hashlock in the canonical form the escrows compare against: 64 lowercase hex
+/// characters, no `0x` prefix.
+pub fn validate_secret_hash(secret_hash: &str) -> Result<(), ValidationError> {
+    let valid_char = |c: char| c.is_ascii_digit() || ('a'..='f').contains(&c);
+    if secret_hash.len() != 64 || !secret_hash.chars().all(valid_char) {
+        return Err(ValidationError::InvalidSecretHash {});
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_000_000;
+
+    #[test]
+    fn timelock_bounds() {
+        assert_eq!(Err(ValidationError::InvalidTimelock {}), validate_timelock(0, NOW));
+        assert_eq!(Ok(()), validate_timelock(1, NOW));
+        assert_eq!(Ok(()), validate_timelock(NOW + MAX_TIMELOCK_HORIZON, NOW));
+        assert_eq!(
+            Err(ValidationError::InvalidTimelock {}),
+            validate_timelock(NOW + MAX_TIMELOCK_HORIZON + 1, NOW)
+        );
+        assert_eq!(Ok(()), validate_timelock(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn auction_params_bounds() {
+        let price = |p: u128| Some(Uint128::from(p));
+        assert_eq!(Ok(()), validate_auction_params(price(200), price(1), price(100)));
+        assert_eq!(Ok(()), validate_auction_params(price(101), price(1), price(100)));
+        assert_eq!(Ok(()), validate_auction_params(price(100), price(0), price(100)));
+        assert_eq!(Ok(()), validate_auction_params(price(100), None, price(100)));
+        assert_eq!(
+            Err(ValidationError::InvalidAuctionParams {}),
+            validate_auction_params(price(100), price(1), price(100))
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidAuctionParams {}),
+            validate_auction_params(price(99), price(0), price(100))
+        );
+        assert_eq!(Ok(()), validate_auction_params(None, price(1), price(100)));
+    }
+
+    #[test]
+    fn amounts_must_be_non_zero() {
+        assert_eq!(Ok(()), validate_amounts(Uint128::one(), None));
+        assert_eq!(Ok(()), validate_amounts(Uint128::one(), Some(Uint128::one())));
+        assert_eq!(Err(ValidationError::InvalidAmount {}), validate_amounts(Uint128::zero(), None));
+        assert_eq!(
+            Err(ValidationError::InvalidAmount {}),
+            validate_amounts(Uint128::one(), Some(Uint128::zero()))
+        );
+    }
+
+    #[test]
+    fn minimum_fill_bounds() {
+        let total = Uint128::from(100u128);
+        assert_eq!(Ok(()), validate_minimum_fill(true, Some(total), total));
+        assert_eq!(Ok(()), validate_minimum_fill(true, None, total));
+        assert_eq!(Ok(()), validate_minimum_fill(false, Some(total + Uint128::one()), total));
+        assert_eq!(
+            Err(ValidationError::MinimumFillTooLarge {}),
+            validate_minimum_fill(true, Some(total + Uint128::one()), total)
+        );
+    }
+
+    #[test]
+    fn identifier_bounds() {
+        assert_eq!(Ok(()), validate_identifier("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"));
+        assert_eq!(Ok(()), validate_identifier(&"x".repeat(MAX_IDENTIFIER_LEN)));
+        assert_eq!(
+            Err(ValidationError::InvalidIdentifier {}),
+            validate_identifier(&"x".repeat(MAX_IDENTIFIER_LEN + 1))
+        );
+        assert_eq!(Err(ValidationError::InvalidIdentifier {}), validate_identifier(""));
+        assert_eq!(Err(ValidationError::InvalidIdentifier {}), validate_identifier("eth mainnet"));
+    }
+
+    #[test]
+    fn secret_hash_must_be_lowercase_hex() {
+        let hash = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+        assert_eq!(Ok(()), validate_secret_hash(hash));
+        for malformed in [
+            hash.to_uppercase(),
+            format!("0x{}", hash),
+            hash[..62].to_string(),
+            format!("{}00", hash),
+            "hash123".to_string(),
+        ] {
+            assert_eq!(Err(ValidationError::InvalidSecretHash {}), validate_secret_hash(&malformed));
+        }
+    }
+
+}